@@ -68,6 +68,18 @@ fn main() {
 
 lazy_static! {
     static ref RE: Regex = Regex::new("^// err=(.*)$").unwrap();
+    static ref PACKAGE: Regex = Regex::new(r"^package (\w+)").unwrap();
+}
+
+/// Whether the file is a program, rather than a package for the programs to import
+fn is_main(include_file: &Path) -> io::Result<bool> {
+    let buf = BufReader::new(File::open(include_file)?);
+    for line in buf.lines() {
+        if let Some(caps) = PACKAGE.captures(&line?) {
+            return Ok(&caps[1] == "main");
+        }
+    }
+    Ok(false)
 }
 
 fn generate_test_for_file(
@@ -76,6 +88,9 @@ fn generate_test_for_file(
     out: &Path,
     include_file: &Path,
 ) -> io::Result<()> {
+    if !is_main(include_file)? {
+        return Ok(());
+    }
     let f = File::open(include_file)?;
     let buf = BufReader::new(f);
     let error_str = buf.lines().find_map(|line| {
@@ -0,0 +1,541 @@
+use crate::declaration::{expression_list, name_list};
+use crate::expression::{Expression, InnerExpression};
+use crate::func::Body;
+use crate::name::Name;
+use crate::statement::{InnerStatement, Statement};
+use crate::types::Type;
+use crate::unitary_op::UniOp;
+use crate::{AstError, AstResult, Located, Parseable};
+use gor_parse::Rule;
+use pest::iterators::Pairs;
+use pest::Span;
+
+/// `if init; condition { then } else otherwise`
+#[derive(Debug)]
+pub struct IfStatement<'i> {
+    pub init: Option<Box<Statement<'i>>>,
+    pub condition: Expression<'i>,
+    pub then: Body<'i>,
+    pub otherwise: Option<Else<'i>>,
+    span: Span<'i>,
+}
+
+#[derive(Debug)]
+pub enum Else<'i> {
+    If(Box<IfStatement<'i>>),
+    Block(Body<'i>),
+}
+
+impl<'i> Parseable<'i> for IfStatement<'i> {
+    const RULE: Rule = Rule::if_statement;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs.peekable();
+        let mut init = None;
+        let mut next = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found an if without a condition"))?;
+        if pairs.peek().map(|p| p.as_rule()) == Some(Rule::expression) {
+            init = Some(Box::new(Statement::from_simple(next)?));
+            next = pairs
+                .next()
+                .ok_or(AstError::InvalidState("Found an if without a condition"))?;
+        }
+        let condition = Expression::descend(next)?;
+        let then = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found an if without a body"))?;
+        let then = Body::descend(then)?;
+        let otherwise = match pairs.next() {
+            None => None,
+            Some(pair) if pair.as_rule() == Rule::if_statement => {
+                Some(Else::If(Box::new(IfStatement::descend(pair)?)))
+            }
+            Some(pair) => Some(Else::Block(Body::descend(pair)?)),
+        };
+        Ok(IfStatement {
+            init,
+            condition,
+            then,
+            otherwise,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for IfStatement<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// `for init; condition; post { body }`
+///
+/// `for condition { body }` and `for { body }` leave out the parts they don't have, while
+/// `for k, v := range x { body }` has only a range clause.
+#[derive(Debug)]
+pub struct ForStatement<'i> {
+    pub init: Option<Box<Statement<'i>>>,
+    pub condition: Option<Expression<'i>>,
+    pub post: Option<Box<Statement<'i>>>,
+    pub range: Option<RangeClause<'i>>,
+    pub body: Body<'i>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for ForStatement<'i> {
+    const RULE: Rule = Rule::for_statement;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut init = None;
+        let mut condition = None;
+        let mut post = None;
+        let mut range = None;
+        let mut body = None;
+        for pair in pairs {
+            match pair.as_rule() {
+                Rule::expression => condition = Some(Expression::descend(pair)?),
+                Rule::range_clause => range = Some(RangeClause::descend(pair)?),
+                Rule::for_clause => {
+                    for part in pair.into_inner() {
+                        let rule = part.as_rule();
+                        let inner = match part.into_inner().next() {
+                            Some(inner) => inner,
+                            None => continue,
+                        };
+                        match rule {
+                            Rule::for_init => init = Some(Box::new(Statement::from_simple(inner)?)),
+                            Rule::for_condition => condition = Some(Expression::descend(inner)?),
+                            _ => post = Some(Box::new(Statement::from_simple(inner)?)),
+                        }
+                    }
+                }
+                Rule::block => body = Some(Body::descend(pair)?),
+                r => {
+                    return Err(AstError::InvalidRuleClass(
+                        "for clause",
+                        r,
+                        pair.as_str().to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(ForStatement {
+            init,
+            condition,
+            post,
+            range,
+            body: body.ok_or(AstError::InvalidState("Found a for without a body"))?,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for ForStatement<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// `k, v := range x`, `k, v = range x`, or `range x` on its own
+#[derive(Debug)]
+pub struct RangeClause<'i> {
+    pub targets: RangeTargets<'i>,
+    pub exp: Expression<'i>,
+    span: Span<'i>,
+}
+
+/// Where each iteration's key and value go: at most two of them, either of which may be left out
+#[derive(Debug)]
+pub enum RangeTargets<'i> {
+    /// New variables for each iteration
+    Declare(Vec<Name>),
+    /// Existing places, assigned to at the start of each iteration
+    Assign(Vec<Expression<'i>>),
+}
+
+impl<'i> Parseable<'i> for RangeClause<'i> {
+    const RULE: Rule = Rule::range_clause;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs.peekable();
+        let targets = match pairs.next_if(|p| p.as_rule() != Rule::expression) {
+            None => RangeTargets::Assign(vec![]),
+            Some(targets) => {
+                let rule = targets.as_rule();
+                let list = targets
+                    .into_inner()
+                    .next()
+                    .ok_or(AstError::InvalidState("Found a range without targets"))?;
+                match rule {
+                    Rule::range_declare => RangeTargets::Declare(name_list(list)?),
+                    _ => RangeTargets::Assign(expression_list(list)?),
+                }
+            }
+        };
+        let count = match &targets {
+            RangeTargets::Declare(names) => names.len(),
+            RangeTargets::Assign(targets) => targets.len(),
+        };
+        if count > 2 {
+            return Err(AstError::InvalidStateString(format!(
+                "Range has more than two targets: {}",
+                span.as_str()
+            )));
+        }
+        let exp = pairs.next().ok_or(AstError::InvalidState(
+            "Found a range without an expression",
+        ))?;
+        Ok(RangeClause {
+            targets,
+            exp: Expression::descend(exp)?,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for RangeClause<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// `switch init; tag { clauses }`
+///
+/// Without a tag, each case is compared against `true`.
+#[derive(Debug)]
+pub struct SwitchStatement<'i> {
+    pub init: Option<Box<Statement<'i>>>,
+    pub tag: Option<Expression<'i>>,
+    pub clauses: Vec<CaseClause<'i>>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for SwitchStatement<'i> {
+    const RULE: Rule = Rule::switch_statement;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs.peekable();
+        let mut init = None;
+        let mut tag = None;
+        if let Some(next) = pairs.next_if(|p| p.as_rule() != Rule::case_clause) {
+            match pairs.next_if(|p| p.as_rule() == Rule::expression) {
+                Some(expression) => {
+                    init = Some(Box::new(Statement::from_simple(next)?));
+                    tag = Some(Expression::descend(expression)?);
+                }
+                // A lone expression is the tag, while a lone statement of any other kind is
+                // the init of a tagless switch.
+                None if next.as_rule() == Rule::expression => {
+                    tag = Some(Expression::descend(next)?)
+                }
+                None => init = Some(Box::new(Statement::from_simple(next)?)),
+            }
+        }
+        let clauses = pairs
+            .map(CaseClause::descend)
+            .collect::<AstResult<Vec<_>>>()?;
+        if clauses.iter().filter(|c| c.values.is_none()).count() > 1 {
+            return Err(AstError::InvalidStateString(format!(
+                "Multiple defaults in switch: {}",
+                span.as_str()
+            )));
+        }
+        Ok(SwitchStatement {
+            init,
+            tag,
+            clauses,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for SwitchStatement<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// `case a, b: body`, or `default: body` when there are no values
+#[derive(Debug)]
+pub struct CaseClause<'i> {
+    pub values: Option<Vec<Expression<'i>>>,
+    pub body: Body<'i>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for CaseClause<'i> {
+    const RULE: Rule = Rule::case_clause;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs;
+        let label = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a case without a label"))?;
+        let values = match label.as_rule() {
+            Rule::case_values => {
+                let list = label
+                    .into_inner()
+                    .next()
+                    .ok_or(AstError::InvalidState("Found a case without any values"))?;
+                Some(expression_list(list)?)
+            }
+            _ => None,
+        };
+        let body = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a case without a body"))?;
+        Ok(CaseClause {
+            values,
+            body: Body::build(&body.as_span(), body.into_inner())?,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for CaseClause<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// `switch v := x.(type) { case T: body }`, choosing a clause by the dynamic type of `x`
+#[derive(Debug)]
+pub struct TypeSwitchStatement<'i> {
+    pub init: Option<Box<Statement<'i>>>,
+    /// Declared in each clause, holding the value of `subject`
+    pub binding: Option<Name>,
+    pub subject: Expression<'i>,
+    pub clauses: Vec<TypeCaseClause<'i>>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for TypeSwitchStatement<'i> {
+    const RULE: Rule = Rule::type_switch_statement;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs.peekable();
+        let init = pairs
+            .next_if(|p| p.as_rule() != Rule::type_switch_guard)
+            .map(Statement::from_simple)
+            .transpose()?
+            .map(Box::new);
+        let guard = pairs.next().ok_or(AstError::InvalidState(
+            "Found a type switch without a guard",
+        ))?;
+        let mut guard = guard.into_inner().peekable();
+        let binding = guard
+            .next_if(|p| p.as_rule() == Rule::name)
+            .map(Name::descend)
+            .transpose()?;
+        let subject = guard.next().ok_or(AstError::InvalidState(
+            "Found a type switch without an expression",
+        ))?;
+        let clauses = pairs
+            .map(TypeCaseClause::descend)
+            .collect::<AstResult<Vec<_>>>()?;
+        if clauses.iter().filter(|c| c.types.is_none()).count() > 1 {
+            return Err(AstError::InvalidStateString(format!(
+                "Multiple defaults in switch: {}",
+                span.as_str()
+            )));
+        }
+        if clauses.iter().any(|c| {
+            matches!(
+                c.body.statements.last().map(|s| &s.inner),
+                Some(InnerStatement::Fallthrough)
+            )
+        }) {
+            return Err(AstError::InvalidStateString(format!(
+                "Cannot fallthrough in type switch: {}",
+                span.as_str()
+            )));
+        }
+        Ok(TypeSwitchStatement {
+            init,
+            binding,
+            subject: Expression::descend(subject)?,
+            clauses,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for TypeSwitchStatement<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// `case T, U: body`, or `default: body` when there are no types.
+///
+/// `nil` is parsed as a type name, and matches only a nil value.
+#[derive(Debug)]
+pub struct TypeCaseClause<'i> {
+    pub types: Option<Vec<Type<'i>>>,
+    pub body: Body<'i>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for TypeCaseClause<'i> {
+    const RULE: Rule = Rule::type_case_clause;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs;
+        let label = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a case without a label"))?;
+        let types = match label.as_rule() {
+            Rule::type_case_values => Some(
+                label
+                    .into_inner()
+                    .map(Type::descend)
+                    .collect::<AstResult<Vec<_>>>()?,
+            ),
+            _ => None,
+        };
+        let body = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a case without a body"))?;
+        Ok(TypeCaseClause {
+            types,
+            body: Body::build(&body.as_span(), body.into_inner())?,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for TypeCaseClause<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// `select { clauses }`, which waits until one of its cases can send or receive
+#[derive(Debug)]
+pub struct SelectStatement<'i> {
+    pub clauses: Vec<CommClause<'i>>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for SelectStatement<'i> {
+    const RULE: Rule = Rule::select_statement;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let clauses = pairs
+            .map(CommClause::descend)
+            .collect::<AstResult<Vec<_>>>()?;
+        if clauses.iter().filter(|c| c.communication.is_none()).count() > 1 {
+            return Err(AstError::InvalidStateString(format!(
+                "Multiple defaults in select: {}",
+                span.as_str()
+            )));
+        }
+        Ok(SelectStatement {
+            clauses,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for SelectStatement<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// `case ch <- v: body`, `case v := <-ch: body`, or `default: body` when there's no communication
+#[derive(Debug)]
+pub struct CommClause<'i> {
+    pub communication: Option<Communication<'i>>,
+    pub body: Body<'i>,
+    span: Span<'i>,
+}
+
+/// The send or receive that a `select` case waits for
+#[derive(Debug)]
+pub enum Communication<'i> {
+    /// `ch <- v`
+    Send {
+        channel: Expression<'i>,
+        value: Expression<'i>,
+    },
+    /// `<-ch`, where the value and whether it was sent are stored just like a range clause's key
+    /// and value: `v, ok := <-ch` or `v, ok = <-ch`
+    Receive {
+        targets: RangeTargets<'i>,
+        channel: Expression<'i>,
+    },
+}
+
+impl<'i> Parseable<'i> for CommClause<'i> {
+    const RULE: Rule = Rule::comm_clause;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs;
+        let label = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a case without a label"))?;
+        let communication = match label.as_rule() {
+            Rule::comm_case => {
+                let statement = label.into_inner().next().ok_or(AstError::InvalidState(
+                    "Found a case without a communication",
+                ))?;
+                Some(communication(Statement::from_simple(statement)?)?)
+            }
+            _ => None,
+        };
+        let body = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a case without a body"))?;
+        Ok(CommClause {
+            communication,
+            body: Body::build(&body.as_span(), body.into_inner())?,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for CommClause<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+fn communication(statement: Statement) -> AstResult<Communication> {
+    let debug_expr = statement.as_span().as_str().to_string();
+    let (targets, values) = match statement.inner {
+        InnerStatement::Send { channel, value } => {
+            return Ok(Communication::Send { channel, value })
+        }
+        InnerStatement::Expression(value) => (RangeTargets::Assign(vec![]), vec![value]),
+        InnerStatement::ShortVarDeclaration { names, values } if names.len() <= 2 => {
+            (RangeTargets::Declare(names), values)
+        }
+        InnerStatement::Assignment {
+            targets,
+            op: None,
+            values,
+        } if targets.len() <= 2 => (RangeTargets::Assign(targets), values),
+        _ => (RangeTargets::Assign(vec![]), vec![]),
+    };
+    match <[_; 1]>::try_from(values) {
+        Ok(
+            [Expression {
+                inner:
+                    InnerExpression::UniOp {
+                        op: UniOp::Receive,
+                        exp,
+                    },
+                ..
+            }],
+        ) => Ok(Communication::Receive {
+            targets,
+            channel: *exp,
+        }),
+        _ => Err(AstError::InvalidStateString(format!(
+            "Select case must be receive, send or assign recv: {}",
+            debug_expr
+        ))),
+    }
+}
@@ -0,0 +1,217 @@
+use crate::expression::Expression;
+use crate::name::Name;
+use crate::statement::values_match;
+use crate::types::Type;
+use crate::{AstError, AstResult, Located, Parseable};
+use gor_parse::Rule;
+use pest::iterators::{Pair, Pairs};
+use pest::Span;
+
+/// A single line of a `var` declaration: `var a, b int = 1, 2`
+#[derive(Debug)]
+pub struct VarSpec<'i> {
+    pub names: Vec<Name>,
+    pub r#type: Option<Type<'i>>,
+    pub values: Vec<Expression<'i>>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for VarSpec<'i> {
+    const RULE: Rule = Rule::var_spec;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut names = vec![];
+        let mut r#type = None;
+        let mut values = vec![];
+        for pair in pairs {
+            match pair.as_rule() {
+                Rule::name_list => names = name_list(pair)?,
+                Rule::r#type => r#type = Some(Type::descend(pair)?),
+                Rule::expression_list => values = expression_list(pair)?,
+                r => {
+                    return Err(AstError::InvalidRuleClass(
+                        "var spec",
+                        r,
+                        pair.as_str().to_string(),
+                    ))
+                }
+            }
+        }
+        if !values.is_empty() && !values_match(names.len(), &values) {
+            return Err(AstError::InvalidStateString(format!(
+                "Expected {} values in declaration: {}",
+                names.len(),
+                span.as_str()
+            )));
+        }
+        Ok(VarSpec {
+            names,
+            r#type,
+            values,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for VarSpec<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// A single line of a `const` declaration: `const a, b int = 1, 2`.
+///
+/// A line within parentheses that leaves out its type and values repeats those of the line before
+/// it, where `iota` is the index of the line.
+#[derive(Debug)]
+pub struct ConstSpec<'i> {
+    pub names: Vec<Name>,
+    pub r#type: Option<Type<'i>>,
+    pub values: Vec<Expression<'i>>,
+    pub iota: usize,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for ConstSpec<'i> {
+    const RULE: Rule = Rule::const_spec;
+
+    /// Builds the line as it's written, which [const_declaration] fills in from the line before
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut names = vec![];
+        let mut r#type = None;
+        let mut values = vec![];
+        for pair in pairs {
+            match pair.as_rule() {
+                Rule::name => names.push(Name::descend(pair)?),
+                Rule::const_type => r#type = Some(Type::descend(wrapped(pair)?)?),
+                Rule::const_values => values = expression_list(wrapped(pair)?)?,
+                r => {
+                    return Err(AstError::InvalidRuleClass(
+                        "const spec",
+                        r,
+                        pair.as_str().to_string(),
+                    ))
+                }
+            }
+        }
+        if r#type.is_some() && values.is_empty() {
+            return Err(AstError::InvalidStateString(format!(
+                "Missing init expr for const declaration: {}",
+                span.as_str()
+            )));
+        }
+        Ok(ConstSpec {
+            names,
+            r#type,
+            values,
+            iota: 0,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for ConstSpec<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// A single type in a `type` declaration: `type T struct { ... }`, or `type A = B` for an alias
+#[derive(Debug)]
+pub struct TypeSpec<'i> {
+    pub name: Name,
+    pub alias: bool,
+    pub r#type: Type<'i>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for TypeSpec<'i> {
+    const RULE: Rule = Rule::type_spec;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut name = None;
+        let mut alias = false;
+        let mut r#type = None;
+        for pair in pairs {
+            match pair.as_rule() {
+                Rule::name => name = Some(Name::descend(pair)?),
+                Rule::alias => alias = true,
+                Rule::r#type => r#type = Some(Type::descend(pair)?),
+                r => {
+                    return Err(AstError::InvalidRuleClass(
+                        "type spec",
+                        r,
+                        pair.as_str().to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(TypeSpec {
+            name: name.ok_or(AstError::InvalidState("Found a type without a name"))?,
+            alias,
+            r#type: r#type.ok_or(AstError::InvalidState("Found a type without a definition"))?,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for TypeSpec<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// All the specs in a `type` declaration, whether grouped in parentheses or not.
+pub(crate) fn type_declaration(pair: Pair<Rule>) -> AstResult<Vec<TypeSpec>> {
+    pair.into_inner().map(TypeSpec::descend).collect()
+}
+
+/// All the specs in a `var` declaration, whether grouped in parentheses or not.
+pub(crate) fn var_declaration(pair: Pair<Rule>) -> AstResult<Vec<VarSpec>> {
+    pair.into_inner().map(VarSpec::descend).collect()
+}
+
+/// All the specs in a `const` declaration, with any that leave out their values repeating the spec
+/// before them.
+pub(crate) fn const_declaration(pair: Pair<Rule>) -> AstResult<Vec<ConstSpec>> {
+    let mut specs: Vec<ConstSpec> = vec![];
+    for (iota, pair) in pair.into_inner().enumerate() {
+        let mut spec = ConstSpec::descend(pair)?;
+        spec.iota = iota;
+        if spec.values.is_empty() {
+            let previous = specs.last().ok_or_else(|| {
+                AstError::InvalidStateString(format!(
+                    "Missing init expr for const declaration: {}",
+                    spec.span.as_str()
+                ))
+            })?;
+            spec.r#type = previous.r#type.clone();
+            spec.values = previous.values.clone();
+        }
+        // Unlike `var`, there's no call that could give several values
+        if spec.names.len() != spec.values.len() {
+            return Err(AstError::InvalidStateString(format!(
+                "Expected {} values in declaration: {}",
+                spec.names.len(),
+                spec.span.as_str()
+            )));
+        }
+        specs.push(spec);
+    }
+    Ok(specs)
+}
+
+/// The single rule inside one that only exists to switch whitespace handling back on
+fn wrapped(pair: Pair<Rule>) -> AstResult<Pair<Rule>> {
+    pair.into_inner()
+        .next()
+        .ok_or(AstError::InvalidState("Found an empty wrapper rule"))
+}
+
+pub(crate) fn name_list(pair: Pair<Rule>) -> AstResult<Vec<Name>> {
+    pair.into_inner().map(Name::descend).collect()
+}
+
+pub(crate) fn expression_list(pair: Pair<Rule>) -> AstResult<Vec<Expression>> {
+    pair.into_inner().map(Expression::descend).collect()
+}
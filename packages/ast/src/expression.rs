@@ -1,5 +1,7 @@
 use crate::binary_op::BinOp;
+use crate::func::FuncLiteral;
 use crate::name::Name;
+use crate::types::{InnerType, Type};
 use crate::unitary_op::UniOp;
 use crate::{expect_rule, AstError};
 use crate::{AstErrorContext, AstResult, Parseable};
@@ -8,17 +10,82 @@ use gor_parse::Rule;
 use gor_parse::PRECEDENCE;
 use pest::iterators::{Pair, Pairs};
 use pest::Span;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, OnceLock};
 
 #[must_use = "expressions are side-effect free unless evaluated"]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone)]
 pub struct Expression<'i> {
     pub inner: InnerExpression<'i>,
     pub span: Span<'i>,
+    /// Whether the expression is constant, once it's been evaluated
+    constant: OnceLock<bool>,
 }
 
 impl Expression<'_> {
-    const fn new<'i>(span: Span<'i>, inner: InnerExpression<'i>) -> Expression<'i> {
-        Expression { span, inner }
+    pub(crate) const fn new<'i>(span: Span<'i>, inner: InnerExpression<'i>) -> Expression<'i> {
+        Expression {
+            span,
+            inner,
+            constant: OnceLock::new(),
+        }
+    }
+
+    /// Whether the expression was found to be constant when it was first evaluated.
+    ///
+    /// A name refers to the same declaration every time the expression that it's in is evaluated,
+    /// so the expression is either constant every time or never is.
+    pub fn is_constant(&self) -> Option<bool> {
+        self.constant.get().copied()
+    }
+
+    /// Remember whether the expression is constant, after evaluating it for the first time
+    pub fn set_constant(&self, constant: bool) {
+        let _ = self.constant.set(constant);
+    }
+
+    /// Whether the expression may produce a second, boolean, value to show whether it succeeded:
+    /// `v, ok := x.(T)`, `v, ok := m[k]` or `v, ok := <-ch`
+    pub fn is_comma_ok(&self) -> bool {
+        matches!(
+            self.inner,
+            InnerExpression::TypeAssertion { .. }
+                | InnerExpression::Index { .. }
+                | InnerExpression::UniOp {
+                    op: UniOp::Receive,
+                    ..
+                }
+        )
+    }
+
+    /// Whether the expression is a function or method call, which may give several results
+    pub fn is_call(&self) -> bool {
+        match &self.inner {
+            InnerExpression::Call { .. } | InnerExpression::Apply { .. } => true,
+            InnerExpression::BinOp {
+                op: BinOp::Dot,
+                right,
+                ..
+            } => matches!(right.inner, InnerExpression::Call { .. }),
+            _ => false,
+        }
+    }
+}
+
+/// Whether the expression has been found to be constant is left out, as it isn't part of its syntax
+impl Debug for Expression<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Expression")
+            .field("inner", &self.inner)
+            .field("span", &self.span)
+            .finish()
+    }
+}
+
+/// Expressions are equal if their syntax is, whether or not they've been evaluated
+impl PartialEq for Expression<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner && self.span == other.span
     }
 }
 
@@ -31,23 +98,74 @@ pub enum InnerExpression<'i> {
         right: Box<Expression<'i>>,
     },
     String(String),
-    Number(i64),
+    /// An integer literal, which can't be negative since `-` is an operator
+    Number(u64),
+    /// A floating-point literal as written, without underscores, so that it can be evaluated
+    /// exactly
+    Float(String),
+    /// An imaginary literal's coefficient, as a decimal literal without underscores
+    Imaginary(String),
     Name(Name),
     UniOp {
         op: UniOp,
         exp: Box<Expression<'i>>,
     },
+    /// `f(a, b)`, or `f(a, s...)` to spread the last argument over a variadic parameter
     Call {
         name: Name,
         parameters: Vec<Expression<'i>>,
+        spread: bool,
+    },
+    /// A call of anything other than a name, like `fs[0](x)` or `func() { ... }()`
+    Apply {
+        function: Box<Expression<'i>>,
+        parameters: Vec<Expression<'i>>,
+        spread: bool,
+    },
+    /// `func(x int) int { return x + y }`, which is shared by every closure created from it
+    Func(Arc<FuncLiteral<'i>>),
+    /// `x.(T)`
+    TypeAssertion {
+        exp: Box<Expression<'i>>,
+        r#type: Type<'i>,
     },
+    /// `T{a: 1, b: 2}` or `T{1, 2}`
+    Composite {
+        r#type: Type<'i>,
+        elements: Vec<Element<'i>>,
+    },
+    /// `{1, 2}` as an element or key of a composite literal, which has the enclosing literal's
+    /// element or key type
+    Elided(Vec<Element<'i>>),
+    /// `s[i]`
+    Index {
+        exp: Box<Expression<'i>>,
+        index: Box<Expression<'i>>,
+    },
+    /// `s[low:high]` or `s[low:high:max]`
+    Slice {
+        exp: Box<Expression<'i>>,
+        low: Option<Box<Expression<'i>>>,
+        high: Option<Box<Expression<'i>>>,
+        max: Option<Box<Expression<'i>>>,
+    },
+    /// A type passed to a builtin, like `[]int` in `make([]int, 3)`
+    Type(Type<'i>),
+}
+
+/// A single element of a composite literal, with its key if it has one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element<'i> {
+    pub key: Option<Expression<'i>>,
+    pub value: Expression<'i>,
 }
 
 impl<'i> Parseable<'i> for Expression<'i> {
     const RULE: Rule = Rule::expression;
     fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Expression<'i>> {
         let exp = term_precedence(pairs)?;
-        if &exp.span == span {
+        // Pest includes any trailing whitespace or comments in the outer span
+        if exp.span.start() == span.start() && exp.span.end() <= span.end() {
             Ok(exp)
         } else {
             Err(AstError::InvalidStateString(format!(
@@ -59,7 +177,12 @@ impl<'i> Parseable<'i> for Expression<'i> {
 }
 
 fn term_precedence(pairs: Pairs<Rule>) -> AstResult<Expression> {
-    PRECEDENCE.climb(pairs, term_primary, term_infix)
+    PRECEDENCE
+        .map_primary(term_primary)
+        .map_prefix(term_prefix)
+        .map_postfix(term_postfix)
+        .map_infix(term_infix)
+        .parse(pairs)
 }
 
 fn term_primary(pair: Pair<Rule>) -> AstResult<Expression> {
@@ -74,6 +197,29 @@ fn term_primary(pair: Pair<Rule>) -> AstResult<Expression> {
     Ok(Expression::new(span, expr))
 }
 
+/// The value of an integer literal, where a leading `0` alone makes it octal
+fn integer(literal: &str) -> AstResult<u64> {
+    let digits = literal.replace('_', "");
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some(_) if digits.starts_with('0') => (8, &digits[1..]),
+        _ => (10, &digits[..]),
+    };
+    Ok(u64::from_str_radix(digits, radix)?)
+}
+
+/// The coefficient of an imaginary literal in decimal, where `0123i` is `123i` for backward
+/// compatibility rather than octal
+fn imaginary(literal: &str) -> AstResult<String> {
+    let coefficient = literal.trim_end_matches('i').replace('_', "");
+    Ok(match coefficient.get(..2) {
+        Some("0x" | "0X" | "0b" | "0B" | "0o" | "0O") => integer(&coefficient)?.to_string(),
+        _ => coefficient,
+    })
+}
+
 fn next_to_inner(next: Pair<Rule>) -> AstResult<InnerExpression> {
     Ok(match next.as_rule() {
         Rule::string => {
@@ -85,13 +231,13 @@ fn next_to_inner(next: Pair<Rule>) -> AstResult<InnerExpression> {
                 ))?;
             InnerExpression::String(string_inner.as_str().to_owned())
         }
-        Rule::number => InnerExpression::Number(next.as_str().parse()?),
+        Rule::number => InnerExpression::Number(integer(next.as_str())?),
+        Rule::float => InnerExpression::Float(next.as_str().replace('_', "")),
+        Rule::imaginary => InnerExpression::Imaginary(imaginary(next.as_str())?),
         Rule::expression => Expression::descend(next)?.inner,
         Rule::name => InnerExpression::Name(Name::descend(next)?),
-        Rule::unitary_op => InnerExpression::UniOp {
-            op: UniOp::Negate,
-            exp: Box::new(Expression::parse(next.into_inner())?),
-        },
+        Rule::composite_literal => composite_literal(next)?,
+        Rule::func_literal => InnerExpression::Func(Arc::new(FuncLiteral::descend(next)?)),
         Rule::call => {
             let mut call = next.into_inner();
             let name = call
@@ -99,10 +245,12 @@ fn next_to_inner(next: Pair<Rule>) -> AstResult<InnerExpression> {
                 .ok_or(AstError::InvalidState("Found a call with no name"))?;
             expect_rule(&name, Rule::name)?;
             let name: Name = name.as_str().into();
-            let parameters: Vec<Expression> = call
-                .map(|expression| Ok(Expression::descend(expression)?) as AstResult<Expression>)
-                .collect::<AstResult<_>>()?;
-            InnerExpression::Call { name, parameters }
+            let (parameters, spread) = arguments(call)?;
+            InnerExpression::Call {
+                name,
+                parameters,
+                spread,
+            }
         }
         r => {
             return Err(AstError::RuleMismatch {
@@ -114,6 +262,164 @@ fn next_to_inner(next: Pair<Rule>) -> AstResult<InnerExpression> {
     })
 }
 
+/// The arguments to a call, and whether the last is spread with `...`
+fn arguments(pairs: Pairs<Rule>) -> AstResult<(Vec<Expression>, bool)> {
+    let mut spread = false;
+    let mut parameters = vec![];
+    for argument in pairs {
+        match argument.as_rule() {
+            Rule::ellipsis => spread = true,
+            Rule::r#type => parameters.push(Expression::new(
+                argument.as_span(),
+                InnerExpression::Type(Type::descend(argument)?),
+            )),
+            _ => parameters.push(Expression::descend(argument)?),
+        }
+    }
+    Ok((parameters, spread))
+}
+
+fn composite_literal(pair: Pair<Rule>) -> AstResult<InnerExpression> {
+    let mut pairs = pair.into_inner();
+    let r#type = pairs.next().ok_or(AstError::InvalidState(
+        "Found a composite literal without a type",
+    ))?;
+    let r#type = Type::build(&r#type.as_span(), r#type.into_inner())?;
+    let value = pairs.next().ok_or(AstError::InvalidState(
+        "Found a composite literal without a value",
+    ))?;
+    let elements = literal_elements(value)?;
+    // Slice and array literals may give indexes for some elements but not others
+    let keyed = elements.iter().filter(|e| e.key.is_some()).count();
+    if keyed != 0
+        && keyed != elements.len()
+        && !matches!(r#type.inner, InnerType::Slice(_) | InnerType::Array { .. })
+    {
+        return Err(AstError::InvalidState(
+            "Mixture of keyed and positional elements in composite literal",
+        ));
+    }
+    Ok(InnerExpression::Composite { r#type, elements })
+}
+
+/// The elements of a literal value, any of which may be a literal with its type elided
+fn literal_elements(value: Pair<Rule>) -> AstResult<Vec<Element>> {
+    value
+        .into_inner()
+        .map(|keyed| {
+            let mut pairs = keyed.into_inner().rev();
+            let value = pairs
+                .next()
+                .ok_or(AstError::InvalidState("Found an empty element"))?;
+            Ok(Element {
+                key: pairs.next().map(literal_element).transpose()?,
+                value: literal_element(value)?,
+            })
+        })
+        .collect()
+}
+
+fn literal_element(pair: Pair<Rule>) -> AstResult<Expression> {
+    match pair.as_rule() {
+        Rule::literal_value => Ok(Expression::new(
+            pair.as_span(),
+            InnerExpression::Elided(literal_elements(pair)?),
+        )),
+        _ => Expression::descend(pair),
+    }
+}
+
+fn term_prefix<'i>(
+    op: Pair<'i, Rule>,
+    exp: AstResult<Expression<'i>>,
+) -> AstResult<Expression<'i>> {
+    let exp = exp.with_rule(op.as_rule())?;
+    let span = op.as_span().start_pos().span(&exp.span.end_pos());
+    let op = UniOp::try_from(op.as_rule())?;
+    Ok(Expression::new(
+        span,
+        InnerExpression::UniOp {
+            op,
+            exp: Box::new(exp),
+        },
+    ))
+}
+
+fn term_postfix<'i>(
+    exp: AstResult<Expression<'i>>,
+    op: Pair<'i, Rule>,
+) -> AstResult<Expression<'i>> {
+    let exp = Box::new(exp.with_rule(op.as_rule())?);
+    let span = exp.span.start_pos().span(&op.as_span().end_pos());
+    let inner = match op.as_rule() {
+        Rule::type_assertion => {
+            let r#type = op.into_inner().next().ok_or(AstError::InvalidState(
+                "Found a type assertion without a type",
+            ))?;
+            InnerExpression::TypeAssertion {
+                exp,
+                r#type: Type::descend(r#type)?,
+            }
+        }
+        Rule::index => {
+            let index = op
+                .into_inner()
+                .next()
+                .ok_or(AstError::InvalidState("Found an index without a value"))?;
+            InnerExpression::Index {
+                exp,
+                index: Box::new(Expression::descend(index)?),
+            }
+        }
+        Rule::arguments => {
+            let (parameters, spread) = arguments(op.into_inner())?;
+            InnerExpression::Apply {
+                function: exp,
+                parameters,
+                spread,
+            }
+        }
+        Rule::slice => {
+            let mut low = None;
+            let mut high = None;
+            let mut max = None;
+            for bound in op.into_inner() {
+                let slot = match bound.as_rule() {
+                    Rule::slice_low => &mut low,
+                    Rule::slice_high => &mut high,
+                    _ => &mut max,
+                };
+                *slot = bound
+                    .into_inner()
+                    .next()
+                    .map(Expression::descend)
+                    .transpose()?
+                    .map(Box::new);
+            }
+            if max.is_some() && high.is_none() {
+                return Err(AstError::InvalidStateString(format!(
+                    "Middle index required in 3-index slice: {}",
+                    span.as_str()
+                )));
+            }
+            InnerExpression::Slice {
+                exp,
+                low,
+                high,
+                max,
+            }
+        }
+        r => {
+            return Err(AstError::InvalidRuleClass(
+                "postfix operator",
+                r,
+                op.as_str().to_string(),
+            ))
+        }
+    };
+    Ok(Expression::new(span, inner))
+}
+
 fn term_infix<'i>(
     left: AstResult<Expression<'i>>,
     op: Pair<'i, Rule>,
@@ -1,17 +1,19 @@
 use crate::name::Name;
 use crate::statement::Statement;
+use crate::types::{InnerType, Type};
 use crate::{expect_rule, AstError, AstResult, Located, Parseable};
 use gor_core::{Function, Member};
 use gor_parse::Rule;
 use pest::iterators::Pairs;
 use pest::Span;
 
-#[allow(unused)]
 #[derive(Debug)]
 pub struct SourceFunction<'i> {
-    pub(crate) name: Name,
-    signature: Signature<'i>,
-    body: Body<'i>,
+    /// Methods have a receiver, declared before the name: `func (t *T) name()`
+    pub receiver: Option<Parameter<'i>>,
+    pub name: Name,
+    pub signature: Signature<'i>,
+    pub body: Body<'i>,
     span: Span<'i>,
 }
 
@@ -19,7 +21,17 @@ impl<'i> Parseable<'i> for SourceFunction<'i> {
     const RULE: Rule = Rule::func;
 
     fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
-        let mut pairs = pairs;
+        let mut pairs = pairs.peekable();
+        let receiver = pairs
+            .next_if(|p| p.as_rule() == Rule::receiver)
+            .map(|receiver| {
+                let param = receiver
+                    .into_inner()
+                    .next()
+                    .ok_or(AstError::InvalidState("Found a receiver without a type"))?;
+                Parameter::descend(param)
+            })
+            .transpose()?;
         let next = pairs
             .next()
             .ok_or(AstError::InvalidState("No name in func"))?;
@@ -35,41 +47,129 @@ impl<'i> Parseable<'i> for SourceFunction<'i> {
             .ok_or(AstError::InvalidState("No body in func"))?;
         expect_rule(&next, Rule::block)?;
         let body = Body::descend(next)?;
-        Ok(SourceFunction {
+        let function = SourceFunction {
+            receiver,
             name,
             signature,
             body,
-            span: span.clone(),
-        })
+            span: *span,
+        };
+        if function.receiver.is_some() && function.receiver_base().is_none() {
+            return Err(AstError::InvalidStateString(format!(
+                "Invalid receiver type for method {}",
+                function.name
+            )));
+        }
+        Ok(function)
+    }
+}
+
+impl<'i> SourceFunction<'i> {
+    /// For a method, the name of the type it's declared on, and whether the receiver is a pointer
+    pub fn receiver_base(&self) -> Option<(Name, bool)> {
+        let r#type = &self.receiver.as_ref()?.r#type;
+        match &r#type.inner {
+            InnerType::Name(name) => Some((*name, false)),
+            InnerType::Pointer(pointee) => match &pointee.inner {
+                InnerType::Name(name) => Some((*name, true)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<'i> Located<'i> for SourceFunction<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
     }
 }
 
 impl<'i> Member for SourceFunction<'i> {}
 impl<'i> Function<'i> for SourceFunction<'i> {}
 
+/// A function literal: `func(x int) int { return x }`
 #[derive(Debug)]
+pub struct FuncLiteral<'i> {
+    pub signature: Signature<'i>,
+    pub body: Body<'i>,
+    span: Span<'i>,
+}
+
+impl<'i> Parseable<'i> for FuncLiteral<'i> {
+    const RULE: Rule = Rule::func_literal;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs;
+        let signature = pairs
+            .next()
+            .ok_or(AstError::InvalidState("No signature in func literal"))?;
+        expect_rule(&signature, Rule::signature)?;
+        let body = pairs
+            .next()
+            .ok_or(AstError::InvalidState("No body in func literal"))?;
+        expect_rule(&body, Rule::block)?;
+        Ok(FuncLiteral {
+            signature: Signature::descend(signature)?,
+            body: Body::descend(body)?,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Located<'i> for FuncLiteral<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+/// Function literals are the same if they're the same source
+impl PartialEq for FuncLiteral<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.span == other.span
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameter<'i> {
+    pub name: Option<Name>,
+    pub r#type: Type<'i>,
+    /// `xs ...T`, which takes any number of arguments as a `[]T`
+    pub variadic: bool,
     span: Span<'i>,
 }
 
 impl<'i> Parseable<'i> for Parameter<'i> {
     const RULE: Rule = Rule::param;
 
-    fn build(_span: &Span<'i>, _pairs: Pairs<'i, Rule>) -> AstResult<Self> {
-        unimplemented!("Nothing needs parameters yet")
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs.peekable();
+        let name = pairs
+            .next_if(|p| p.as_rule() == Rule::name)
+            .map(Name::descend)
+            .transpose()?;
+        let variadic = pairs.next_if(|p| p.as_rule() == Rule::ellipsis).is_some();
+        let r#type = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a parameter with no type"))?;
+        Ok(Parameter {
+            name,
+            r#type: Type::descend(r#type)?,
+            variadic,
+            span: *span,
+        })
     }
 }
 
 impl<'i> Located<'i> for Parameter<'i> {
     fn as_span(&self) -> Span<'i> {
-        self.span.clone()
+        self.span
     }
 }
 
-#[allow(unused)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameters<'i> {
-    parameters: Vec<Parameter<'i>>,
+    pub parameters: Vec<Parameter<'i>>,
     span: Span<'i>,
 }
 
@@ -77,26 +177,66 @@ impl<'i> Parseable<'i> for Parameters<'i> {
     const RULE: Rule = Rule::params;
 
     fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
-        let parameters = pairs
+        let mut parameters = pairs
             .map(Parameter::descend)
             .collect::<AstResult<Vec<Parameter>>>()?;
+        if parameters.iter().any(|p| p.name.is_some()) {
+            group_parameter_names(&mut parameters)?;
+        }
+        if let Some((_, fixed)) = parameters.split_last() {
+            if let Some(parameter) = fixed.iter().find(|p| p.variadic) {
+                return Err(AstError::InvalidStateString(format!(
+                    "Can only use ... with final parameter: {}",
+                    parameter.span.as_str()
+                )));
+            }
+        }
         Ok(Self {
-            span: span.clone(),
+            span: *span,
             parameters,
         })
     }
 }
 
+/// In a list of named parameters, `a, b int` gives both `a` and `b` the type `int`.
+///
+/// The grammar can't tell a lone name from a type, so we find those that were parsed as types and
+/// turn them into names with the type of the next parameter that has one.
+fn group_parameter_names(parameters: &mut [Parameter]) -> AstResult<()> {
+    let mut group_type: Option<Type> = None;
+    for parameter in parameters.iter_mut().rev() {
+        if parameter.name.is_some() {
+            group_type = Some(parameter.r#type.clone());
+            continue;
+        }
+        let name = match parameter.r#type.inner {
+            InnerType::Name(name) => name,
+            _ => {
+                return Err(AstError::InvalidStateString(format!(
+                    "Mixed named and unnamed parameters: {}",
+                    parameter.span.as_str()
+                )))
+            }
+        };
+        let r#type = group_type.clone().ok_or_else(|| {
+            AstError::InvalidStateString(format!("Parameter {} has no type", name))
+        })?;
+        parameter.name = Some(name);
+        parameter.r#type = r#type;
+    }
+    Ok(())
+}
+
 impl<'i> Located<'i> for Parameters<'i> {
     fn as_span(&self) -> Span<'i> {
-        self.span.clone()
+        self.span
     }
 }
 
-#[allow(unused)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Signature<'i> {
-    parameters: Parameters<'i>,
+    pub parameters: Parameters<'i>,
+    pub results: Parameters<'i>,
     span: Span<'i>,
 }
 
@@ -109,23 +249,64 @@ impl<'i> Parseable<'i> for Signature<'i> {
             .next()
             .ok_or(AstError::InvalidState("No parameters in signature"))?;
         let parameters = Parameters::descend(next)?;
+        let results = match pairs.next() {
+            Some(result) => {
+                let result_span = result.as_span();
+                let inner = result
+                    .into_inner()
+                    .next()
+                    .ok_or(AstError::InvalidState("Found an empty result"))?;
+                match inner.as_rule() {
+                    Rule::params => Parameters::descend(inner)?,
+                    _ => Parameters {
+                        parameters: vec![Parameter {
+                            name: None,
+                            r#type: Type::descend(inner)?,
+                            variadic: false,
+                            span: result_span,
+                        }],
+                        span: result_span,
+                    },
+                }
+            }
+            None => Parameters {
+                parameters: vec![],
+                span: span.end_pos().span(&span.end_pos()),
+            },
+        };
+        if let Some(result) = results.parameters.iter().find(|p| p.variadic) {
+            return Err(AstError::InvalidStateString(format!(
+                "Can't use ... with a result: {}",
+                result.span.as_str()
+            )));
+        }
         Ok(Self {
-            span: span.clone(),
+            span: *span,
             parameters,
+            results,
         })
     }
 }
 
+impl<'i> Signature<'i> {
+    /// Whether the final parameter takes any number of arguments
+    pub fn is_variadic(&self) -> bool {
+        self.parameters
+            .parameters
+            .last()
+            .is_some_and(|p| p.variadic)
+    }
+}
+
 impl<'i> Located<'i> for Signature<'i> {
     fn as_span(&self) -> Span<'i> {
-        self.span.clone()
+        self.span
     }
 }
 
 #[derive(Debug)]
 pub struct Body<'i> {
-    #[allow(dead_code)]
-    statements: Vec<Statement<'i>>,
+    pub statements: Vec<Statement<'i>>,
     span: Span<'i>,
 }
 
@@ -138,13 +319,13 @@ impl<'i> Parseable<'i> for Body<'i> {
             .collect::<AstResult<Vec<Statement>>>()?;
         Ok(Body {
             statements,
-            span: span.clone(),
+            span: *span,
         })
     }
 }
 
 impl<'i> Located<'i> for Body<'i> {
     fn as_span(&self) -> Span<'i> {
-        self.span.clone()
+        self.span
     }
 }
@@ -0,0 +1,160 @@
+use crate::declaration::VarSpec;
+use crate::name::Name;
+use crate::{AstError, AstResult};
+use gor_parse::Rule;
+use pest::iterators::Pair;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The names that a package-level declaration refers to, found from its tokens.
+///
+/// Names the declaration declares for itself, like parameters and local variables, aren't counted,
+/// even where they're used before they're declared or in a different scope.
+#[derive(Debug, Default)]
+pub(crate) struct References {
+    /// Names used on their own, which may be package-level variables or functions
+    names: HashSet<Name>,
+    /// Names selected with `x.name`, which may be methods
+    selected: HashSet<Name>,
+}
+
+impl References {
+    pub(crate) fn of(pair: Pair<Rule>) -> Self {
+        let mut references = References::default();
+        let mut declared = HashSet::new();
+        let mut selecting = false;
+        for pair in std::iter::once(pair.clone()).chain(pair.into_inner().flatten()) {
+            declared.extend(declared_names(&pair));
+            match pair.as_rule() {
+                Rule::dot => selecting = true,
+                // The selected name is inside a term, and maybe a call
+                Rule::term | Rule::call => {}
+                Rule::name if selecting => {
+                    references.selected.insert(Name::from(pair.as_str()));
+                    selecting = false;
+                }
+                Rule::name => {
+                    references.names.insert(Name::from(pair.as_str()));
+                }
+                _ => selecting = false,
+            }
+        }
+        references.names.retain(|name| !declared.contains(name));
+        references
+    }
+
+    pub(crate) fn extend(&mut self, other: References) {
+        self.names.extend(other.names);
+        self.selected.extend(other.selected);
+    }
+}
+
+/// The names that a single rule declares, if it's a declaration
+fn declared_names(pair: &Pair<Rule>) -> Vec<Name> {
+    let names = |pair: Pair<Rule>| -> Vec<Name> {
+        pair.into_inner()
+            .filter(|p| p.as_rule() == Rule::name)
+            .map(|p| Name::from(p.as_str()))
+            .collect()
+    };
+    match pair.as_rule() {
+        Rule::short_var_declaration | Rule::range_declare | Rule::var_spec | Rule::field_decl => {
+            pair.clone()
+                .into_inner()
+                .find(|p| p.as_rule() == Rule::name_list)
+                .map(names)
+                .unwrap_or_default()
+        }
+        Rule::param | Rule::type_switch_guard | Rule::labeled_statement => names(pair.clone()),
+        _ => vec![],
+    }
+}
+
+/// The order in which Go initializes a package's variables: repeatedly the earliest in declaration
+/// order that doesn't depend on any that are still uninitialized.
+///
+/// A variable depends on those its initial values refer to, either directly or through the
+/// functions and methods they refer to.  A method is assumed to be referred to by any selector
+/// with its name.
+pub(crate) fn initialization_order(
+    specs: &[VarSpec],
+    references: &[References],
+    functions: &HashMap<Name, References>,
+    methods: &HashMap<Name, References>,
+) -> AstResult<Vec<usize>> {
+    let variables: HashMap<Name, usize> = specs
+        .iter()
+        .enumerate()
+        .flat_map(|(index, spec)| spec.names.iter().map(move |name| (*name, index)))
+        .filter(|(name, _)| !name.is_blank())
+        .collect();
+    let dependencies: Vec<BTreeSet<usize>> = references
+        .iter()
+        .map(|references| {
+            let mut dependencies = BTreeSet::new();
+            let mut visited = HashSet::new();
+            let mut pending = vec![references];
+            while let Some(references) = pending.pop() {
+                for name in &references.names {
+                    if let Some(&index) = variables.get(name) {
+                        dependencies.insert(index);
+                    } else if let Some(function) = functions.get(name) {
+                        if visited.insert((false, *name)) {
+                            pending.push(function);
+                        }
+                    }
+                }
+                for name in &references.selected {
+                    if let Some(method) = methods.get(name) {
+                        if visited.insert((true, *name)) {
+                            pending.push(method);
+                        }
+                    }
+                }
+            }
+            dependencies
+        })
+        .collect();
+
+    let mut initialized = vec![false; specs.len()];
+    let mut order = Vec::with_capacity(specs.len());
+    while order.len() < specs.len() {
+        let ready = (0..specs.len()).find(|&index| {
+            !initialized[index]
+                && dependencies[index]
+                    .iter()
+                    .all(|&dependency| initialized[dependency])
+        });
+        let Some(index) = ready else {
+            return Err(cycle(specs, &dependencies, &initialized));
+        };
+        initialized[index] = true;
+        order.push(index);
+    }
+    Ok(order)
+}
+
+/// Follow the uninitialized dependencies of the first variable that couldn't be initialized until
+/// they lead back to one already seen
+fn cycle(specs: &[VarSpec], dependencies: &[BTreeSet<usize>], initialized: &[bool]) -> AstError {
+    let mut path: Vec<usize> = vec![];
+    let mut current = initialized.iter().position(|done| !done);
+    while let Some(index) = current {
+        if let Some(start) = path.iter().position(|&seen| seen == index) {
+            path.drain(..start);
+            path.push(index);
+            break;
+        }
+        path.push(index);
+        current = dependencies[index]
+            .iter()
+            .copied()
+            .find(|&dependency| !initialized[dependency]);
+    }
+    let name = |index: usize| specs[index].names[0];
+    AstError::InitializationCycle(
+        path.windows(2)
+            .map(|pair| format!("{} refers to {}", name(pair[0]), name(pair[1])))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
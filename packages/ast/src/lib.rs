@@ -1,6 +1,5 @@
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     missing_debug_implementations,
@@ -9,7 +8,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unreachable_pub,
     unused,
@@ -56,6 +54,9 @@ pub enum AstError {
         error: Box<AstError>,
         context: String,
     },
+    /// Package-level variables whose initial values depend on each other
+    #[error("Initialization cycle: {0}")]
+    InitializationCycle(String),
     #[error("Failed to parse an integer")]
     IntError(#[from] ParseIntError),
 }
@@ -108,17 +109,24 @@ pub trait Located<'i> {
 ///
 /// Currently all are infix operators.
 pub mod binary_op;
+/// AST for control structures
+pub mod control;
+/// AST for Declarations
+pub mod declaration;
 /// AST for Expressions
 pub mod expression;
 /// AST for Functions
 pub mod func;
+mod initialization;
 /// AST for Modules
 pub mod module;
 /// An interned String usable as a name
 pub mod name;
 /// AST for Statements
 pub mod statement;
-/// The unitary `-` operation
+/// AST for Types
+pub mod types;
+/// Prefix operations, like `-` and `!`
 pub mod unitary_op;
 
 fn expect_rule(pair: &Pair<Rule>, expected: Rule) -> AstResult<()> {
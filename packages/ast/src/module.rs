@@ -1,4 +1,6 @@
-use crate::func::SourceFunction;
+use crate::declaration::{const_declaration, type_declaration, ConstSpec, TypeSpec, VarSpec};
+use crate::func::{FuncLiteral, SourceFunction};
+use crate::initialization::{initialization_order, References};
 use crate::name::Name;
 use crate::{AstError, AstResult, Parseable};
 use gor_parse::Rule;
@@ -13,6 +15,18 @@ pub struct SourceModule<'i> {
     pub package: Name,
     pub imports: Vec<Name>,
     functions: HashMap<Name, Box<SourceFunction<'i>>>,
+    /// Methods, keyed by the name of their receiver's base type
+    methods: HashMap<Name, HashMap<Name, Box<SourceFunction<'i>>>>,
+    types: HashMap<Name, TypeSpec<'i>>,
+    constants: Vec<ConstSpec<'i>>,
+    /// The spec that declares each constant, and the constant's position in it
+    constant_names: HashMap<Name, (usize, usize)>,
+    /// Package-level variables, in the order they're initialized
+    variables: Vec<VarSpec<'i>>,
+    /// The `init` functions, which may be declared any number of times
+    inits: Vec<SourceFunction<'i>>,
+    /// Every function literal, keyed by where it starts in the source
+    literals: HashMap<usize, FuncLiteral<'i>>,
 }
 
 impl<'s: 'i, 'i> Parseable<'s> for SourceModule<'i> {
@@ -27,13 +41,68 @@ impl<'i> SourceModule<'i> {
     pub fn function(&self, name: Name) -> Option<&SourceFunction<'i>> {
         self.functions.get(&name).map(|b| b.as_ref())
     }
+
+    pub fn method(&self, r#type: Name, name: Name) -> Option<&SourceFunction<'i>> {
+        self.methods
+            .get(&r#type)
+            .and_then(|methods| methods.get(&name))
+            .map(|b| b.as_ref())
+    }
+
+    /// The names of the types that have methods declared on them
+    pub fn method_receivers(&self) -> impl Iterator<Item = Name> + '_ {
+        self.methods.keys().copied()
+    }
+
+    pub fn type_spec(&self, name: Name) -> Option<&TypeSpec<'i>> {
+        self.types.get(&name)
+    }
+
+    /// The spec that declares the constant with this name, along with its position in the spec
+    pub fn constant(&self, name: Name) -> Option<(&ConstSpec<'i>, usize)> {
+        self.constant_names
+            .get(&name)
+            .map(|&(spec, index)| (&self.constants[spec], index))
+    }
+
+    /// The specs declaring package-level variables, in the order Go initializes them: each comes
+    /// after any others its values depend on
+    pub fn variables(&self) -> impl Iterator<Item = &VarSpec<'i>> {
+        self.variables.iter()
+    }
+
+    /// The `init` functions, in the order they're declared
+    pub fn init_functions(&self) -> impl Iterator<Item = &SourceFunction<'i>> {
+        self.inits.iter()
+    }
+
+    /// The function literal that starts at `offset` in the source, so that a closure can find its
+    /// literal again without borrowing from the module
+    pub fn literal(&self, offset: usize) -> Option<&FuncLiteral<'i>> {
+        self.literals.get(&offset)
+    }
 }
 
 fn primary<'s: 'i, 'i>(module: Pairs<'s, Rule>) -> AstResult<SourceModule<'i>> {
     let mut package = None;
     let mut imports = vec![];
     let mut functions: HashMap<Name, Box<SourceFunction<'i>>> = HashMap::new();
+    let mut methods: HashMap<Name, HashMap<Name, Box<SourceFunction<'i>>>> = HashMap::new();
+    let mut types: HashMap<Name, TypeSpec<'i>> = HashMap::new();
+    let mut constants = vec![];
+    let mut constant_names = HashMap::new();
+    let mut variables = vec![];
+    let mut inits = vec![];
+    let mut variable_references = vec![];
+    let mut function_references = HashMap::new();
+    let mut method_references: HashMap<Name, References> = HashMap::new();
+    let mut literals = HashMap::new();
     for pair in module {
+        for literal in pair.clone().into_inner().flatten() {
+            if literal.as_rule() == Rule::func_literal {
+                literals.insert(literal.as_span().start(), FuncLiteral::descend(literal)?);
+            }
+        }
         match pair.as_rule() {
             Rule::package => {
                 let name = pair
@@ -58,8 +127,55 @@ fn primary<'s: 'i, 'i>(module: Pairs<'s, Rule>) -> AstResult<SourceModule<'i>> {
                 imports.push(Name::from(name.as_str()));
             }
             Rule::func => {
+                let references = References::of(pair.clone());
                 let func = SourceFunction::descend(pair)?;
-                functions.insert(func.name, Box::new(func));
+                match func.receiver_base() {
+                    Some((r#type, _)) => {
+                        method_references
+                            .entry(func.name)
+                            .or_default()
+                            .extend(references);
+                        methods
+                            .entry(r#type)
+                            .or_default()
+                            .insert(func.name, Box::new(func));
+                    }
+                    None if func.name == "init".into() => {
+                        if !func.signature.parameters.parameters.is_empty()
+                            || !func.signature.results.parameters.is_empty()
+                        {
+                            return Err(AstError::InvalidState(
+                                "func init must have no arguments and no return values",
+                            ));
+                        }
+                        inits.push(func);
+                    }
+                    None => {
+                        function_references.insert(func.name, references);
+                        functions.insert(func.name, Box::new(func));
+                    }
+                }
+            }
+            Rule::var_declaration => {
+                for pair in pair.into_inner() {
+                    variable_references.push(References::of(pair.clone()));
+                    variables.push(VarSpec::descend(pair)?);
+                }
+            }
+            Rule::type_declaration => {
+                for spec in type_declaration(pair)? {
+                    types.insert(spec.name, spec);
+                }
+            }
+            Rule::const_declaration => {
+                for spec in const_declaration(pair)? {
+                    for (index, name) in spec.names.iter().enumerate() {
+                        if !name.is_blank() {
+                            constant_names.insert(*name, (constants.len(), index));
+                        }
+                    }
+                    constants.push(spec);
+                }
             }
             Rule::EOI => {}
             r => {
@@ -71,12 +187,30 @@ fn primary<'s: 'i, 'i>(module: Pairs<'s, Rule>) -> AstResult<SourceModule<'i>> {
             }
         }
     }
+    let order = initialization_order(
+        &variables,
+        &variable_references,
+        &function_references,
+        &method_references,
+    )?;
+    let mut variables: Vec<_> = variables.into_iter().map(Some).collect();
+    let variables = order
+        .into_iter()
+        .filter_map(|index| variables[index].take())
+        .collect();
     match package {
         None => Err(AstError::InvalidState("Module must have package set")),
         Some(package) => Ok(SourceModule {
             package,
             imports,
             functions,
+            methods,
+            types,
+            constants,
+            constant_names,
+            variables,
+            inits,
+            literals,
         }),
     }
 }
@@ -18,6 +18,11 @@ macro_rules! and_then {
 }
 
 impl Name {
+    /// Whether this is the blank identifier `_`, which may be assigned to but never read
+    pub fn is_blank(&self) -> bool {
+        &*self.0 == "_"
+    }
+
     pub fn from_quoted(name: &str) -> Option<Name> {
         and_then!(
             name,
@@ -1,20 +1,63 @@
-use crate::expression::Expression;
+use crate::binary_op::BinOp;
+use crate::control::{
+    ForStatement, IfStatement, SelectStatement, SwitchStatement, TypeSwitchStatement,
+};
+use crate::declaration::{expression_list, name_list, var_declaration, VarSpec};
+use crate::expression::{Expression, InnerExpression};
+use crate::name::Name;
 use crate::{AstError, AstResult, Located, Parseable};
 use gor_parse::Rule;
-use pest::iterators::Pairs;
+use pest::iterators::{Pair, Pairs};
 use pest::Span;
 
 #[derive(Debug)]
 pub enum InnerStatement<'i> {
     Expression(Expression<'i>),
-    Assignment,
+    /// `a, b = c, d`, or `a op= b` for a binary operator `op`.
+    ///
+    /// `a++` and `a--` are treated as `a += 1` and `a -= 1`.
+    Assignment {
+        targets: Vec<Expression<'i>>,
+        op: Option<BinOp>,
+        values: Vec<Expression<'i>>,
+    },
+    /// `a, b := c, d`
+    ShortVarDeclaration {
+        names: Vec<Name>,
+        values: Vec<Expression<'i>>,
+    },
+    VarDeclaration(Vec<VarSpec<'i>>),
+    If(IfStatement<'i>),
+    For(ForStatement<'i>),
+    Switch(SwitchStatement<'i>),
+    TypeSwitch(TypeSwitchStatement<'i>),
+    Select(SelectStatement<'i>),
+    /// `label: statement`, where the label may be the target of a `break` or `continue`
+    Labeled {
+        label: Name,
+        statement: Box<Statement<'i>>,
+    },
+    Break(Option<Name>),
+    Continue(Option<Name>),
+    /// Only valid as the last statement of a `case` clause
+    Fallthrough,
+    /// `ch <- v`
+    Send {
+        channel: Expression<'i>,
+        value: Expression<'i>,
+    },
+    /// `go f(x)`, which runs the call in a new goroutine
+    Go(Expression<'i>),
+    /// `defer f(x)`, which calls `f(x)` when the surrounding function returns or panics
+    Defer(Expression<'i>),
     Func,
+    /// `return`, `return x` or `return x, y`
+    Return(Vec<Expression<'i>>),
 }
 
 #[derive(Debug)]
 pub struct Statement<'i> {
-    #[allow(dead_code)]
-    inner: InnerStatement<'i>,
+    pub inner: InnerStatement<'i>,
     span: Span<'i>,
 }
 
@@ -22,32 +65,203 @@ impl<'i> Parseable<'i> for Statement<'i> {
     const RULE: Rule = Rule::statement;
 
     fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
-        let debug_expr = span.as_str().to_string();
         let mut inner = pairs;
         let next = inner
             .next()
-            .ok_or(AstError::InvalidState("No parameters in signature"))?;
-        let inner_statement = match next.as_rule() {
-            Rule::expression => InnerStatement::Expression(Expression::descend(next)?),
-            Rule::assignment => InnerStatement::Assignment,
-            Rule::func => InnerStatement::Func,
-            r => {
-                return Err(AstError::InvalidRuleClass(
-                    "expression, assignment, func",
-                    r,
-                    debug_expr,
-                ))
-            }
-        };
+            .ok_or(AstError::InvalidState("Found an empty statement"))?;
+        Ok(Statement {
+            inner: inner_statement(next)?,
+            span: *span,
+        })
+    }
+}
+
+impl<'i> Statement<'i> {
+    /// Simple statements, such as the init clause of an `if`, aren't wrapped in a `statement` rule
+    pub(crate) fn from_simple(pair: Pair<'i, Rule>) -> AstResult<Self> {
+        let span = pair.as_span();
         Ok(Statement {
-            inner: inner_statement,
-            span: span.clone(),
+            inner: inner_statement(pair)?,
+            span,
         })
     }
 }
 
+fn inner_statement(next: Pair<Rule>) -> AstResult<InnerStatement> {
+    let debug_expr = next.as_str().to_string();
+    Ok(match next.as_rule() {
+        Rule::expression => InnerStatement::Expression(Expression::descend(next)?),
+        Rule::assignment => assignment(next)?,
+        Rule::inc_dec => inc_dec(next)?,
+        Rule::send_statement => {
+            let mut pairs = next.into_inner();
+            let channel = pairs
+                .next()
+                .ok_or(AstError::InvalidState("Found a send without a channel"))?;
+            let value = pairs
+                .next()
+                .ok_or(AstError::InvalidState("Found a send without a value"))?;
+            InnerStatement::Send {
+                channel: Expression::descend(channel)?,
+                value: Expression::descend(value)?,
+            }
+        }
+        Rule::short_var_declaration => {
+            let mut pairs = next.into_inner();
+            let names = pairs
+                .next()
+                .ok_or(AstError::InvalidState("Found a declaration without names"))?;
+            let values = pairs
+                .next()
+                .ok_or(AstError::InvalidState("Found a declaration without values"))?;
+            let names = name_list(names)?;
+            let values = expression_list(values)?;
+            if !values_match(names.len(), &values) {
+                return Err(AstError::InvalidStateString(format!(
+                    "Expected {} values in declaration: {}",
+                    names.len(),
+                    debug_expr
+                )));
+            }
+            InnerStatement::ShortVarDeclaration { names, values }
+        }
+        Rule::var_declaration => InnerStatement::VarDeclaration(var_declaration(next)?),
+        Rule::if_statement => InnerStatement::If(IfStatement::descend(next)?),
+        Rule::for_statement => InnerStatement::For(ForStatement::descend(next)?),
+        Rule::switch_statement => InnerStatement::Switch(SwitchStatement::descend(next)?),
+        Rule::type_switch_statement => {
+            InnerStatement::TypeSwitch(TypeSwitchStatement::descend(next)?)
+        }
+        Rule::select_statement => InnerStatement::Select(SelectStatement::descend(next)?),
+        Rule::labeled_statement => {
+            let mut pairs = next.into_inner();
+            let label = pairs.next().ok_or(AstError::InvalidState(
+                "Found a labeled statement without a label",
+            ))?;
+            let statement = pairs.next().ok_or(AstError::InvalidState(
+                "Found a labeled statement without a statement",
+            ))?;
+            InnerStatement::Labeled {
+                label: Name::descend(label)?,
+                statement: Box::new(Statement::descend(statement)?),
+            }
+        }
+        Rule::break_statement => {
+            InnerStatement::Break(next.into_inner().next().map(Name::descend).transpose()?)
+        }
+        Rule::continue_statement => {
+            InnerStatement::Continue(next.into_inner().next().map(Name::descend).transpose()?)
+        }
+        Rule::fallthrough_statement => InnerStatement::Fallthrough,
+        Rule::go_statement => {
+            let call = next.into_inner().next().ok_or(AstError::InvalidState(
+                "Found a go statement without a call",
+            ))?;
+            let call = Expression::descend(call)?;
+            if !call.is_call() {
+                return Err(AstError::InvalidStateString(format!(
+                    "Expression in go must be function call: {}",
+                    debug_expr
+                )));
+            }
+            InnerStatement::Go(call)
+        }
+        Rule::defer_statement => {
+            let call = next.into_inner().next().ok_or(AstError::InvalidState(
+                "Found a defer statement without a call",
+            ))?;
+            let call = Expression::descend(call)?;
+            if !call.is_call() {
+                return Err(AstError::InvalidStateString(format!(
+                    "Expression in defer must be function call: {}",
+                    debug_expr
+                )));
+            }
+            InnerStatement::Defer(call)
+        }
+        Rule::func => InnerStatement::Func,
+        Rule::return_statement => InnerStatement::Return(match next.into_inner().next() {
+            Some(values) => expression_list(values)?,
+            None => vec![],
+        }),
+        r => return Err(AstError::InvalidRuleClass(
+            "expression, assignment, send, declaration, if, for, switch, select, label, branch, go, func, return",
+            r,
+            debug_expr,
+        )),
+    })
+}
+
+fn assignment(pair: Pair<Rule>) -> AstResult<InnerStatement> {
+    let debug_expr = pair.as_str().to_string();
+    let mut pairs = pair.into_inner();
+    let targets = pairs.next().ok_or(AstError::InvalidState(
+        "Found an assignment without targets",
+    ))?;
+    let targets = expression_list(targets)?;
+    let mut next = pairs
+        .next()
+        .ok_or(AstError::InvalidState("Found an assignment without values"))?;
+    let mut op = None;
+    if next.as_rule() == Rule::compound_assign {
+        let inner = next.into_inner().next().ok_or(AstError::InvalidState(
+            "Found an assignment without an operator",
+        ))?;
+        op = Some(BinOp::try_from(inner.as_rule())?);
+        next = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found an assignment without values"))?;
+    }
+    let values = expression_list(next)?;
+    let matched = match op {
+        Some(_) => targets.len() == 1 && values.len() == 1,
+        None => values_match(targets.len(), &values),
+    };
+    if !matched {
+        return Err(AstError::InvalidStateString(format!(
+            "Mismatched assignment: {}",
+            debug_expr
+        )));
+    }
+    Ok(InnerStatement::Assignment {
+        targets,
+        op,
+        values,
+    })
+}
+
+/// Whether there's a value for each of `count` names or targets, allowing for `v, ok := x.(T)`
+/// and for a call that returns several results
+pub(crate) fn values_match(count: usize, values: &[Expression]) -> bool {
+    match values {
+        [value] if count > 1 => value.is_call() || (count == 2 && value.is_comma_ok()),
+        _ => values.len() == count,
+    }
+}
+
+fn inc_dec(pair: Pair<Rule>) -> AstResult<InnerStatement> {
+    let mut pairs = pair.into_inner();
+    let target = pairs.next().ok_or(AstError::InvalidState(
+        "Found an increment without a target",
+    ))?;
+    let target = Expression::descend(target)?;
+    let op = pairs.next().ok_or(AstError::InvalidState(
+        "Found an increment without an operator",
+    ))?;
+    let one = Expression::new(op.as_span(), InnerExpression::Number(1));
+    let op = match op.as_rule() {
+        Rule::increment => BinOp::Add,
+        _ => BinOp::Sub,
+    };
+    Ok(InnerStatement::Assignment {
+        targets: vec![target],
+        op: Some(op),
+        values: vec![one],
+    })
+}
+
 impl<'i> Located<'i> for Statement<'i> {
     fn as_span(&self) -> Span<'i> {
-        self.span.clone()
+        self.span
     }
 }
@@ -1,5 +1,13 @@
+use crate::binary_op::BinOp;
+use crate::control::{Communication, Else, RangeTargets};
+use crate::declaration::TypeSpec;
 use crate::expression::{Expression, InnerExpression};
+use crate::func::{Body, SourceFunction};
+use crate::module::SourceModule;
 use crate::name::Name;
+use crate::statement::{InnerStatement, Statement};
+use crate::types::{ChannelDirection, InnerType, InterfaceElement};
+use crate::unitary_op::UniOp;
 use crate::Parseable;
 use anyhow::{anyhow, Context, Result};
 use gor_parse::{parse, Rule};
@@ -7,7 +15,7 @@ use pest::iterators::Pairs;
 use pretty_assertions::assert_eq;
 
 #[track_caller]
-fn parse_expression(input: &str) -> Result<Pairs<Rule>> {
+fn parse_expression(input: &str) -> Result<Pairs<'_, Rule>> {
     let p = parse(Rule::expression, input)?;
     let first = p.peek().context("Expected a parse")?;
     assert_eq!(first.as_span().start(), 0);
@@ -47,6 +55,65 @@ fn parse_with_escaped_slash() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn parse_integer_literals() -> Result<()> {
+    for (input, expected) in [
+        ("1_000_000", 1_000_000),
+        ("0xFF", 255),
+        ("0b1010", 10),
+        ("0o17", 15),
+        ("017", 15),
+        ("0", 0),
+        ("0xFFFF_FFFF_FFFF_FFFF", u64::MAX),
+    ] {
+        let e = Expression::parse(parse_expression(input)?)?;
+        assert_eq!(InnerExpression::Number(expected), e.inner, "{}", input);
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_float_literals() -> Result<()> {
+    for (input, expected) in [
+        ("1.5", "1.5"),
+        ("1.", "1."),
+        (".25", ".25"),
+        ("1e9", "1e9"),
+        ("6.022_140e+23", "6.022140e+23"),
+        ("1E-3", "1E-3"),
+    ] {
+        let e = Expression::parse(parse_expression(input)?)?;
+        assert_eq!(
+            InnerExpression::Float(expected.to_string()),
+            e.inner,
+            "{}",
+            input
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_imaginary_literals() -> Result<()> {
+    for (input, expected) in [
+        ("3i", "3"),
+        ("0123i", "0123"),
+        ("1.5e3i", "1.5e3"),
+        (".5i", ".5"),
+        ("0x1Fi", "31"),
+        ("1_000i", "1000"),
+    ] {
+        let e = Expression::parse(parse_expression(input)?)?;
+        assert_eq!(
+            InnerExpression::Imaginary(expected.to_string()),
+            e.inner,
+            "{}",
+            input
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn parse_call() -> Result<()> {
     let p = parse_expression("foo()")?;
@@ -54,7 +121,8 @@ fn parse_call() -> Result<()> {
     assert_eq!(
         InnerExpression::Call {
             name: "foo".into(),
-            parameters: vec![]
+            parameters: vec![],
+            spread: false,
         },
         e.inner
     );
@@ -65,7 +133,10 @@ fn parse_call() -> Result<()> {
 fn parse_call_with_params() -> Result<()> {
     let p = parse_expression("foo(1,2)")?;
     let e = Expression::parse(p)?;
-    if let InnerExpression::Call { name, parameters } = e.inner {
+    if let InnerExpression::Call {
+        name, parameters, ..
+    } = e.inner
+    {
         assert_eq!(Name::from("foo"), name);
         assert_eq!(2, parameters.len());
         assert_eq!(InnerExpression::Number(1), parameters[0].inner);
@@ -76,6 +147,1049 @@ fn parse_call_with_params() -> Result<()> {
     }
 }
 
+#[test]
+fn parse_dot_binds_tightest() -> Result<()> {
+    let e = Expression::parse(parse_expression("a.b + c")?)?;
+    match e.inner {
+        InnerExpression::BinOp {
+            left,
+            op: BinOp::Add,
+            ..
+        } => match left.inner {
+            InnerExpression::BinOp { op: BinOp::Dot, .. } => Ok(()),
+            left => Err(anyhow!("Expected a.b: {:?}", left)),
+        },
+        inner => Err(anyhow!("Expected a sum: {:?}", inner)),
+    }
+}
+
+fn parse_function(input: &str) -> Result<SourceFunction<'_>> {
+    Ok(SourceFunction::parse(parse(Rule::func, input)?)?)
+}
+
+fn parameter_names(function: &SourceFunction) -> Vec<Option<String>> {
+    function
+        .signature
+        .parameters
+        .parameters
+        .iter()
+        .map(|p| p.name.map(|n| n.to_string()))
+        .collect()
+}
+
+fn parameter_types<'i>(function: &SourceFunction<'i>) -> Vec<InnerType<'i>> {
+    function
+        .signature
+        .parameters
+        .parameters
+        .iter()
+        .map(|p| p.r#type.inner.clone())
+        .collect()
+}
+
+#[test]
+fn parse_grouped_parameters() -> Result<()> {
+    let f = parse_function("func f(a, b int, c string) {}")?;
+    assert_eq!(
+        vec![Some("a".into()), Some("b".into()), Some("c".into())],
+        parameter_names(&f)
+    );
+    assert_eq!(
+        vec![
+            InnerType::Name("int".into()),
+            InnerType::Name("int".into()),
+            InnerType::Name("string".into())
+        ],
+        parameter_types(&f)
+    );
+    Ok(())
+}
+
+#[test]
+fn parse_unnamed_parameters() -> Result<()> {
+    let f = parse_function("func f(int, fmt.Stringer) {}")?;
+    assert_eq!(vec![None, None], parameter_names(&f));
+    assert_eq!(
+        vec![
+            InnerType::Name("int".into()),
+            InnerType::Qualified {
+                package: "fmt".into(),
+                name: "Stringer".into()
+            }
+        ],
+        parameter_types(&f)
+    );
+    Ok(())
+}
+
+#[test]
+fn parse_mixed_parameters() {
+    assert!(parse_function("func f(a, fmt.Stringer, b int) {}").is_err());
+}
+
+#[test]
+fn parse_single_result() -> Result<()> {
+    let f = parse_function("func f() int { return 1 }")?;
+    let results = &f.signature.results.parameters;
+    assert_eq!(1, results.len());
+    assert_eq!(None, results[0].name);
+    assert_eq!(InnerType::Name("int".into()), results[0].r#type.inner);
+    Ok(())
+}
+
+#[test]
+fn parse_named_results() -> Result<()> {
+    let f = parse_function("func f() (x, y int, err error) { return }")?;
+    let results = &f.signature.results.parameters;
+    assert_eq!(
+        vec![Some("x".into()), Some("y".into()), Some("err".into())],
+        results.iter().map(|p| p.name).collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn parse_no_result() -> Result<()> {
+    let f = parse_function("func f() {}")?;
+    assert!(f.signature.results.parameters.is_empty());
+    Ok(())
+}
+
+#[test]
+fn parse_method() -> Result<()> {
+    let f = parse_function("func (c *Counter) Inc(by int) { c.n += by }")?;
+    assert_eq!(Name::from("Inc"), f.name);
+    assert_eq!(
+        Some(Name::from("c")),
+        f.receiver.as_ref().and_then(|r| r.name)
+    );
+    assert_eq!(Some((Name::from("Counter"), true)), f.receiver_base());
+    let f = parse_function("func (Counter) Zero() int { return 0 }")?;
+    assert_eq!(None, f.receiver.as_ref().and_then(|r| r.name));
+    assert_eq!(Some((Name::from("Counter"), false)), f.receiver_base());
+    Ok(())
+}
+
+#[test]
+fn parse_invalid_receiver() {
+    assert!(parse_function("func (c **Counter) Inc() {}").is_err());
+}
+
+#[test]
+fn keywords_are_not_names() {
+    assert!(parse(Rule::name, "return").is_err());
+    assert!(parse(Rule::name, "returned").is_ok());
+}
+
+fn parse_statement(input: &str) -> Result<Statement<'_>> {
+    Ok(Statement::parse(parse(Rule::statement, input)?)?)
+}
+
+#[test]
+fn parse_compound_assignment() -> Result<()> {
+    let s = parse_statement("x &^= 3")?;
+    if let InnerStatement::Assignment {
+        targets,
+        op,
+        values,
+    } = s.inner
+    {
+        assert_eq!(InnerExpression::Name("x".into()), targets[0].inner);
+        assert_eq!(Some(BinOp::BitClear), op);
+        assert_eq!(InnerExpression::Number(3), values[0].inner);
+        Ok(())
+    } else {
+        Err(anyhow!("Expected an assignment: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_increment() -> Result<()> {
+    let s = parse_statement("x++")?;
+    if let InnerStatement::Assignment { op, values, .. } = s.inner {
+        assert_eq!(Some(BinOp::Add), op);
+        assert_eq!(InnerExpression::Number(1), values[0].inner);
+        Ok(())
+    } else {
+        Err(anyhow!("Expected an assignment: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_short_var_declaration() -> Result<()> {
+    let s = parse_statement("a, b := 1, c")?;
+    if let InnerStatement::ShortVarDeclaration { names, values } = s.inner {
+        assert_eq!(vec![Name::from("a"), Name::from("b")], names);
+        assert_eq!(2, values.len());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a declaration: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_var_declaration() -> Result<()> {
+    let s = parse_statement("var (\n a, b int\n c = 1\n)")?;
+    if let InnerStatement::VarDeclaration(specs) = s.inner {
+        assert_eq!(2, specs.len());
+        assert_eq!(vec![Name::from("a"), Name::from("b")], specs[0].names);
+        assert!(specs[0].values.is_empty());
+        assert!(specs[1].r#type.is_none());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a declaration: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_if_else_chain() -> Result<()> {
+    let s = parse_statement("if x := f(); x > 0 { a() } else if x < 0 { b() } else { c() }")?;
+    if let InnerStatement::If(statement) = s.inner {
+        assert!(statement.init.is_some());
+        assert_eq!(1, statement.then.statements.len());
+        match statement.otherwise {
+            Some(Else::If(statement)) => {
+                assert!(statement.init.is_none());
+                assert!(matches!(statement.otherwise, Some(Else::Block(_))));
+            }
+            e => return Err(anyhow!("Expected an else if: {:?}", e)),
+        }
+        Ok(())
+    } else {
+        Err(anyhow!("Expected an if: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_for_clause() -> Result<()> {
+    let s = parse_statement("for i := 0; i < 10; i++ { print(i) }")?;
+    if let InnerStatement::For(statement) = s.inner {
+        assert!(statement.init.is_some());
+        assert!(statement.condition.is_some());
+        assert!(statement.post.is_some());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a for: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_for_clause_parts_optional() -> Result<()> {
+    let s = parse_statement("for ; ; {}")?;
+    if let InnerStatement::For(statement) = s.inner {
+        assert!(statement.init.is_none());
+        assert!(statement.condition.is_none());
+        assert!(statement.post.is_none());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a for: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_for_condition() -> Result<()> {
+    let s = parse_statement("for x < 3 { x++ }")?;
+    if let InnerStatement::For(statement) = s.inner {
+        assert!(statement.init.is_none());
+        assert!(statement.condition.is_some());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a for: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_labeled_break() -> Result<()> {
+    let s = parse_statement("outer: for { break outer }")?;
+    if let InnerStatement::Labeled { label, statement } = s.inner {
+        assert_eq!(Name::from("outer"), label);
+        if let InnerStatement::For(statement) = statement.inner {
+            assert!(matches!(
+                statement.body.statements[0].inner,
+                InnerStatement::Break(Some(_))
+            ));
+            return Ok(());
+        }
+    }
+    Err(anyhow!("Expected a labeled for"))
+}
+
+#[test]
+fn parse_switch() -> Result<()> {
+    let s = parse_statement("switch x := f(); x { case 1, 2: g(); fallthrough\n default: }")?;
+    if let InnerStatement::Switch(statement) = s.inner {
+        assert!(statement.init.is_some());
+        assert!(statement.tag.is_some());
+        assert_eq!(2, statement.clauses.len());
+        assert_eq!(2, statement.clauses[0].values.as_ref().map_or(0, Vec::len));
+        assert!(matches!(
+            statement.clauses[0].body.statements[1].inner,
+            InnerStatement::Fallthrough
+        ));
+        assert!(statement.clauses[1].values.is_none());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a switch: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_switch_init_without_tag() -> Result<()> {
+    let s = parse_statement("switch x := f(); { case x > 1: }")?;
+    if let InnerStatement::Switch(statement) = s.inner {
+        assert!(statement.init.is_some());
+        assert!(statement.tag.is_none());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a switch: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_switch_multiple_defaults() {
+    assert!(parse_statement("switch { default: \n default: }").is_err());
+}
+
+#[test]
+fn parse_type_switch() -> Result<()> {
+    let s = parse_statement(
+        "switch v := x.y.(type) { case int, fmt.Stringer: return v\n case nil:\n default: }",
+    )?;
+    if let InnerStatement::TypeSwitch(switch) = s.inner {
+        assert_eq!(Some(Name::from("v")), switch.binding);
+        assert!(matches!(
+            switch.subject.inner,
+            InnerExpression::BinOp { op: BinOp::Dot, .. }
+        ));
+        assert_eq!(3, switch.clauses.len());
+        let types = switch.clauses[0].types.as_ref().context("Expected types")?;
+        assert!(matches!(types[1].inner, InnerType::Qualified { .. }));
+        assert!(switch.clauses[2].types.is_none());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a type switch: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_type_switch_without_binding() -> Result<()> {
+    let s = parse_statement("switch f(); x.(type) {}")?;
+    if let InnerStatement::TypeSwitch(switch) = s.inner {
+        assert!(switch.init.is_some());
+        assert!(switch.binding.is_none());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a type switch: {:?}", s))
+    }
+}
+
+#[test]
+fn parse_type_switch_fallthrough() {
+    assert!(parse_statement("switch x.(type) { case int: fallthrough\n default: }").is_err());
+}
+
+#[test]
+fn parse_index_and_slice() -> Result<()> {
+    let e = Expression::parse(parse_expression("-a.b[i][1:n:m]")?)?;
+    let InnerExpression::UniOp { exp, .. } = e.inner else {
+        return Err(anyhow!("Expected a negation: {:?}", e));
+    };
+    let InnerExpression::Slice {
+        exp,
+        low,
+        high,
+        max,
+    } = exp.inner
+    else {
+        return Err(anyhow!("Expected a slice: {:?}", exp));
+    };
+    assert!(low.is_some() && high.is_some() && max.is_some());
+    assert!(matches!(exp.inner, InnerExpression::Index { .. }));
+    let e = Expression::parse(parse_expression("s[:]")?)?;
+    assert!(matches!(
+        e.inner,
+        InnerExpression::Slice {
+            low: None,
+            high: None,
+            max: None,
+            ..
+        }
+    ));
+    Ok(())
+}
+
+#[test]
+fn parse_slice_missing_high() {
+    assert!(Expression::parse(parse_expression("s[1::3]").unwrap()).is_err());
+}
+
+#[test]
+fn parse_make() -> Result<()> {
+    let e = Expression::parse(parse_expression("make([][]int, n)")?)?;
+    if let InnerExpression::Call { parameters, .. } = e.inner {
+        if let InnerExpression::Type(r#type) = &parameters[0].inner {
+            assert!(matches!(r#type.inner, InnerType::Slice(_)));
+            return Ok(());
+        }
+    }
+    Err(anyhow!("Expected a call with a type argument"))
+}
+
+#[test]
+fn parse_array_literals() -> Result<()> {
+    let body = parse_block("{ a := [2 * n][3]int{}; b := [...]string{\"x\"} }")?;
+    let types: Vec<_> = body
+        .statements
+        .iter()
+        .map(|s| match &s.inner {
+            InnerStatement::ShortVarDeclaration { values, .. } => match &values[0].inner {
+                InnerExpression::Composite { r#type, .. } => Some(r#type.inner.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let [Some(InnerType::Array {
+        len: Some(len),
+        element,
+    }), Some(InnerType::Array { len: None, .. })] = types.as_slice()
+    else {
+        return Err(anyhow!("Expected two arrays: {:?}", types));
+    };
+    assert_eq!("2 * n", len.span.as_str());
+    assert!(matches!(element.inner, InnerType::Array { .. }));
+    Ok(())
+}
+
+#[test]
+fn parse_map_literal() -> Result<()> {
+    let body = parse_block("{ m := map[string][]int{\"a\": []int{}, k: nil} }")?;
+    if let InnerStatement::ShortVarDeclaration { values, .. } = &body.statements[0].inner {
+        if let InnerExpression::Composite { r#type, elements } = &values[0].inner {
+            let InnerType::Map { key, value } = &r#type.inner else {
+                return Err(anyhow!("Expected a map type: {:?}", r#type));
+            };
+            assert_eq!(InnerType::Name("string".into()), key.inner);
+            assert!(matches!(value.inner, InnerType::Slice(_)));
+            assert!(elements.iter().all(|e| e.key.is_some()));
+            return Ok(());
+        }
+    }
+    Err(anyhow!("Expected a map literal: {:?}", body))
+}
+
+#[test]
+fn parse_map_comma_ok() -> Result<()> {
+    let s = parse_statement("v, ok := m[k]")?;
+    assert!(matches!(
+        s.inner,
+        InnerStatement::ShortVarDeclaration { .. }
+    ));
+    Ok(())
+}
+
+#[test]
+fn parse_range() -> Result<()> {
+    let body = parse_block("{ for k, v := range m { }; for i = range 10 { }; for range s { } }")?;
+    let ranges: Vec<_> = body
+        .statements
+        .iter()
+        .filter_map(|s| match &s.inner {
+            InnerStatement::For(statement) => statement.range.as_ref(),
+            _ => None,
+        })
+        .collect();
+    let [declare, assign, bare] = ranges.as_slice() else {
+        return Err(anyhow!("Expected three range loops: {:?}", body));
+    };
+    assert!(matches!(&declare.targets, RangeTargets::Declare(names) if names.len() == 2));
+    assert!(matches!(&assign.targets, RangeTargets::Assign(targets) if targets.len() == 1));
+    assert!(matches!(&bare.targets, RangeTargets::Assign(targets) if targets.is_empty()));
+    assert_eq!(InnerExpression::Number(10), assign.exp.inner);
+    Ok(())
+}
+
+#[test]
+fn parse_range_too_many_targets() {
+    assert!(parse_statement("for a, b, c := range x { }").is_err());
+}
+
+#[test]
+fn parse_struct_type() -> Result<()> {
+    let spec = TypeSpec::parse(parse(
+        Rule::type_spec,
+        "Point struct { x, y int; label string \"tag\" }",
+    )?)?;
+    assert_eq!(Name::from("Point"), spec.name);
+    assert!(!spec.alias);
+    if let InnerType::Struct(fields) = spec.r#type.inner {
+        let names: Vec<_> = fields.iter().map(|f| f.name.to_string()).collect();
+        assert_eq!(vec!["x", "y", "label"], names);
+        assert_eq!(InnerType::Name("int".into()), fields[1].r#type.inner);
+        Ok(())
+    } else {
+        Err(anyhow!("Expected a struct: {:?}", spec))
+    }
+}
+
+#[test]
+fn parse_struct_duplicate_field() {
+    assert!(
+        TypeSpec::parse(parse(Rule::type_spec, "T struct { a int; a string }").unwrap()).is_err()
+    );
+}
+
+#[test]
+fn parse_type_alias() -> Result<()> {
+    let spec = TypeSpec::parse(parse(Rule::type_spec, "Number = int")?)?;
+    assert!(spec.alias);
+    Ok(())
+}
+
+fn parse_module(input: &str) -> Result<SourceModule<'_>> {
+    Ok(SourceModule::parse(parse(Rule::module, input)?)?)
+}
+
+#[test]
+fn parse_const_block() -> Result<()> {
+    let module = parse_module(
+        "package main\nconst (\n  A = iota * 10\n  B\n  C, D int = iota, 5\n  E, F\n)",
+    )?;
+    let (b, index) = module.constant("B".into()).context("B")?;
+    assert_eq!((1, 0), (b.iota, index));
+    assert_eq!("iota * 10", b.values[0].span.as_str());
+    let (f, index) = module.constant("F".into()).context("F")?;
+    assert_eq!((3, 1), (f.iota, index));
+    assert!(f.r#type.is_some());
+    assert_eq!("5", f.values[1].span.as_str());
+    Ok(())
+}
+
+#[test]
+fn parse_const_type_on_same_line() -> Result<()> {
+    let module = parse_module("package main\nconst (\n  a = 1\n  b\n  c = 2\n)")?;
+    let (b, _) = module.constant("b".into()).context("b")?;
+    assert!(b.r#type.is_none());
+    assert_eq!("1", b.values[0].span.as_str());
+    let (c, _) = module.constant("c".into()).context("c")?;
+    assert_eq!("2", c.values[0].span.as_str());
+    Ok(())
+}
+
+#[test]
+fn parse_const_without_values() {
+    assert!(parse_module("package main\nconst a").is_err());
+    assert!(parse_module("package main\nconst a int").is_err());
+    assert!(parse_module("package main\nconst a, b = 1").is_err());
+}
+
+fn variable_order(module: &SourceModule) -> Vec<String> {
+    module
+        .variables()
+        .map(|spec| spec.names[0].to_string())
+        .collect()
+}
+
+#[test]
+fn package_variables_in_dependency_order() -> Result<()> {
+    let module = parse_module(
+        "package main
+        var (
+            a = b + c
+            b = f()
+            c = 1
+        )
+        func f() int { return d }
+        var d = 2",
+    )?;
+    assert_eq!(vec!["c", "d", "b", "a"], variable_order(&module));
+    Ok(())
+}
+
+#[test]
+fn package_variables_depend_through_methods() -> Result<()> {
+    let module = parse_module(
+        "package main
+        var x = T{}.Get()
+        type T struct{}
+        func (t T) Get() int { return y }
+        var y = 1",
+    )?;
+    assert_eq!(vec!["y", "x"], variable_order(&module));
+    Ok(())
+}
+
+#[test]
+fn local_declarations_are_not_dependencies() -> Result<()> {
+    let module = parse_module(
+        "package main
+        var total = sum(1, 2)
+        func sum(xs ...int) int {
+            total := 0
+            for _, x := range xs {
+                total += x
+            }
+            return total
+        }",
+    )?;
+    assert_eq!(vec!["total"], variable_order(&module));
+    Ok(())
+}
+
+#[test]
+fn package_variable_initialization_cycle() {
+    let result = parse_module(
+        "package main
+        var a = f()
+        func f() int { return b }
+        var b = a",
+    );
+    match result {
+        Err(error) => assert_eq!(
+            "Initialization cycle: a refers to b, b refers to a",
+            error.to_string()
+        ),
+        Ok(module) => panic!("Expected a cycle: {:?}", variable_order(&module)),
+    }
+}
+
+#[test]
+fn parse_init_functions() -> Result<()> {
+    let module = parse_module("package main\nfunc init() {}\nfunc init() {}")?;
+    assert_eq!(2, module.init_functions().count());
+    assert!(module.function("init".into()).is_none());
+    assert!(parse_module("package main\nfunc init(x int) {}").is_err());
+    Ok(())
+}
+
+#[test]
+fn parse_interface_type() -> Result<()> {
+    let spec = TypeSpec::parse(parse(
+        Rule::type_spec,
+        "ReadCloser interface { Reader; Close() error\n Name(long bool) string }",
+    )?)?;
+    if let InnerType::Interface(elements) = spec.r#type.inner {
+        assert_eq!(3, elements.len());
+        assert!(matches!(&elements[0], InterfaceElement::Embedded(_)));
+        if let InterfaceElement::Method { name, signature } = &elements[2] {
+            assert_eq!(Name::from("Name"), *name);
+            assert_eq!(1, signature.parameters.parameters.len());
+            assert_eq!(1, signature.results.parameters.len());
+        } else {
+            return Err(anyhow!("Expected a method: {:?}", elements[2]));
+        }
+        Ok(())
+    } else {
+        Err(anyhow!("Expected an interface: {:?}", spec))
+    }
+}
+
+#[test]
+fn parse_type_assertion() -> Result<()> {
+    let e = Expression::parse(parse_expression("a.b.(T) + 1")?)?;
+    if let InnerExpression::BinOp { left, op, .. } = e.inner {
+        assert_eq!(BinOp::Add, op);
+        if let InnerExpression::TypeAssertion { exp, r#type } = left.inner {
+            assert!(matches!(
+                exp.inner,
+                InnerExpression::BinOp { op: BinOp::Dot, .. }
+            ));
+            assert_eq!(InnerType::Name("T".into()), r#type.inner);
+            return Ok(());
+        }
+    }
+    Err(anyhow!("Expected an addition of a type assertion"))
+}
+
+#[test]
+fn parse_comma_ok() -> Result<()> {
+    let s = parse_statement("v, ok := x.(T)")?;
+    assert!(matches!(
+        s.inner,
+        InnerStatement::ShortVarDeclaration { .. }
+    ));
+    assert!(parse_statement("v, ok := x + 1").is_err());
+    Ok(())
+}
+
+fn parse_block(input: &str) -> Result<Body<'_>> {
+    Ok(Body::parse(parse(Rule::block, input)?)?)
+}
+
+#[test]
+fn parse_composite_literal() -> Result<()> {
+    let body = parse_block("{ p := Point{x: 1, y: 2,} }")?;
+    if let InnerStatement::ShortVarDeclaration { values, .. } = &body.statements[0].inner {
+        if let InnerExpression::Composite { r#type, elements } = &values[0].inner {
+            assert_eq!(InnerType::Name("Point".into()), r#type.inner);
+            assert_eq!(2, elements.len());
+            assert_eq!(
+                Some(InnerExpression::Name("y".into())),
+                elements[1].key.as_ref().map(|k| k.inner.clone())
+            );
+            return Ok(());
+        }
+    }
+    Err(anyhow!("Expected a composite literal: {:?}", body))
+}
+
+#[test]
+fn parse_composite_literal_with_elided_types() -> Result<()> {
+    let body = parse_block("{ m := map[Point][]int{{1, 2}: {3}, {}: {}} }")?;
+    if let InnerStatement::ShortVarDeclaration { values, .. } = &body.statements[0].inner {
+        if let InnerExpression::Composite { elements, .. } = &values[0].inner {
+            assert_eq!(2, elements.len());
+            assert!(matches!(
+                elements[0].key.as_ref().map(|k| &k.inner),
+                Some(InnerExpression::Elided(elements)) if elements.len() == 2
+            ));
+            assert!(matches!(
+                &elements[0].value.inner,
+                InnerExpression::Elided(_)
+            ));
+            return Ok(());
+        }
+    }
+    Err(anyhow!("Expected a composite literal: {:?}", body))
+}
+
+#[test]
+fn parse_composite_literal_mixed_elements() {
+    assert!(parse_block("{ p := Point{x: 1, 2} }").is_err());
+}
+
+#[test]
+fn parse_header_brace_starts_body() -> Result<()> {
+    let body = parse_block("{ if ok { print(x) } }")?;
+    if let InnerStatement::If(statement) = &body.statements[0].inner {
+        assert_eq!(
+            InnerExpression::Name("ok".into()),
+            statement.condition.inner
+        );
+        assert_eq!(1, statement.then.statements.len());
+        Ok(())
+    } else {
+        Err(anyhow!("Expected an if: {:?}", body))
+    }
+}
+
+#[test]
+fn parse_composite_literal_in_header_brackets() -> Result<()> {
+    let body = parse_block("{ for p != (Point{}) && f(Point{1, 2}) { } }")?;
+    assert!(matches!(body.statements[0].inner, InnerStatement::For(_)));
+    Ok(())
+}
+
+#[test]
+fn parse_keyword_prefixed_names() -> Result<()> {
+    let s = parse_statement("breakfast := format")?;
+    assert!(matches!(
+        s.inner,
+        InnerStatement::ShortVarDeclaration { .. }
+    ));
+    let s = parse_statement("returned = 1")?;
+    assert!(matches!(s.inner, InnerStatement::Assignment { .. }));
+    Ok(())
+}
+
+#[test]
+fn parse_unary_precedence() -> Result<()> {
+    let e = Expression::parse(parse_expression("-a + b")?)?;
+    if let InnerExpression::BinOp { left, op, .. } = e.inner {
+        assert_eq!(BinOp::Add, op);
+        assert!(matches!(
+            left.inner,
+            InnerExpression::UniOp {
+                op: UniOp::Negate,
+                ..
+            }
+        ));
+        Ok(())
+    } else {
+        Err(anyhow!("Expected an addition: {:?}", e))
+    }
+}
+
+#[test]
+fn parse_address_and_deref() -> Result<()> {
+    let e = Expression::parse(parse_expression("a * *&p.x[0]")?)?;
+    let InnerExpression::BinOp {
+        op: BinOp::Mul,
+        right,
+        ..
+    } = e.inner
+    else {
+        return Err(anyhow!("Expected a multiplication: {:?}", e));
+    };
+    let InnerExpression::UniOp {
+        op: UniOp::Deref,
+        exp,
+    } = right.inner
+    else {
+        return Err(anyhow!("Expected a dereference: {:?}", right));
+    };
+    let InnerExpression::UniOp {
+        op: UniOp::Address,
+        exp,
+    } = exp.inner
+    else {
+        return Err(anyhow!("Expected an address: {:?}", exp));
+    };
+    assert!(matches!(exp.inner, InnerExpression::Index { .. }));
+    Ok(())
+}
+
+#[test]
+fn parse_operator_on_next_line_starts_statement() -> Result<()> {
+    let body = parse_block("{ x := f(a)\n*p = x\n y := m[k]\n-x }")?;
+    assert_eq!(4, body.statements.len());
+    assert!(matches!(
+        &body.statements[1].inner,
+        InnerStatement::Assignment { targets, .. } if matches!(
+            targets[0].inner,
+            InnerExpression::UniOp { op: UniOp::Deref, .. }
+        )
+    ));
+    let body = parse_block("{ x := a +\n b }")?;
+    assert_eq!(1, body.statements.len());
+    Ok(())
+}
+
+#[test]
+fn parse_func_literal() -> Result<()> {
+    let body = parse_block("{ f := func(x int) int { return x }\n f(1) }")?;
+    assert_eq!(2, body.statements.len());
+    let InnerStatement::ShortVarDeclaration { values, .. } = &body.statements[0].inner else {
+        return Err(anyhow!("Expected a declaration: {:?}", body.statements[0]));
+    };
+    let InnerExpression::Func(literal) = &values[0].inner else {
+        return Err(anyhow!("Expected a func literal: {:?}", values[0]));
+    };
+    assert_eq!(1, literal.signature.parameters.parameters.len());
+    assert_eq!(1, literal.signature.results.parameters.len());
+    assert_eq!(1, literal.body.statements.len());
+    Ok(())
+}
+
+#[test]
+fn parse_apply() -> Result<()> {
+    let e = Expression::parse(parse_expression("fs[0](1, 2)")?)?;
+    let InnerExpression::Apply {
+        function,
+        parameters,
+        ..
+    } = e.inner
+    else {
+        return Err(anyhow!("Expected a call: {:?}", e));
+    };
+    assert!(matches!(function.inner, InnerExpression::Index { .. }));
+    assert_eq!(2, parameters.len());
+    let e = Expression::parse(parse_expression("func() {}()")?)?;
+    assert!(matches!(
+        e.inner,
+        InnerExpression::Apply { function, .. } if matches!(function.inner, InnerExpression::Func(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn parse_func_type_ends_at_line() -> Result<()> {
+    let body = parse_block("{ var f func(int)\n print(f) }")?;
+    assert_eq!(2, body.statements.len());
+    let InnerStatement::VarDeclaration(specs) = &body.statements[0].inner else {
+        return Err(anyhow!(
+            "Expected a var declaration: {:?}",
+            body.statements[0]
+        ));
+    };
+    let Some(InnerType::Func(signature)) = specs[0].r#type.as_ref().map(|t| &t.inner) else {
+        return Err(anyhow!("Expected a func type: {:?}", specs[0]));
+    };
+    assert!(signature.results.parameters.is_empty());
+    Ok(())
+}
+
+#[test]
+fn parse_multiple_results() -> Result<()> {
+    let s = parse_statement("return a, b")?;
+    assert!(matches!(s.inner, InnerStatement::Return(values) if values.len() == 2));
+    let s = parse_statement("return")?;
+    assert!(matches!(s.inner, InnerStatement::Return(values) if values.is_empty()));
+    let s = parse_statement("q, _ := divmod(a, b)")?;
+    let InnerStatement::ShortVarDeclaration { names, values } = s.inner else {
+        return Err(anyhow!("Expected a declaration: {:?}", s));
+    };
+    assert!(names[1].is_blank());
+    assert!(values[0].is_call());
+    assert!(parse_statement("a, b = x.pair()").is_ok());
+    assert!(parse_statement("a, b, c = 1, 2").is_err());
+    Ok(())
+}
+
+#[test]
+fn parse_variadic() -> Result<()> {
+    let body = parse_block("{ f := func(prefix string, xs ...int) {}\n f(\"a\", s...) }")?;
+    let InnerStatement::ShortVarDeclaration { values, .. } = &body.statements[0].inner else {
+        return Err(anyhow!("Expected a declaration: {:?}", body.statements[0]));
+    };
+    let InnerExpression::Func(literal) = &values[0].inner else {
+        return Err(anyhow!("Expected a func literal: {:?}", values[0]));
+    };
+    assert!(literal.signature.is_variadic());
+    assert!(!literal.signature.parameters.parameters[0].variadic);
+    let InnerStatement::Expression(call) = &body.statements[1].inner else {
+        return Err(anyhow!("Expected a call: {:?}", body.statements[1]));
+    };
+    assert!(matches!(
+        &call.inner,
+        InnerExpression::Call { parameters, spread: true, .. } if parameters.len() == 2
+    ));
+    assert!(parse_block("{ f := func(xs ...int, y int) {} }").is_err());
+    Ok(())
+}
+
+#[test]
+fn parse_go_statement() -> Result<()> {
+    let s = parse_statement("go worker(1, results)")?;
+    assert!(matches!(s.inner, InnerStatement::Go(call) if call.is_call()));
+    assert!(matches!(
+        parse_statement("gopher(1)")?.inner,
+        InnerStatement::Expression(_)
+    ));
+    assert!(parse_statement("go x + 1").is_err());
+    Ok(())
+}
+
+#[test]
+fn parse_defer_statement() -> Result<()> {
+    let s = parse_statement("defer mu.Unlock()")?;
+    assert!(matches!(s.inner, InnerStatement::Defer(call) if call.is_call()));
+    let s = parse_statement("defer func() { recover() }()")?;
+    assert!(matches!(s.inner, InnerStatement::Defer(call) if call.is_call()));
+    assert!(matches!(
+        parse_statement("deferred()")?.inner,
+        InnerStatement::Expression(_)
+    ));
+    assert!(parse_statement("defer x").is_err());
+    Ok(())
+}
+
+#[test]
+fn parse_channel_types() -> Result<()> {
+    let body = parse_block(
+        "{ var a chan int\n var b <-chan int\n var c chan<- chan int\n var d chan (<-chan int) }",
+    )?;
+    let types = body
+        .statements
+        .iter()
+        .map(|statement| match &statement.inner {
+            InnerStatement::VarDeclaration(specs) => specs[0].r#type.as_ref().map(|t| &t.inner),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    fn channel<'i>(r#type: Option<&InnerType<'i>>) -> Option<(ChannelDirection, InnerType<'i>)> {
+        match r#type {
+            Some(InnerType::Channel { direction, element }) => {
+                Some((*direction, element.inner.clone()))
+            }
+            _ => None,
+        }
+    }
+    assert_eq!(
+        Some((ChannelDirection::Both, InnerType::Name("int".into()))),
+        channel(types[0])
+    );
+    assert_eq!(
+        Some((ChannelDirection::Receive, InnerType::Name("int".into()))),
+        channel(types[1])
+    );
+    let (direction, element) = channel(types[2]).context("Expected a channel")?;
+    assert_eq!(ChannelDirection::Send, direction);
+    assert_eq!(
+        Some(ChannelDirection::Both),
+        channel(Some(&element)).map(|c| c.0)
+    );
+    let (direction, element) = channel(types[3]).context("Expected a channel")?;
+    assert_eq!(ChannelDirection::Both, direction);
+    assert_eq!(
+        Some(ChannelDirection::Receive),
+        channel(Some(&element)).map(|c| c.0)
+    );
+    Ok(())
+}
+
+#[test]
+fn parse_send_and_receive() -> Result<()> {
+    let s = parse_statement("ch <- x + 1")?;
+    let InnerStatement::Send { channel, value } = s.inner else {
+        return Err(anyhow!("Expected a send: {:?}", s));
+    };
+    assert_eq!(InnerExpression::Name("ch".into()), channel.inner);
+    assert!(matches!(value.inner, InnerExpression::BinOp { .. }));
+    let s = parse_statement("v, ok := <-ch")?;
+    let InnerStatement::ShortVarDeclaration { values, .. } = s.inner else {
+        return Err(anyhow!("Expected a declaration: {:?}", s));
+    };
+    assert!(matches!(
+        values[0].inner,
+        InnerExpression::UniOp {
+            op: UniOp::Receive,
+            ..
+        }
+    ));
+    // `a <-b` is a send rather than a comparison, while `a < -b` compares
+    assert!(matches!(
+        parse_statement("a <-b")?.inner,
+        InnerStatement::Send { .. }
+    ));
+    assert!(matches!(
+        parse_statement("a < -b")?.inner,
+        InnerStatement::Expression(_)
+    ));
+    // A receive on the next line is a statement of its own
+    let body = parse_block("{ f()\n <-done }")?;
+    assert_eq!(2, body.statements.len());
+    Ok(())
+}
+
+#[test]
+fn parse_select_statement() -> Result<()> {
+    let s = parse_statement(
+        "select {\n case ch <- 1:\n case <-done:\n return\n case v, ok := <-in:\n print(v, ok)\n case x = <-in:\n default:\n }",
+    )?;
+    let InnerStatement::Select(select) = s.inner else {
+        return Err(anyhow!("Expected a select: {:?}", s));
+    };
+    assert_eq!(5, select.clauses.len());
+    assert!(matches!(
+        select.clauses[0].communication,
+        Some(Communication::Send { .. })
+    ));
+    assert!(matches!(
+        &select.clauses[1].communication,
+        Some(Communication::Receive { targets: RangeTargets::Assign(targets), .. }) if targets.is_empty()
+    ));
+    assert_eq!(1, select.clauses[1].body.statements.len());
+    assert!(matches!(
+        &select.clauses[2].communication,
+        Some(Communication::Receive { targets: RangeTargets::Declare(names), .. }) if names.len() == 2
+    ));
+    assert!(matches!(
+        &select.clauses[3].communication,
+        Some(Communication::Receive { targets: RangeTargets::Assign(targets), .. }) if targets.len() == 1
+    ));
+    assert!(select.clauses[4].communication.is_none());
+
+    assert!(parse_statement("select {\n case x := f():\n }").is_err());
+    assert!(parse_statement("select {\n default:\n default:\n }").is_err());
+    Ok(())
+}
+
+#[test]
+fn parse_mismatched_assignment() {
+    assert!(parse_statement("a, b = 1").is_err());
+}
+
 #[allow(non_snake_case)]
 mod binop {
     use super::parse_expression;
@@ -0,0 +1,238 @@
+use crate::declaration::name_list;
+use crate::expression::Expression;
+use crate::func::Signature;
+use crate::name::Name;
+use crate::{AstError, AstResult, Located, Parseable};
+use gor_parse::Rule;
+use pest::iterators::{Pair, Pairs};
+use pest::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type<'i> {
+    pub inner: InnerType<'i>,
+    pub span: Span<'i>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InnerType<'i> {
+    /// A type declared in the current package, or predeclared
+    Name(Name),
+    /// A type exported by another package
+    Qualified { package: Name, name: Name },
+    /// `struct { a, b int; c string }`, with one entry per field name
+    Struct(Vec<Field<'i>>),
+    /// `*T`
+    Pointer(Box<Type<'i>>),
+    /// `[]T`
+    Slice(Box<Type<'i>>),
+    /// `[N]T`, or `[...]T` when the length is left to a composite literal
+    Array {
+        len: Option<Box<Expression<'i>>>,
+        element: Box<Type<'i>>,
+    },
+    /// `map[K]V`
+    Map {
+        key: Box<Type<'i>>,
+        value: Box<Type<'i>>,
+    },
+    /// `func(int) string`
+    Func(Box<Signature<'i>>),
+    /// `chan T`, `chan<- T` or `<-chan T`
+    Channel {
+        direction: ChannelDirection,
+        element: Box<Type<'i>>,
+    },
+    /// `interface { M(); Embedded }`
+    Interface(Vec<InterfaceElement<'i>>),
+}
+
+/// The operations a channel type allows
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChannelDirection {
+    /// `chan T`, which may be sent to and received from
+    Both,
+    /// `chan<- T`
+    Send,
+    /// `<-chan T`
+    Receive,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterfaceElement<'i> {
+    Method {
+        name: Name,
+        signature: Signature<'i>,
+    },
+    /// Another interface, whose methods are all included in this one
+    Embedded(Type<'i>),
+}
+
+/// A single named field in a struct type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field<'i> {
+    pub name: Name,
+    pub r#type: Type<'i>,
+}
+
+impl<'i> Parseable<'i> for Type<'i> {
+    const RULE: Rule = Rule::r#type;
+
+    fn build(span: &Span<'i>, pairs: Pairs<'i, Rule>) -> AstResult<Self> {
+        let mut pairs = pairs;
+        let next = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a type with no contents"))?;
+        let inner = match next.as_rule() {
+            Rule::name => InnerType::Name(Name::descend(next)?),
+            Rule::qualified_name => {
+                let mut names = next.into_inner();
+                let package = names.next().ok_or(AstError::InvalidState(
+                    "Found a qualified name with no package",
+                ))?;
+                let name = names.next().ok_or(AstError::InvalidState(
+                    "Found a qualified name with no name",
+                ))?;
+                InnerType::Qualified {
+                    package: Name::descend(package)?,
+                    name: Name::descend(name)?,
+                }
+            }
+            Rule::struct_type => InnerType::Struct(struct_fields(next)?),
+            Rule::pointer_type => {
+                let pointee = next
+                    .into_inner()
+                    .next()
+                    .ok_or(AstError::InvalidState("Found a pointer without a type"))?;
+                InnerType::Pointer(Box::new(Type::descend(pointee)?))
+            }
+            Rule::slice_type => {
+                let element = next
+                    .into_inner()
+                    .next()
+                    .ok_or(AstError::InvalidState("Found a slice without a type"))?;
+                InnerType::Slice(Box::new(Type::descend(element)?))
+            }
+            Rule::array_type => {
+                let mut pairs = next.into_inner();
+                let len = pairs
+                    .next()
+                    .ok_or(AstError::InvalidState("Found an array without a length"))?;
+                let element = pairs
+                    .next()
+                    .ok_or(AstError::InvalidState("Found an array without a type"))?;
+                InnerType::Array {
+                    len: match len.as_rule() {
+                        Rule::ellipsis => None,
+                        _ => Some(Box::new(Expression::descend(len)?)),
+                    },
+                    element: Box::new(Type::descend(element)?),
+                }
+            }
+            Rule::map_type => {
+                let mut pairs = next.into_inner();
+                let key = pairs
+                    .next()
+                    .ok_or(AstError::InvalidState("Found a map without a key type"))?;
+                let value = pairs
+                    .next()
+                    .ok_or(AstError::InvalidState("Found a map without a value type"))?;
+                InnerType::Map {
+                    key: Box::new(Type::descend(key)?),
+                    value: Box::new(Type::descend(value)?),
+                }
+            }
+            Rule::func_type => {
+                let signature = next.into_inner().next().ok_or(AstError::InvalidState(
+                    "Found a func type without a signature",
+                ))?;
+                InnerType::Func(Box::new(Signature::descend(signature)?))
+            }
+            Rule::chan_type => {
+                let mut pairs = next.into_inner();
+                let mut element = pairs
+                    .next()
+                    .ok_or(AstError::InvalidState("Found a channel without a type"))?;
+                let direction = match element.as_rule() {
+                    Rule::receive_only => ChannelDirection::Receive,
+                    Rule::send_only => ChannelDirection::Send,
+                    _ => ChannelDirection::Both,
+                };
+                if direction != ChannelDirection::Both {
+                    element = pairs
+                        .next()
+                        .ok_or(AstError::InvalidState("Found a channel without a type"))?;
+                }
+                InnerType::Channel {
+                    direction,
+                    element: Box::new(Type::descend(element)?),
+                }
+            }
+            Rule::interface_type => InnerType::Interface(
+                next.into_inner()
+                    .map(interface_element)
+                    .collect::<AstResult<_>>()?,
+            ),
+            Rule::r#type => Type::descend(next)?.inner,
+            r => {
+                return Err(AstError::InvalidRuleClass(
+                    "type",
+                    r,
+                    next.as_str().to_string(),
+                ))
+            }
+        };
+        Ok(Type { inner, span: *span })
+    }
+}
+
+impl<'i> Located<'i> for Type<'i> {
+    fn as_span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+fn struct_fields(pair: Pair<Rule>) -> AstResult<Vec<Field>> {
+    let mut fields = vec![];
+    for decl in pair.into_inner() {
+        let mut pairs = decl.into_inner();
+        let names = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a field without names"))?;
+        let r#type = pairs
+            .next()
+            .ok_or(AstError::InvalidState("Found a field without a type"))?;
+        let r#type = Type::descend(r#type)?;
+        for name in name_list(names)? {
+            if fields.iter().any(|f: &Field| f.name == name) {
+                return Err(AstError::InvalidStateString(format!(
+                    "Duplicate field {}",
+                    name
+                )));
+            }
+            fields.push(Field {
+                name,
+                r#type: r#type.clone(),
+            });
+        }
+    }
+    Ok(fields)
+}
+
+fn interface_element(pair: Pair<Rule>) -> AstResult<InterfaceElement> {
+    match pair.as_rule() {
+        Rule::method_spec => {
+            let mut pairs = pair.into_inner();
+            let name = pairs
+                .next()
+                .ok_or(AstError::InvalidState("Found a method without a name"))?;
+            let signature = pairs
+                .next()
+                .ok_or(AstError::InvalidState("Found a method without a signature"))?;
+            Ok(InterfaceElement::Method {
+                name: Name::descend(name)?,
+                signature: Signature::descend(signature)?,
+            })
+        }
+        _ => Ok(InterfaceElement::Embedded(Type::descend(pair)?)),
+    }
+}
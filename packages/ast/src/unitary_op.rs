@@ -1,4 +1,35 @@
+use crate::{AstError, AstResult};
+use gor_parse::Rule;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum UniOp {
     Negate,
+    Not,
+    /// `&x`, the address of a variable or of a composite literal
+    Address,
+    /// `*p`, the value a pointer points to
+    Deref,
+    /// `<-ch`, which waits for a value from a channel
+    Receive,
+}
+
+impl TryFrom<Rule> for UniOp {
+    type Error = AstError;
+
+    fn try_from(value: Rule) -> AstResult<Self> {
+        Ok(match value {
+            Rule::negate => UniOp::Negate,
+            Rule::not => UniOp::Not,
+            Rule::address => UniOp::Address,
+            Rule::deref => UniOp::Deref,
+            Rule::receive => UniOp::Receive,
+            r => {
+                return Err(AstError::InvalidRuleClass(
+                    "UniOp",
+                    r,
+                    format!("Rule::{:?}", r),
+                ))
+            }
+        })
+    }
 }
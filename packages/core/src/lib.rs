@@ -1,6 +1,5 @@
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     missing_debug_implementations,
@@ -9,7 +8,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unreachable_pub,
     unused,
@@ -0,0 +1,139 @@
+use crate::slice::Slice;
+use crate::{RuntimeError, RuntimeResult, Type, Value};
+
+/// The values passed to a function or builtin.
+///
+/// A call like `f(a, s...)` spreads its last argument, a slice, over the function's variadic
+/// parameter, and only a function with such a parameter may be called that way.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Arguments {
+    values: Vec<Value>,
+    spread: bool,
+}
+
+/// The arguments that fill a variadic parameter
+#[derive(Debug)]
+pub(crate) enum Variadic {
+    /// Each of the arguments after the fixed parameters
+    Values(Vec<Value>),
+    /// A slice passed on with `...`
+    Spread(Value),
+}
+
+impl Arguments {
+    pub fn new(values: Vec<Value>) -> Self {
+        Arguments {
+            values,
+            spread: false,
+        }
+    }
+
+    /// Arguments whose last value is a slice spread with `...`
+    pub fn spread(values: Vec<Value>) -> Self {
+        Arguments {
+            spread: !values.is_empty(),
+            values,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The values for a function without a variadic parameter
+    pub(crate) fn values(self) -> RuntimeResult<Vec<Value>> {
+        if self.spread {
+            return Err(RuntimeError::NotVariadic);
+        }
+        Ok(self.values)
+    }
+
+    /// The values for a function that takes exactly `N` arguments
+    pub(crate) fn exactly<const N: usize>(self) -> RuntimeResult<[Value; N]> {
+        let values = self.values()?;
+        let found = values.len();
+        values
+            .try_into()
+            .map_err(|_| RuntimeError::ArgumentCount { expected: N, found })
+    }
+
+    /// Split the arguments between `fixed` parameters and a variadic parameter after them
+    pub(crate) fn variadic(self, fixed: usize) -> RuntimeResult<(Vec<Value>, Variadic)> {
+        let mut values = self.values;
+        if self.spread {
+            // The spread slice is the only value for the variadic parameter
+            if values.len() != fixed + 1 {
+                return Err(RuntimeError::ArgumentCount {
+                    expected: fixed + 1,
+                    found: values.len(),
+                });
+            }
+            let rest = values.pop().unwrap_or(Value::Nil);
+            return Ok((values, Variadic::Spread(rest)));
+        }
+        if values.len() < fixed {
+            return Err(RuntimeError::ArgumentCount {
+                expected: fixed,
+                found: values.len(),
+            });
+        }
+        let rest = values.split_off(fixed);
+        Ok((values, Variadic::Values(rest)))
+    }
+}
+
+impl Variadic {
+    /// Each value, copied out of the slice if one was spread
+    pub(crate) fn into_values(self) -> RuntimeResult<Vec<Value>> {
+        match self {
+            Variadic::Values(values) => Ok(values),
+            Variadic::Spread(Value::Slice(slice)) => Ok(slice.values()),
+            Variadic::Spread(Value::Nil) => Ok(vec![]),
+            Variadic::Spread(value) => Err(not_a_slice(&value)),
+        }
+    }
+
+    /// A slice of `element`s, which shares its elements with a spread slice rather than copying
+    /// them.  Without any values, it's nil.
+    pub(crate) fn into_slice(self, element: Type) -> RuntimeResult<Value> {
+        Ok(Value::Slice(match self {
+            Variadic::Values(values) => {
+                let values = values
+                    .into_iter()
+                    .map(|value| element.implicit(value))
+                    .collect::<RuntimeResult<Vec<_>>>()?;
+                if let Some(value) = values.iter().find(|v| !element.accepts(v)) {
+                    return Err(RuntimeError::TypeError {
+                        expected: element,
+                        found: value.as_type(),
+                    });
+                }
+                if values.is_empty() {
+                    Slice::nil(element)
+                } else {
+                    Slice::new(element, values)
+                }
+            }
+            Variadic::Spread(Value::Slice(slice)) if *slice.element() == element => slice,
+            Variadic::Spread(Value::Slice(slice)) => {
+                return Err(RuntimeError::TypeError {
+                    expected: Type::Slice(Box::new(element)),
+                    found: Type::Slice(Box::new(slice.element().clone())),
+                })
+            }
+            Variadic::Spread(Value::Nil) => Slice::nil(element),
+            Variadic::Spread(value) => return Err(not_a_slice(&value)),
+        }))
+    }
+}
+
+fn not_a_slice(value: &Value) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        function: "...",
+        r#type: value.as_type(),
+    }
+}
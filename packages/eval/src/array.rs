@@ -0,0 +1,111 @@
+use crate::place::Place;
+use crate::slice::{check_index, Slice};
+use crate::variable::Variable;
+use crate::{EvalResult, RuntimeResult, Type, Value};
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+/// The value of an array: a fixed number of elements of the same type.
+///
+/// Each element is stored in its own variable, so that slicing an array that's stored somewhere
+/// shares its elements.  Cloning an array copies every element, which gives arrays the value
+/// semantics that Go expects when they're assigned or passed to a function.
+pub struct Array {
+    element: Type,
+    elements: Arc<[Variable]>,
+}
+
+impl Array {
+    pub(crate) fn new(element: Type, values: Vec<Value>) -> Self {
+        Array {
+            element,
+            elements: values.into_iter().map(Variable::new).collect(),
+        }
+    }
+
+    /// An array with every element set to its zero value
+    pub(crate) fn zero(element: Type, len: usize) -> RuntimeResult<Self> {
+        let zero = element.zero_value()?;
+        Ok(Array::new(element, vec![zero; len]))
+    }
+
+    pub fn element(&self) -> &Type {
+        &self.element
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub(crate) fn values(&self) -> Vec<Value> {
+        self.elements.iter().map(Variable::get).collect()
+    }
+
+    /// The element at `index`, which panics if it's out of range just as Go does
+    pub(crate) fn place(&self, index: i64) -> RuntimeResult<Place> {
+        let index = check_index(index, self.len())?;
+        Ok(self.elements[index].clone().into())
+    }
+
+    pub(crate) fn get(&self, index: i64) -> EvalResult {
+        self.place(index)?.get()
+    }
+
+    /// A slice sharing the elements of this array
+    pub(crate) fn slice(
+        &self,
+        low: Option<i64>,
+        high: Option<i64>,
+        max: Option<i64>,
+    ) -> RuntimeResult<Slice> {
+        Slice::of(self.element.clone(), self.elements.clone()).slice(low, high, max)
+    }
+
+    /// Arrays are equal if all their elements are equal.
+    ///
+    /// Every element is compared, so an element type that isn't comparable is always an error.
+    pub(crate) fn equals(&self, other: &Array) -> RuntimeResult<bool> {
+        let interface = matches!(self.element, Type::Interface(_));
+        let mut equal = true;
+        for (left, right) in self.values().into_iter().zip(other.values()) {
+            equal &= left.equals(right, interface)?;
+        }
+        Ok(equal)
+    }
+}
+
+/// A copy of every element, in a new array
+impl Clone for Array {
+    fn clone(&self) -> Self {
+        Array::new(self.element.clone(), self.values())
+    }
+}
+
+impl PartialEq for Array {
+    fn eq(&self, other: &Self) -> bool {
+        self.element == other.element && self.values() == other.values()
+    }
+}
+
+impl Debug for Array {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Array({})", self)
+    }
+}
+
+impl Display for Array {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (index, value) in self.values().iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            Display::fmt(value, f)?;
+        }
+        write!(f, "]")
+    }
+}
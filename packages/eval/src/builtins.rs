@@ -0,0 +1,233 @@
+//! The builtin functions that work on Go's aggregate types, pointers and complex numbers
+
+use crate::arguments::Arguments;
+use crate::channel::Channel;
+use crate::map::Map;
+use crate::numeric;
+use crate::place::Pointer;
+use crate::slice::Slice;
+use crate::variable::Variable;
+use crate::{EvalResult, Number, Numeric, RuntimeError, RuntimeResult, Type, Value};
+
+/// `len(v)`, which for a string is its length in bytes
+pub(crate) fn len(arguments: Arguments) -> EvalResult {
+    let [value] = arguments.exactly()?;
+    Ok(Value::Int(match &value {
+        Value::Slice(slice) => slice.len(),
+        Value::Array(array) => array.len(),
+        Value::Map(map) => map.len(),
+        Value::Channel(channel) => channel.len(),
+        Value::String(s) => s.len(),
+        Value::Nil => 0,
+        value => return Err(invalid("len", value)),
+    } as i64))
+}
+
+/// `cap(v)`
+pub(crate) fn cap(arguments: Arguments) -> EvalResult {
+    let [value] = arguments.exactly()?;
+    Ok(Value::Int(match &value {
+        Value::Slice(slice) => slice.cap(),
+        Value::Array(array) => array.len(),
+        Value::Channel(channel) => channel.cap(),
+        Value::Nil => 0,
+        value => return Err(invalid("cap", value)),
+    } as i64))
+}
+
+/// `append(s, values...)`
+///
+/// Appending to an untyped `nil` gives a slice of whatever type the first value has.
+pub(crate) fn append(arguments: Arguments) -> EvalResult {
+    let (slice, values) = arguments.variadic(1)?;
+    let values = values.into_values()?;
+    let slice = match slice.into_iter().next() {
+        Some(Value::Slice(slice)) => slice,
+        Some(Value::Nil) => match values.first() {
+            Some(value) => Slice::nil(value.as_type()),
+            None => return Ok(Value::Nil),
+        },
+        Some(value) => return Err(invalid("append", &value)),
+        None => unreachable!("variadic gives one fixed argument"),
+    };
+    let values = values
+        .into_iter()
+        .map(|value| slice.element().implicit(value))
+        .collect::<RuntimeResult<Vec<_>>>()?;
+    if let Some(value) = values.iter().find(|v| !slice.element().accepts(v)) {
+        return Err(RuntimeError::TypeError {
+            expected: slice.element().clone(),
+            found: value.as_type(),
+        });
+    }
+    Ok(Value::Slice(slice.append(values)?))
+}
+
+/// `copy(dst, src)`, which copies as many elements as both slices have.  The source may also be a
+/// string, whose bytes are copied into a `[]byte`.
+pub(crate) fn copy(arguments: Arguments) -> EvalResult {
+    let [destination, source] = arguments.exactly()?;
+    let source = match source {
+        Value::Slice(source) => source.values(),
+        Value::String(source) => source
+            .bytes()
+            .map(|byte| Type::Numeric(Numeric::Uint8).implicit(Value::Int(byte.into())))
+            .collect::<RuntimeResult<_>>()?,
+        Value::Nil => vec![],
+        value => return Err(invalid("copy", &value)),
+    };
+    Ok(Value::Int(match destination {
+        Value::Slice(destination) => destination.copy_from(source),
+        Value::Nil => 0,
+        value => return Err(invalid("copy", &value)),
+    } as i64))
+}
+
+/// `delete(m, key)`
+pub(crate) fn delete(arguments: Arguments) -> EvalResult {
+    let [map, key] = arguments.exactly()?;
+    let Value::Map(map) = map else {
+        return Err(invalid("delete", &map));
+    };
+    let key = numeric::implicit(map.key(), key)?;
+    if !map.key().accepts(&key) {
+        return Err(RuntimeError::TypeError {
+            expected: map.key().clone(),
+            found: key.as_type(),
+        });
+    }
+    map.delete(&key)?;
+    Ok(Value::Void)
+}
+
+/// `clear(v)`, which empties a map or sets every element of a slice to its zero value
+pub(crate) fn clear(arguments: Arguments) -> EvalResult {
+    let [value] = arguments.exactly()?;
+    match &value {
+        Value::Map(map) => map.clear(),
+        Value::Slice(slice) => slice.clear()?,
+        value => return Err(invalid("clear", value)),
+    }
+    Ok(Value::Void)
+}
+
+/// `close(ch)`
+pub(crate) fn close(arguments: Arguments) -> EvalResult {
+    let [value] = arguments.exactly()?;
+    match &value {
+        Value::Channel(channel) => channel.close()?,
+        value => return Err(invalid("close", value)),
+    }
+    Ok(Value::Void)
+}
+
+/// `panic(v)`, which starts unwinding the goroutine's calls.
+///
+/// As of Go 1.21, `panic(nil)` panics with a runtime error instead, so that `recover` can tell it
+/// apart from there being no panic.
+pub(crate) fn panic(arguments: Arguments) -> EvalResult {
+    match arguments.exactly()? {
+        [Value::Nil] => Err(RuntimeError::panic("panic called with nil argument")),
+        [value] => Err(RuntimeError::Panic(value)),
+    }
+}
+
+/// `make(T, sizes...)` for a slice, a map or a channel, where a map's size is only a hint and a
+/// channel's is the size of its buffer
+pub(crate) fn make(r#type: Type, sizes: Vec<Value>) -> EvalResult {
+    let sizes = sizes
+        .iter()
+        .map(Value::as_int)
+        .collect::<RuntimeResult<Vec<_>>>()?;
+    match (&r#type, sizes.as_slice()) {
+        (Type::Slice(element), [len]) => {
+            Ok(Value::Slice(Slice::make((**element).clone(), *len, *len)?))
+        }
+        (Type::Slice(element), [len, cap]) => {
+            Ok(Value::Slice(Slice::make((**element).clone(), *len, *cap)?))
+        }
+        (Type::Slice(_), _) => Err(RuntimeError::ArgumentCount {
+            expected: 2,
+            found: sizes.len() + 1,
+        }),
+        (Type::Map(key, value), [] | [_]) => {
+            if sizes.first().is_some_and(|size| *size < 0) {
+                return Err(RuntimeError::panic(
+                    "runtime error: makemap: size out of range",
+                ));
+            }
+            Ok(Value::Map(Map::new((**key).clone(), (**value).clone())))
+        }
+        (Type::Map(..), _) => Err(RuntimeError::ArgumentCount {
+            expected: 2,
+            found: sizes.len() + 1,
+        }),
+        (Type::Channel(direction, element), [] | [_]) => {
+            let capacity = sizes.first().copied().unwrap_or(0);
+            if capacity < 0 {
+                return Err(RuntimeError::panic("makechan: size out of range"));
+            }
+            Ok(Value::Channel(
+                Channel::new((**element).clone(), capacity as usize).with_direction(*direction),
+            ))
+        }
+        (Type::Channel(..), _) => Err(RuntimeError::ArgumentCount {
+            expected: 2,
+            found: sizes.len() + 1,
+        }),
+        _ => Err(RuntimeError::InvalidArgument {
+            function: "make",
+            r#type,
+        }),
+    }
+}
+
+/// `new(T)`, a pointer to a new variable holding the zero value of `T`
+pub(crate) fn new(r#type: Type) -> EvalResult {
+    let variable = Variable::new(r#type.zero_value()?);
+    Ok(Value::Pointer(Pointer::new(variable.into(), r#type)))
+}
+
+/// `complex(real, imaginary)`, where an untyped constant part takes the type of the other, or is
+/// a `float64` if both are untyped
+pub(crate) fn complex(arguments: Arguments) -> EvalResult {
+    let [real, imaginary] = arguments.exactly()?;
+    let part = |other: &Value| match other {
+        Value::Int(_) => Type::Numeric(Numeric::Float64),
+        other => other.as_type(),
+    };
+    let real = numeric::implicit(&part(&imaginary), real)?;
+    let imaginary = numeric::implicit(&part(&real), imaginary)?;
+    let number = match (real.as_number(), imaginary.as_number()) {
+        (Some(real), Some(imaginary)) => Number::from_parts(real, imaginary),
+        _ => None,
+    };
+    number
+        .map(Number::value)
+        .ok_or_else(|| invalid("complex", &real))
+}
+
+/// `real(x)`
+pub(crate) fn real(arguments: Arguments) -> EvalResult {
+    let [value] = arguments.exactly()?;
+    match value.as_number().and_then(Number::parts) {
+        Some((real, _)) => Ok(real.value()),
+        None => Err(invalid("real", &value)),
+    }
+}
+
+/// `imag(x)`
+pub(crate) fn imag(arguments: Arguments) -> EvalResult {
+    let [value] = arguments.exactly()?;
+    match value.as_number().and_then(Number::parts) {
+        Some((_, imaginary)) => Ok(imaginary.value()),
+        None => Err(invalid("imag", &value)),
+    }
+}
+
+fn invalid(function: &'static str, value: &Value) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        function,
+        r#type: value.as_type(),
+    }
+}
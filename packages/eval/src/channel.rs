@@ -0,0 +1,391 @@
+use crate::goroutine::{self, Blocked};
+use crate::{RuntimeError, RuntimeResult, Type, Value};
+use gor_ast::types::ChannelDirection;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+/// The value of a channel: a reference to a queue, shared with every copy of the channel.
+///
+/// A goroutine that has to wait to send or receive parks its task until another goroutine
+/// completes the operation for it, so it never holds up the thread it was running on.
+#[derive(Clone)]
+pub struct Channel {
+    element: Box<Type>,
+    direction: ChannelDirection,
+    /// A nil channel has no queue, so sending to it or receiving from it blocks forever
+    queue: Option<Arc<Queue>>,
+}
+
+#[derive(Debug)]
+struct Queue {
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    buffer: VecDeque<Value>,
+    closed: bool,
+    /// Goroutines waiting for a receiver, along with their case and the value they're sending
+    senders: VecDeque<(Arc<Waiter>, usize, Value)>,
+    /// Goroutines waiting for a sender, along with their case
+    receivers: VecDeque<(Arc<Waiter>, usize)>,
+}
+
+/// How a goroutine's wait on a channel ended
+#[derive(Debug, Clone)]
+enum Completion {
+    /// A receiver took the value being sent
+    Sent,
+    Received(Value),
+    Closed,
+}
+
+/// A goroutine waiting on one or more channels, which is woken by the goroutine that completes
+/// the first of its cases to become ready
+#[derive(Debug)]
+struct Waiter {
+    completion: Mutex<Option<(usize, Completion)>>,
+    notify: Notify,
+    blocked: Blocked,
+}
+
+impl Channel {
+    pub(crate) fn nil(element: Type) -> Self {
+        Channel {
+            element: Box::new(element),
+            direction: ChannelDirection::Both,
+            queue: None,
+        }
+    }
+
+    /// `make(chan T, capacity)`, where an unbuffered channel has no capacity
+    pub(crate) fn new(element: Type, capacity: usize) -> Self {
+        Channel {
+            element: Box::new(element),
+            direction: ChannelDirection::Both,
+            queue: Some(Arc::new(Queue {
+                capacity,
+                state: Default::default(),
+            })),
+        }
+    }
+
+    pub fn element(&self) -> &Type {
+        &self.element
+    }
+
+    pub fn direction(&self) -> ChannelDirection {
+        self.direction
+    }
+
+    /// The same channel, which may only be used in one direction
+    pub(crate) fn with_direction(self, direction: ChannelDirection) -> Self {
+        Channel { direction, ..self }
+    }
+
+    /// How many values are waiting in the buffer
+    pub fn len(&self) -> usize {
+        match &self.queue {
+            Some(queue) => queue.state.lock().unwrap().buffer.len(),
+            None => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn cap(&self) -> usize {
+        self.queue.as_ref().map_or(0, |queue| queue.capacity)
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.queue.is_none()
+    }
+
+    /// Identifies the queue, so that copies of a channel compare as equal
+    pub(crate) fn address(&self) -> usize {
+        self.queue
+            .as_ref()
+            .map_or(0, |queue| Arc::as_ptr(queue) as usize)
+    }
+
+    /// `ch <- value`, which waits for a receiver unless there's room in the buffer.
+    ///
+    /// Sending on a closed channel panics, including when it's closed while waiting.
+    pub(crate) async fn send(&self, value: Value) -> RuntimeResult<()> {
+        select(vec![Case::Send(self.clone(), value)], false).await?;
+        Ok(())
+    }
+
+    /// `v, ok := <-ch`, which waits for a sender unless there's a value in the buffer.
+    ///
+    /// Once a closed channel is empty, it gives the zero value without waiting, and `ok` is false.
+    pub(crate) async fn receive(&self) -> RuntimeResult<(Value, bool)> {
+        match select(vec![Case::Receive(self.clone())], false).await? {
+            Selected::Received(_, value, ok) => Ok((value, ok)),
+            selected => unreachable!("a receive can't have {:?}", selected),
+        }
+    }
+
+    /// `close(ch)`, which wakes every goroutine waiting on the channel
+    pub(crate) fn close(&self) -> RuntimeResult<()> {
+        self.allows(ChannelDirection::Send, "close")?;
+        let Some(queue) = &self.queue else {
+            return Err(RuntimeError::panic("close of nil channel"));
+        };
+        let mut state = queue.state.lock().unwrap();
+        if state.closed {
+            return Err(RuntimeError::panic("close of closed channel"));
+        }
+        state.closed = true;
+        for (receiver, case) in state.receivers.drain(..) {
+            receiver.complete(case, Completion::Closed);
+        }
+        for (sender, case, _) in state.senders.drain(..) {
+            sender.complete(case, Completion::Closed);
+        }
+        Ok(())
+    }
+
+    fn allows(&self, direction: ChannelDirection, operation: &'static str) -> RuntimeResult<()> {
+        if self.direction == ChannelDirection::Both || self.direction == direction {
+            Ok(())
+        } else {
+            Err(RuntimeError::ChannelDirection {
+                operation,
+                r#type: Type::Channel(self.direction, self.element.clone()),
+            })
+        }
+    }
+}
+
+/// One of the communications that a `select` chooses between
+#[derive(Debug)]
+pub(crate) enum Case {
+    Send(Channel, Value),
+    Receive(Channel),
+}
+
+/// The outcome of a `select`, with the index of the case that went ahead
+#[derive(Debug)]
+pub(crate) enum Selected {
+    /// No case was ready, so the `default` clause runs instead
+    Default,
+    Sent(usize),
+    /// The value received, or the zero value along with `false` once the channel's closed
+    Received(usize, Value, bool),
+}
+
+/// Go ahead with one of the cases, chosen uniformly at random from those that are ready.
+///
+/// If none are ready, a `select` with a `default` clause gives up straight away, while one without
+/// waits for the first case that becomes ready.  A nil channel never becomes ready, so a `select`
+/// with only nil channels, or no cases at all, waits forever.
+pub(crate) async fn select(cases: Vec<Case>, default: bool) -> RuntimeResult<Selected> {
+    for case in &cases {
+        match case {
+            Case::Send(channel, _) => channel.allows(ChannelDirection::Send, "send to")?,
+            Case::Receive(channel) => channel.allows(ChannelDirection::Receive, "receive from")?,
+        }
+    }
+    let waiter = {
+        let mut states = lock(&cases);
+        let mut order = (0..cases.len()).collect::<Vec<_>>();
+        fastrand::shuffle(&mut order);
+        for index in order {
+            let case = &cases[index];
+            let Some(state) = states.get_mut(&case.channel().address()) else {
+                continue;
+            };
+            let completion = match case {
+                Case::Send(channel, value) => {
+                    let capacity = channel.cap();
+                    match state.try_send(capacity, value.clone())? {
+                        Some(_) => None,
+                        None => Some(Completion::Sent),
+                    }
+                }
+                Case::Receive(_) => state.try_receive(),
+            };
+            if let Some(completion) = completion {
+                return case.completed(index, completion);
+            }
+        }
+        if default {
+            return Ok(Selected::Default);
+        }
+        let waiter = Waiter::new();
+        for (index, case) in cases.iter().enumerate() {
+            if let Some(state) = states.get_mut(&case.channel().address()) {
+                match case {
+                    Case::Send(_, value) => {
+                        state
+                            .senders
+                            .push_back((waiter.clone(), index, value.clone()))
+                    }
+                    Case::Receive(_) => state.receivers.push_back((waiter.clone(), index)),
+                }
+            }
+        }
+        waiter
+    };
+    let (index, completion) = waiter.wait().await;
+    // Stop waiting on the cases that didn't go ahead
+    for state in lock(&cases).values_mut() {
+        state
+            .senders
+            .retain(|(other, ..)| !Arc::ptr_eq(other, &waiter));
+        state
+            .receivers
+            .retain(|(other, _)| !Arc::ptr_eq(other, &waiter));
+    }
+    cases[index].completed(index, completion)
+}
+
+/// Lock the queue of every channel in the cases, in order of address so that two goroutines
+/// can't each be holding a lock that the other is waiting for.  Nil channels have no queue.
+fn lock(cases: &[Case]) -> BTreeMap<usize, MutexGuard<'_, State>> {
+    let queues = cases
+        .iter()
+        .filter_map(|case| {
+            let channel = case.channel();
+            Some((channel.address(), channel.queue.as_deref()?))
+        })
+        .collect::<BTreeMap<_, _>>();
+    queues
+        .into_iter()
+        .map(|(address, queue)| (address, queue.state.lock().unwrap()))
+        .collect()
+}
+
+impl Case {
+    fn channel(&self) -> &Channel {
+        match self {
+            Case::Send(channel, _) | Case::Receive(channel) => channel,
+        }
+    }
+
+    fn completed(&self, index: usize, completion: Completion) -> RuntimeResult<Selected> {
+        match (self, completion) {
+            (Case::Send(..), Completion::Closed) => Err(closed_send()),
+            (Case::Send(..), _) => Ok(Selected::Sent(index)),
+            (Case::Receive(_), Completion::Received(value)) => {
+                Ok(Selected::Received(index, value, true))
+            }
+            (Case::Receive(channel), _) => Ok(Selected::Received(
+                index,
+                channel.element.zero_value()?,
+                false,
+            )),
+        }
+    }
+}
+
+impl State {
+    /// Send without waiting, if a receiver is already waiting or there's room in the buffer.
+    /// Otherwise, the value is handed back.
+    fn try_send(&mut self, capacity: usize, value: Value) -> RuntimeResult<Option<Value>> {
+        if self.closed {
+            return Err(closed_send());
+        }
+        while let Some((receiver, case)) = self.receivers.pop_front() {
+            if receiver.complete(case, Completion::Received(value.clone())) {
+                return Ok(None);
+            }
+        }
+        if self.buffer.len() < capacity {
+            self.buffer.push_back(value);
+            return Ok(None);
+        }
+        Ok(Some(value))
+    }
+
+    /// Receive without waiting, if there's a value in the buffer, a sender waiting, or the
+    /// channel's closed
+    fn try_receive(&mut self) -> Option<Completion> {
+        if let Some(value) = self.buffer.pop_front() {
+            // The first sender still waiting takes the space that's been freed
+            while let Some((sender, case, pending)) = self.senders.pop_front() {
+                if sender.complete(case, Completion::Sent) {
+                    self.buffer.push_back(pending);
+                    break;
+                }
+            }
+            return Some(Completion::Received(value));
+        }
+        while let Some((sender, case, value)) = self.senders.pop_front() {
+            if sender.complete(case, Completion::Sent) {
+                return Some(Completion::Received(value));
+            }
+        }
+        self.closed.then_some(Completion::Closed)
+    }
+}
+
+impl Waiter {
+    fn new() -> Arc<Self> {
+        Arc::new(Waiter {
+            completion: Mutex::new(None),
+            notify: Notify::new(),
+            blocked: goroutine::block(),
+        })
+    }
+
+    /// Wake the waiting goroutine, unless another of its cases has already gone ahead
+    fn complete(&self, case: usize, completion: Completion) -> bool {
+        let mut current = self.completion.lock().unwrap();
+        if current.is_some() {
+            return false;
+        }
+        *current = Some((case, completion));
+        self.blocked.wake();
+        self.notify.notify_one();
+        true
+    }
+
+    async fn wait(&self) -> (usize, Completion) {
+        loop {
+            let completion = self.completion.lock().unwrap().clone();
+            if let Some(completion) = completion {
+                return completion;
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+fn closed_send() -> RuntimeError {
+    RuntimeError::panic("send on closed channel")
+}
+
+/// Channels are the same if they were made by the same call to `make`
+impl PartialEq for Channel {
+    fn eq(&self, other: &Self) -> bool {
+        self.address() == other.address() && self.element == other.element
+    }
+}
+
+impl Debug for Channel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Channel({} {})",
+            Type::Channel(self.direction, self.element.clone()),
+            self
+        )
+    }
+}
+
+/// A channel prints as the address of its queue
+impl Display for Channel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.queue {
+            Some(queue) => write!(f, "{:p}", Arc::as_ptr(queue)),
+            None => write!(f, "<nil>"),
+        }
+    }
+}
@@ -0,0 +1,706 @@
+use crate::extensions::TypeExt;
+use crate::numeric::format_float;
+use crate::{
+    EvalResult, ExecutionContext, Intrinsic, Number, Numeric, PackageContext, RuntimeError,
+    RuntimeResult, Type, Value,
+};
+use gor_ast::binary_op::BinOp;
+use gor_ast::declaration::ConstSpec;
+use gor_ast::expression::{Expression, InnerExpression};
+use gor_ast::name::Name;
+use gor_ast::unitary_op::UniOp;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{ToPrimitive, Zero};
+use std::cell::RefCell;
+
+/// The most bits an untyped integer constant may need, where Go only promises at least 256
+const PRECISION: u64 = 512;
+
+/// The largest decimal exponent a float literal may have, which is far beyond what any float type
+/// can represent
+const MAX_EXPONENT: u32 = 10_000;
+
+thread_local! {
+    /// The package-level constants whose declarations are being evaluated, so that one which
+    /// refers to itself fails rather than recursing forever.  Evaluation never awaits, so nothing
+    /// else can run on the thread in the meantime.
+    static EVALUATING: RefCell<Vec<(Name, Name)>> = const { RefCell::new(vec![]) };
+}
+
+/// The value of a constant expression.
+///
+/// Arithmetic on constants is exact, so an untyped constant only has to fit its type once it's
+/// used as a value: `1 << 100 >> 98` is 4, and `0.1 + 0.2 == 0.3`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constant {
+    exact: Exact,
+    /// The type given by a declaration or a conversion, or `None` for an untyped constant
+    r#type: Option<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Exact {
+    Int(BigInt),
+    Float(BigRational),
+    /// The real and imaginary parts of a complex number
+    Complex(BigRational, BigRational),
+    Boolean(bool),
+    String(String),
+}
+
+impl Exact {
+    /// The type an untyped constant of this kind takes when it's used as a value
+    fn default_type(&self) -> Type {
+        match self {
+            Exact::Int(_) => Type::Int,
+            Exact::Float(_) => Type::Numeric(Numeric::Float64),
+            Exact::Complex(..) => Type::Numeric(Numeric::Complex128),
+            Exact::Boolean(_) => Type::Boolean,
+            Exact::String(_) => Type::String,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Exact::Int(n) => n.to_string(),
+            Exact::Float(r) => describe(r),
+            Exact::Complex(real, imaginary) => {
+                let imaginary = describe(imaginary);
+                let sign = if imaginary.starts_with('-') { "" } else { "+" };
+                format!("({}{}{}i)", describe(real), sign, imaginary)
+            }
+            Exact::Boolean(b) => b.to_string(),
+            Exact::String(s) => format!("{:?}", s),
+        }
+    }
+}
+
+fn describe(r: &BigRational) -> String {
+    if r.is_integer() {
+        r.to_integer().to_string()
+    } else {
+        format_float(r.to_f64().unwrap_or(f64::NAN), Numeric::Float64)
+    }
+}
+
+/// A number as a fraction, so that integers and floats can be combined
+fn rational(exact: Exact) -> BigRational {
+    match exact {
+        Exact::Int(n) => BigRational::from_integer(n),
+        Exact::Float(r) => r,
+        exact => unreachable!("only integers and floats are rational: {:?}", exact),
+    }
+}
+
+/// A number as its real and imaginary parts, so that any numbers can be combined
+fn parts(exact: Exact) -> (BigRational, BigRational) {
+    match exact {
+        Exact::Complex(real, imaginary) => (real, imaginary),
+        exact => (rational(exact), BigRational::zero()),
+    }
+}
+
+fn is_number(exact: &Exact) -> bool {
+    matches!(exact, Exact::Int(_) | Exact::Float(_) | Exact::Complex(..))
+}
+
+impl Constant {
+    fn untyped(exact: Exact) -> Self {
+        Constant {
+            exact,
+            r#type: None,
+        }
+    }
+
+    pub(crate) fn boolean(b: bool) -> Self {
+        Constant::untyped(Exact::Boolean(b))
+    }
+
+    pub(crate) fn r#type(&self) -> Type {
+        self.r#type
+            .clone()
+            .unwrap_or_else(|| self.exact.default_type())
+    }
+
+    /// The constant as a value of its type, or of the default type for its kind if it's untyped
+    pub(crate) fn value(self) -> EvalResult {
+        let r#type = self.r#type();
+        Ok(match self.convert(r#type.clone())?.exact {
+            Exact::Int(n) => Number::integer(&r#type, n.to_i128().unwrap_or_default())?.value(),
+            Exact::Float(r) => Number::float(&r#type, r.to_f64().unwrap_or(f64::NAN))?.value(),
+            Exact::Complex(real, imaginary) => Number::complex(
+                &r#type,
+                real.to_f64().unwrap_or(f64::NAN),
+                imaginary.to_f64().unwrap_or(f64::NAN),
+            )?
+            .value(),
+            Exact::Boolean(b) => Value::Boolean(b),
+            Exact::String(s) => Value::String(s),
+        })
+    }
+
+    /// The constant as a value of `r#type` if it's an untyped number and the type is numeric, as
+    /// in `x + 1` for a float `x`, or as its own value otherwise
+    pub(crate) fn value_as(self, r#type: &Type) -> EvalResult {
+        match self.exact {
+            Exact::Int(_) | Exact::Float(_) | Exact::Complex(..)
+                if self.r#type.is_none() && r#type.numeric().is_some() =>
+            {
+                self.convert(r#type.clone())?.value()
+            }
+            _ => self.value(),
+        }
+    }
+
+    /// Give the constant a type, which has to be able to represent its value: an integer type
+    /// needs a whole number in its range, while a float type rounds it to its precision.  A complex
+    /// number only has a non-complex type if its imaginary part is zero.
+    fn convert(self, r#type: Type) -> RuntimeResult<Self> {
+        let found = self.r#type();
+        let overflow = |exact: &Exact| RuntimeError::ConstantOverflow {
+            value: exact.describe(),
+            r#type: r#type.clone(),
+        };
+        let exact = match (self.exact, r#type.numeric()) {
+            (Exact::Complex(real, imaginary), Some(numeric)) if !numeric.is_complex() => {
+                if !imaginary.is_zero() {
+                    return Err(RuntimeError::ConstantTruncated {
+                        value: Exact::Complex(real, imaginary).describe(),
+                        r#type,
+                    });
+                }
+                return Constant::untyped(Exact::Float(real)).convert(r#type);
+            }
+            (exact, Some(numeric)) if numeric.is_complex() && is_number(&exact) => {
+                let (real, imaginary) = parts(exact);
+                let round = |r: &BigRational| {
+                    BigRational::from_float(numeric.round(r.to_f64().unwrap_or(f64::NAN)))
+                };
+                match (round(&real), round(&imaginary)) {
+                    (Some(rounded_real), Some(rounded_imaginary)) => {
+                        Exact::Complex(rounded_real, rounded_imaginary)
+                    }
+                    _ => return Err(overflow(&Exact::Complex(real, imaginary))),
+                }
+            }
+            (Exact::Float(r), Some(numeric)) if numeric.is_integer() && !r.is_integer() => {
+                return Err(RuntimeError::ConstantTruncated {
+                    value: Exact::Float(r).describe(),
+                    r#type,
+                })
+            }
+            (exact @ (Exact::Int(_) | Exact::Float(_)), Some(numeric)) if numeric.is_integer() => {
+                let n = rational(exact).to_integer();
+                if n < BigInt::from(numeric.min()) || n > BigInt::from(numeric.max()) {
+                    return Err(overflow(&Exact::Int(n)));
+                }
+                Exact::Int(n)
+            }
+            (exact @ (Exact::Int(_) | Exact::Float(_)), Some(numeric)) => {
+                let r = rational(exact);
+                let rounded = numeric.round(r.to_f64().unwrap_or(f64::NAN));
+                Exact::Float(
+                    BigRational::from_float(rounded).ok_or_else(|| overflow(&Exact::Float(r)))?,
+                )
+            }
+            (exact, None) if exact.default_type() == r#type => exact,
+            _ => {
+                return Err(RuntimeError::TypeError {
+                    expected: r#type,
+                    found,
+                })
+            }
+        };
+        Ok(Constant {
+            exact,
+            r#type: Some(r#type),
+        })
+    }
+
+    fn uni_op(self, op: UniOp) -> RuntimeResult<Self> {
+        let found = self.r#type();
+        let exact = match (op, self.exact) {
+            (UniOp::Negate, Exact::Int(n)) => Exact::Int(-n),
+            (UniOp::Negate, Exact::Float(r)) => Exact::Float(-r),
+            (UniOp::Negate, Exact::Complex(real, imaginary)) => Exact::Complex(-real, -imaginary),
+            (UniOp::Not, Exact::Boolean(b)) => Exact::Boolean(!b),
+            (UniOp::Negate, _) => {
+                return Err(RuntimeError::TypeError {
+                    expected: Type::Int,
+                    found,
+                })
+            }
+            (UniOp::Not, _) => {
+                return Err(RuntimeError::TypeError {
+                    expected: Type::Boolean,
+                    found,
+                })
+            }
+            (op, _) => return Err(RuntimeError::StaticEvaluationFailure(format!("{:?}", op))),
+        };
+        Ok(Constant {
+            exact,
+            r#type: self.r#type,
+        })
+    }
+
+    /// Apply `op`, where a typed operand gives its type to an untyped one and comparisons give
+    /// untyped booleans.  An untyped integer combined with an untyped float becomes a float, and
+    /// either combined with a complex number becomes complex.
+    fn bin_op(self, op: BinOp, right: Constant) -> RuntimeResult<Self> {
+        if matches!(op, BinOp::Shl | BinOp::Shr) {
+            return self.shift(op, right);
+        }
+        let (left, right) = match (&self.r#type, &right.r#type) {
+            (Some(left), Some(right)) if left != right => {
+                return Err(RuntimeError::TypeMismatch {
+                    left: left.clone(),
+                    op,
+                    right: right.clone(),
+                })
+            }
+            (Some(r#type), None) => {
+                let r#type = r#type.clone();
+                (self, right.convert(r#type)?)
+            }
+            (None, Some(r#type)) => {
+                let r#type = r#type.clone();
+                (self.convert(r#type)?, right)
+            }
+            _ => (self, right),
+        };
+        let r#type = left.r#type.clone().or_else(|| right.r#type.clone());
+        let (left_type, right_type) = (left.r#type(), right.r#type());
+        let exact = match (left.exact, right.exact) {
+            (Exact::Int(left), Exact::Int(right)) => match op {
+                BinOp::Eq => Exact::Boolean(left == right),
+                BinOp::Neq => Exact::Boolean(left != right),
+                BinOp::Lt => Exact::Boolean(left < right),
+                BinOp::Leq => Exact::Boolean(left <= right),
+                BinOp::Gt => Exact::Boolean(left > right),
+                BinOp::Geq => Exact::Boolean(left >= right),
+                BinOp::Add => Exact::Int(left + right),
+                BinOp::Sub => Exact::Int(left - right),
+                BinOp::Mul => Exact::Int(left * right),
+                BinOp::Div | BinOp::Modulo if right.is_zero() => {
+                    return Err(RuntimeError::DivisionByZero)
+                }
+                BinOp::Div => Exact::Int(left / right),
+                BinOp::Modulo => Exact::Int(left % right),
+                BinOp::BitOr => Exact::Int(left | right),
+                BinOp::BitXor => Exact::Int(left ^ right),
+                BinOp::BitAnd => Exact::Int(left & right),
+                BinOp::BitClear => Exact::Int(left & !right),
+                // As with values, integers stand in for booleans
+                BinOp::LogicalAnd => Exact::Boolean(!left.is_zero() && !right.is_zero()),
+                BinOp::LogicalOr => Exact::Boolean(!left.is_zero() || !right.is_zero()),
+                op => {
+                    return Err(RuntimeError::TypeOpMismatch {
+                        op,
+                        r#type: left_type,
+                    })
+                }
+            },
+            (
+                left @ (Exact::Int(_) | Exact::Float(_)),
+                right @ (Exact::Int(_) | Exact::Float(_)),
+            ) => {
+                let (left, right) = (rational(left), rational(right));
+                match op {
+                    BinOp::Eq => Exact::Boolean(left == right),
+                    BinOp::Neq => Exact::Boolean(left != right),
+                    BinOp::Lt => Exact::Boolean(left < right),
+                    BinOp::Leq => Exact::Boolean(left <= right),
+                    BinOp::Gt => Exact::Boolean(left > right),
+                    BinOp::Geq => Exact::Boolean(left >= right),
+                    BinOp::Add => Exact::Float(left + right),
+                    BinOp::Sub => Exact::Float(left - right),
+                    BinOp::Mul => Exact::Float(left * right),
+                    BinOp::Div if right.is_zero() => return Err(RuntimeError::DivisionByZero),
+                    BinOp::Div => Exact::Float(left / right),
+                    op => {
+                        return Err(RuntimeError::TypeOpMismatch {
+                            op,
+                            r#type: left_type,
+                        })
+                    }
+                }
+            }
+            (left, right) if is_number(&left) && is_number(&right) => {
+                let ((a, b), (c, d)) = (parts(left), parts(right));
+                match op {
+                    BinOp::Eq => Exact::Boolean(a == c && b == d),
+                    BinOp::Neq => Exact::Boolean(a != c || b != d),
+                    BinOp::Add => Exact::Complex(a + c, b + d),
+                    BinOp::Sub => Exact::Complex(a - c, b - d),
+                    BinOp::Mul => Exact::Complex(&a * &c - &b * &d, &a * &d + &b * &c),
+                    BinOp::Div if c.is_zero() && d.is_zero() => {
+                        return Err(RuntimeError::DivisionByZero)
+                    }
+                    BinOp::Div => {
+                        let denominator = &c * &c + &d * &d;
+                        Exact::Complex(
+                            (&a * &c + &b * &d) / &denominator,
+                            (b * c - a * d) / denominator,
+                        )
+                    }
+                    op => {
+                        return Err(RuntimeError::TypeOpMismatch {
+                            op,
+                            r#type: if left_type.numeric().is_some_and(Numeric::is_complex) {
+                                left_type
+                            } else {
+                                right_type
+                            },
+                        })
+                    }
+                }
+            }
+            // Booleans and strings behave just as their values do
+            (Exact::Boolean(left), Exact::Boolean(right)) => {
+                exact(Value::Boolean(left).bin_op(op, Value::Boolean(right))?)
+            }
+            (Exact::String(left), Exact::String(right)) => {
+                exact(Value::String(left).bin_op(op, Value::String(right))?)
+            }
+            _ => {
+                return Err(RuntimeError::TypeMismatch {
+                    left: left_type,
+                    op,
+                    right: right_type,
+                })
+            }
+        };
+        let comparison = matches!(
+            op,
+            BinOp::Eq | BinOp::Neq | BinOp::Lt | BinOp::Leq | BinOp::Gt | BinOp::Geq
+        );
+        Ok(Constant {
+            exact,
+            r#type: if comparison { None } else { r#type },
+        })
+    }
+
+    /// Shift by a count that doesn't need to have the same type, where the result has the type of
+    /// the left operand.  An untyped float that's a whole number may stand in for an integer.
+    fn shift(self, op: BinOp, count: Constant) -> RuntimeResult<Self> {
+        let found = count.r#type();
+        let count = match count.exact {
+            Exact::Int(n) => n,
+            Exact::Float(r) if r.is_integer() && count.r#type.is_none() => r.to_integer(),
+            _ => {
+                return Err(RuntimeError::TypeError {
+                    expected: Type::Int,
+                    found,
+                })
+            }
+        };
+        let r#type = self.r#type();
+        let n = match self.exact {
+            Exact::Int(n) => n,
+            Exact::Float(r) if r.is_integer() && self.r#type.is_none() => r.to_integer(),
+            _ => return Err(RuntimeError::TypeOpMismatch { op, r#type }),
+        };
+        let count = count
+            .to_u64()
+            .ok_or_else(|| RuntimeError::InvalidShiftCount(count.to_string()))?;
+        // Shifting any further would give the same result, or be out of range anyway
+        let count = count.min(PRECISION + 1) as usize;
+        let exact = match op {
+            BinOp::Shl => Exact::Int(n << count),
+            _ => Exact::Int(n >> count),
+        };
+        Ok(Constant {
+            exact,
+            r#type: self.r#type,
+        })
+    }
+
+    /// Check that a typed constant fits its type, rounding a float to its precision, and that an
+    /// untyped one isn't too large
+    fn checked(self, exp: &Expression) -> RuntimeResult<Self> {
+        match (&self.exact, &self.r#type) {
+            (_, Some(r#type)) => {
+                let r#type = r#type.clone();
+                self.convert(r#type)
+            }
+            (Exact::Int(n), None) if n.bits() > PRECISION => Err(precision(exp)),
+            _ => Ok(self),
+        }
+    }
+}
+
+fn exact(value: Value) -> Exact {
+    match value {
+        Value::Boolean(b) => Exact::Boolean(b),
+        Value::String(s) => Exact::String(s),
+        value => unreachable!(
+            "booleans and strings only give booleans and strings: {}",
+            value
+        ),
+    }
+}
+
+/// Evaluate a constant expression exactly, looking up names as constants in `context`.
+///
+/// Fails with [RuntimeError::StaticEvaluationFailure] if the expression isn't constant, so that it
+/// can be evaluated as a value instead.
+pub(crate) fn evaluate(
+    exp: &Expression,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Constant> {
+    of(exp, context)?.ok_or_else(|| not_constant(exp))
+}
+
+/// The constant that an expression evaluates to, or `None` if it isn't constant.
+///
+/// Whether it's constant is remembered by the expression, so that an expression that isn't is only
+/// tried once rather than every time it's evaluated.
+pub(crate) fn of(
+    exp: &Expression,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Option<Constant>> {
+    if exp.is_constant() == Some(false) {
+        return Ok(None);
+    }
+    match fold(exp, context) {
+        Ok(constant) => {
+            exp.set_constant(true);
+            Ok(Some(constant))
+        }
+        Err(RuntimeError::StaticEvaluationFailure(_)) => {
+            exp.set_constant(false);
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+fn fold(exp: &Expression, context: &dyn ExecutionContext) -> RuntimeResult<Constant> {
+    let constant = match &exp.inner {
+        InnerExpression::Number(n) => Constant::untyped(Exact::Int(BigInt::from(*n))),
+        InnerExpression::Float(f) => {
+            Constant::untyped(Exact::Float(decimal(f).ok_or_else(|| precision(exp))?))
+        }
+        InnerExpression::Imaginary(f) => Constant::untyped(Exact::Complex(
+            BigRational::zero(),
+            decimal(f).ok_or_else(|| precision(exp))?,
+        )),
+        InnerExpression::String(s) => Constant::untyped(Exact::String(s.clone())),
+        InnerExpression::Name(name) => context.constant(*name)?,
+        InnerExpression::UniOp { op, exp } => evaluate(exp, context)?.uni_op(*op)?,
+        InnerExpression::BinOp { left, op, right } if *op != BinOp::Dot => {
+            evaluate(left, context)?.bin_op(*op, evaluate(right, context)?)?
+        }
+        // A conversion of a constant to a basic type, like `Weekday(3)` or `float32(0.1)`
+        InnerExpression::Call {
+            name,
+            parameters,
+            spread: false,
+        } => match context.r#type(*name) {
+            Ok(r#type)
+                if parameters.len() == 1
+                    && (r#type.numeric().is_some()
+                        || matches!(r#type, Type::Boolean | Type::String)) =>
+            {
+                evaluate(&parameters[0], context)?.convert(r#type)?
+            }
+            Ok(_) => return Err(not_constant(exp)),
+            Err(_) => match context.value(*name) {
+                Ok(Value::Intrinsic(intrinsic)) => {
+                    builtin(intrinsic, parameters, context)?.ok_or_else(|| not_constant(exp))?
+                }
+                _ => return Err(not_constant(exp)),
+            },
+        },
+        _ => return Err(not_constant(exp)),
+    };
+    constant.checked(exp)
+}
+
+/// `complex`, `real` or `imag` applied to constants, which gives a constant: the parts of an
+/// untyped number are untyped floats, while those of a `complex64` are `float32`s
+fn builtin(
+    intrinsic: Intrinsic,
+    parameters: &[Expression],
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Option<Constant>> {
+    if !matches!(
+        intrinsic,
+        Intrinsic::Complex | Intrinsic::Real | Intrinsic::Imag
+    ) {
+        return Ok(None);
+    }
+    let mut arguments = parameters
+        .iter()
+        .map(|parameter| evaluate(parameter, context))
+        .collect::<RuntimeResult<Vec<_>>>()?;
+    let expected = if intrinsic == Intrinsic::Complex {
+        2
+    } else {
+        1
+    };
+    if arguments.len() != expected {
+        return Err(RuntimeError::ArgumentCount {
+            expected,
+            found: arguments.len(),
+        });
+    }
+    let complex = Type::Numeric(Numeric::Complex128);
+    if intrinsic != Intrinsic::Complex {
+        let argument = arguments.remove(0);
+        let found = argument.r#type();
+        let r#type = match argument.r#type {
+            Some(r#type) => Some(
+                r#type
+                    .numeric()
+                    .and_then(Numeric::part)
+                    .map(Type::Numeric)
+                    .ok_or(RuntimeError::TypeError {
+                        expected: complex,
+                        found,
+                    })?,
+            ),
+            None if is_number(&argument.exact) => None,
+            None => {
+                return Err(RuntimeError::TypeError {
+                    expected: complex,
+                    found,
+                })
+            }
+        };
+        let (real, imaginary) = parts(argument.exact);
+        let part = if intrinsic == Intrinsic::Real {
+            real
+        } else {
+            imaginary
+        };
+        return Ok(Some(Constant {
+            exact: Exact::Float(part),
+            r#type,
+        }));
+    }
+    let float = Type::Numeric(Numeric::Float64);
+    let (real, imaginary) = (arguments.remove(0), arguments.remove(0));
+    // An untyped part takes the type of the other, as in arithmetic
+    let (real, imaginary, r#type) = match (&real.r#type, &imaginary.r#type) {
+        (Some(left), Some(right)) if left != right => {
+            return Err(RuntimeError::TypeError {
+                expected: left.clone(),
+                found: right.clone(),
+            })
+        }
+        (Some(r#type), _) | (_, Some(r#type)) => {
+            let r#type = r#type.clone();
+            let complex = r#type
+                .numeric()
+                .and_then(Numeric::complex)
+                .map(Type::Numeric)
+                .ok_or_else(|| RuntimeError::TypeError {
+                    expected: float.clone(),
+                    found: r#type.clone(),
+                })?;
+            (
+                real.convert(r#type.clone())?,
+                imaginary.convert(r#type)?,
+                Some(complex),
+            )
+        }
+        (None, None) => {
+            let real = real.convert(float.clone())?;
+            let imaginary = imaginary.convert(float)?;
+            (real, imaginary, None)
+        }
+    };
+    Ok(Some(Constant {
+        exact: Exact::Complex(rational(real.exact), rational(imaginary.exact)),
+        r#type,
+    }))
+}
+
+fn not_constant(exp: &Expression) -> RuntimeError {
+    RuntimeError::StaticEvaluationFailure(exp.span.as_str().to_string())
+}
+
+fn precision(exp: &Expression) -> RuntimeError {
+    RuntimeError::PrecisionOverflow(exp.span.as_str().to_string())
+}
+
+/// The constant an expression evaluates to, if it's an untyped constant
+pub(crate) fn untyped(
+    exp: &Expression,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Option<Constant>> {
+    Ok(of(exp, context)?.filter(|constant| constant.r#type.is_none()))
+}
+
+/// The exact value of a decimal floating-point literal, unless its exponent is out of all
+/// proportion
+fn decimal(literal: &str) -> Option<BigRational> {
+    let (mantissa, exponent) = match literal.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (literal, 0),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits: BigInt = format!("{}{}", whole, fraction).parse().ok()?;
+    let exponent = exponent - fraction.len() as i64;
+    if exponent.unsigned_abs() > MAX_EXPONENT as u64 {
+        return None;
+    }
+    let scale = BigInt::from(10).pow(exponent.unsigned_abs() as u32);
+    Some(if exponent < 0 {
+        BigRational::new(digits, scale)
+    } else {
+        BigRational::from_integer(digits * scale)
+    })
+}
+
+/// The value of a constant declared in `package`, as the `index`th name in `spec`
+pub(crate) fn declared(
+    package: PackageContext,
+    name: Name,
+    spec: &ConstSpec<'_>,
+    index: usize,
+) -> RuntimeResult<Constant> {
+    let key = (package.source().package, name);
+    if EVALUATING.with(|evaluating| evaluating.borrow().contains(&key)) {
+        return Err(RuntimeError::InitializationCycle(name));
+    }
+    EVALUATING.with(|evaluating| evaluating.borrow_mut().push(key));
+    let context = Iota {
+        iota: spec.iota,
+        parent: &package,
+    };
+    let result = evaluate(&spec.values[index], &context).and_then(|constant| match &spec.r#type {
+        Some(r#type) => constant.convert(r#type.resolve(&package)?),
+        None => Ok(constant),
+    });
+    EVALUATING.with(|evaluating| evaluating.borrow_mut().pop());
+    result
+}
+
+/// The scope of a constant declaration, where `iota` is the index of the spec within it
+#[derive(Debug)]
+struct Iota<'c> {
+    iota: usize,
+    parent: &'c dyn ExecutionContext,
+}
+
+impl ExecutionContext for Iota<'_> {
+    fn constant(&self, name: Name) -> RuntimeResult<Constant> {
+        if name == "iota".into() {
+            Ok(Constant::untyped(Exact::Int(BigInt::from(self.iota))))
+        } else {
+            self.parent.constant(name)
+        }
+    }
+
+    fn r#type(&self, name: Name) -> RuntimeResult<Type> {
+        self.parent.r#type(name)
+    }
+
+    fn value(&self, name: Name) -> RuntimeResult<Value> {
+        self.parent.value(name)
+    }
+}
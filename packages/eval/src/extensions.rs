@@ -1,12 +1,40 @@
-use crate::LanguageFeature::ExecutingFunctions;
-use crate::{try_static_eval, EvalResult, ExecutionContext, RuntimeError, Value};
+use crate::array::Array;
+use crate::builtins;
+use crate::channel::{self, Case, Channel, Selected};
+use crate::constant;
+use crate::goroutine;
+use crate::map::Map;
+use crate::numeric;
+use crate::place::{Place, Pointer};
+use crate::range::Range;
+use crate::slice::{check_bounds, check_index, Slice};
+use crate::structs::Struct;
+use crate::types::InterfaceType;
+use crate::variable::Variable;
+use crate::Arguments;
+use crate::Function;
+use crate::LanguageFeature::{AnonymousStructs, NestedFunctions};
+use crate::{
+    Constant, EvalResult, ExecutionContext, Intrinsic, LocalContext, RuntimeError, RuntimeResult,
+    Type, Value, NIL_DEREFERENCE,
+};
 use async_trait::async_trait;
-use futures::future::join_all;
 use gor_ast::binary_op::BinOp;
-use gor_ast::expression::{Expression, InnerExpression};
-use gor_ast::func::SourceFunction;
+use gor_ast::control::{
+    Communication, Else, ForStatement, IfStatement, RangeClause, RangeTargets, SelectStatement,
+    SwitchStatement, TypeSwitchStatement,
+};
+use gor_ast::declaration::VarSpec;
+use gor_ast::expression::{Element, Expression, InnerExpression};
+use gor_ast::func::Body;
+use gor_ast::name::Name;
+use gor_ast::statement::{InnerStatement, Statement};
+use gor_ast::types::{InnerType, InterfaceElement, Type as TypeExpression};
 use gor_ast::unitary_op::UniOp;
-use tokio::join;
+use gor_ast::Located;
+use std::future::Future;
+use std::pin::Pin;
+use tokio::task::yield_now;
 
 pub(crate) trait BinOpExt {
     fn static_apply(&self, l: Value, r: Value) -> EvalResult;
@@ -19,19 +47,41 @@ impl BinOpExt for BinOp {
     }
 
     fn evaluate(&self, left: Value, right: Value) -> EvalResult {
-        self.static_apply(left, right)
+        BinOpExt::static_apply(self, left, right)
     }
 }
 
 #[async_trait]
 pub(crate) trait ShortCircuitOpExt {
-    async fn evaluate<'i>(
+    async fn evaluate(
         &self,
-        left: &Expression<'i>,
-        right: &Expression<'i>,
+        left: &Expression<'_>,
+        right: &Expression<'_>,
         context: &dyn ExecutionContext,
     ) -> EvalResult;
-    fn static_apply(&self, left: Value, right: impl FnOnce() -> EvalResult) -> EvalResult;
+}
+
+#[async_trait]
+impl ShortCircuitOpExt for BinOp {
+    async fn evaluate(
+        &self,
+        left: &Expression<'_>,
+        right: &Expression<'_>,
+        context: &dyn ExecutionContext,
+    ) -> EvalResult {
+        let left = left.evaluate(context).await?;
+        match self {
+            BinOp::LogicalAnd | BinOp::LogicalOr => {
+                let left = left.as_bool()?;
+                if left == (*self == BinOp::LogicalOr) {
+                    Ok(Value::Boolean(left))
+                } else {
+                    Ok(Value::Boolean(right.evaluate(context).await?.as_bool()?))
+                }
+            }
+            op => left.bin_op(*op, right.evaluate(context).await?),
+        }
+    }
 }
 
 #[async_trait]
@@ -39,44 +89,827 @@ pub(crate) trait Evaluable {
     async fn evaluate(&self, context: &dyn ExecutionContext) -> EvalResult;
 }
 
+/// Where control goes once a statement has finished executing
+#[derive(Debug)]
+pub(crate) enum Flow {
+    /// On to the next statement
+    Next,
+    /// Out of the current function
+    Return(Value),
+    /// Out of the innermost loop, or the loop with the given label
+    Break(Option<Name>),
+    /// On to the next iteration of the innermost loop, or the loop with the given label
+    Continue(Option<Name>),
+    /// On to the body of the next clause of the enclosing `switch`
+    Fallthrough,
+}
+
+impl Flow {
+    /// Whether a `break` or `continue` refers to the loop with this label
+    fn targets(label: &Option<Name>, loop_label: Option<Name>) -> bool {
+        label.is_none() || *label == loop_label
+    }
+}
+
 #[async_trait]
-impl<'i> Evaluable for Expression<'i> {
+pub(crate) trait Executable {
+    async fn execute(&self, context: &dyn ExecutionContext) -> RuntimeResult<Flow>;
+}
+
+#[async_trait]
+impl Evaluable for Expression<'_> {
     async fn evaluate(&self, context: &dyn ExecutionContext) -> EvalResult {
-        if let Ok(r) = try_static_eval(self) {
-            return Ok(r);
+        if let Some(constant) = constant::of(self, context)? {
+            return constant.value();
         }
 
         Ok(match &self.inner {
+            InnerExpression::BinOp {
+                left,
+                op: op @ (BinOp::LogicalAnd | BinOp::LogicalOr),
+                right,
+            } => ShortCircuitOpExt::evaluate(op, left, right, context).await?,
+            InnerExpression::BinOp {
+                left,
+                op: BinOp::Dot,
+                right,
+            } => match &right.inner {
+                InnerExpression::Name(name) => select(left, *name, context).await?,
+                InnerExpression::Call { .. } => {
+                    let (method, arguments) = callee(self, context).await?;
+                    method.call(arguments).await?
+                }
+                _ => {
+                    return Err(RuntimeError::InvalidSelector(
+                        self.span.as_str().to_string(),
+                    ))
+                }
+            },
             InnerExpression::BinOp { left, op, right } => {
-                let left = left.evaluate(context);
-                let right = right.evaluate(context);
-                let (left, right) = join!(left, right);
-                op.evaluate(left?, right?)?
+                Box::pin(binary(left, *op, right, context)).await?
             }
             InnerExpression::String(s) => Value::String(s.to_owned()),
-            InnerExpression::Number(n) => Value::Int(*n),
+            InnerExpression::Number(_)
+            | InnerExpression::Float(_)
+            | InnerExpression::Imaginary(_) => {
+                unreachable!("literals are constants")
+            }
             InnerExpression::Name(n) => context.value(*n)?.clone(),
+            InnerExpression::UniOp {
+                op: UniOp::Address,
+                exp,
+            } => Value::Pointer(address(exp, context).await?),
+            InnerExpression::UniOp {
+                op: UniOp::Receive,
+                exp,
+            } => receive(exp, context).await?.0,
             InnerExpression::UniOp { op, exp } => op.evaluate(exp.evaluate(context).await?)?,
-            InnerExpression::Call { name, parameters } => {
-                let parameter_futures: Vec<_> = parameters
-                    .iter()
-                    .map(|expr| expr.evaluate(context))
-                    .collect();
-                let mut vector = vec![];
-                vector.reserve_exact(parameters.len());
-                let parameters = join_all(parameter_futures).await.into_iter().try_fold(
-                    vector,
-                    |mut r, p| {
-                        r.push(p?);
-                        Ok(r) as Result<Vec<Value>, RuntimeError>
-                    },
-                )?;
-                context.value(*name)?.call(&parameters)?
+            InnerExpression::Call {
+                name,
+                parameters,
+                spread,
+            } => {
+                let function = match context.value(*name) {
+                    Ok(function) => function,
+                    Err(error) => {
+                        return Box::pin(conversion(*name, error, parameters, context)).await
+                    }
+                };
+                if function == Value::Intrinsic(Intrinsic::Make) {
+                    let (r#type, sizes) =
+                        parameters
+                            .split_first()
+                            .ok_or(RuntimeError::ArgumentCount {
+                                expected: 1,
+                                found: 0,
+                            })?;
+                    builtins::make(
+                        type_argument(r#type, context)?,
+                        arguments(sizes, *spread, context).await?.values()?,
+                    )?
+                } else if function == Value::Intrinsic(Intrinsic::New) {
+                    let [r#type] = parameters.as_slice() else {
+                        return Err(RuntimeError::ArgumentCount {
+                            expected: 1,
+                            found: parameters.len(),
+                        });
+                    };
+                    builtins::new(type_argument(r#type, context)?)?
+                } else if function == Value::Intrinsic(Intrinsic::Recover) {
+                    if !parameters.is_empty() {
+                        return Err(RuntimeError::ArgumentCount {
+                            expected: 0,
+                            found: parameters.len(),
+                        });
+                    }
+                    context.recover()
+                } else {
+                    function
+                        .call(arguments(parameters, *spread, context).await?)
+                        .await?
+                }
+            }
+            InnerExpression::Apply { .. } => {
+                let (function, arguments) = callee(self, context).await?;
+                function.call(arguments).await?
+            }
+            InnerExpression::Func(literal) => Value::Function(Function::closure(literal, context)?),
+            InnerExpression::Composite { r#type, elements } => match &r#type.inner {
+                // `[...]T{}` is as long as it needs to be to hold its elements
+                InnerType::Array { len: None, element } => {
+                    let element = element.resolve(context)?;
+                    let values = indexed_elements(&element, None, elements, context).await?;
+                    Value::Array(Array::new(element, values))
+                }
+                _ => composite(r#type.resolve(context)?, elements, context).await?,
+            },
+            InnerExpression::Elided(_) => {
+                return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                    "Missing type for {}",
+                    self.span.as_str()
+                )))
+            }
+            InnerExpression::TypeAssertion { exp, r#type } => {
+                let value = exp.evaluate(context).await?;
+                let r#type = r#type.resolve(context)?;
+                if r#type.matches(&value) {
+                    r#type.implicit(value)?
+                } else {
+                    return Err(RuntimeError::panic(assertion_failure(&value, &r#type)));
+                }
+            }
+            InnerExpression::Index { exp, index } => {
+                let value = exp.evaluate(context).await?;
+                let index = index.evaluate(context).await?;
+                match value {
+                    Value::Slice(slice) => slice.get(index.as_int()?)?,
+                    Value::Array(array) => array.get(index.as_int()?)?,
+                    Value::Map(map) => map.index(&checked(map.key().clone(), index)?)?,
+                    Value::Pointer(pointer) => element(pointer.place()?, index)?.get()?,
+                    // Indexing a string gives a byte
+                    Value::String(s) => {
+                        Value::Int(s.as_bytes()[check_index(index.as_int()?, s.len())?] as i64)
+                    }
+                    value => return Err(RuntimeError::NotIndexable(value.as_type())),
+                }
+            }
+            InnerExpression::Slice {
+                exp,
+                low,
+                high,
+                max,
+            } => {
+                let value = exp.evaluate(context).await?;
+                let low = bound(low, context).await?;
+                let high = bound(high, context).await?;
+                let max = bound(max, context).await?;
+                match value {
+                    Value::Slice(slice) => Value::Slice(slice.slice(low, high, max)?),
+                    // Slicing an array shares its elements, so we need to find where it's stored
+                    // rather than slice the copy we evaluated.
+                    Value::Array(_) => {
+                        let place = exp.place(context).await.map_err(|_| {
+                            RuntimeError::NotAddressable(exp.span.as_str().to_string())
+                        })?;
+                        Value::Slice(slice_array(&place, low, high, max)?)
+                    }
+                    Value::Pointer(pointer) => {
+                        Value::Slice(slice_array(pointer.place()?, low, high, max)?)
+                    }
+                    Value::String(s) => {
+                        let (low, high, _) =
+                            check_bounds(low, high, max, s.len(), s.len(), "length")?;
+                        Value::String(
+                            String::from_utf8_lossy(&s.as_bytes()[low..high]).into_owned(),
+                        )
+                    }
+                    value => return Err(RuntimeError::NotIndexable(value.as_type())),
+                }
+            }
+            InnerExpression::Type(_) => {
+                return Err(RuntimeError::NotAValue(self.span.as_str().to_string()))
             }
         })
     }
 }
 
+/// Apply a binary operator other than `&&`, `||` or `.`, where an untyped constant operand takes
+/// the type of the other operand, as `1` does in `x + 1` for a `uint8` `x`
+async fn binary(
+    left: &Expression<'_>,
+    op: BinOp,
+    right: &Expression<'_>,
+    context: &dyn ExecutionContext,
+) -> EvalResult {
+    let (left_expression, right_expression) = (left, right);
+    if !matches!(op, BinOp::Shl | BinOp::Shr) {
+        if let Some(constant) = constant::untyped(left, context)? {
+            let right = right.evaluate(context).await?;
+            let differ = || constant.r#type() != right.as_type();
+            if let Some(unequal) = unequal(op, differ, || is_interface(right_expression, context)) {
+                return Ok(unequal);
+            }
+            return BinOpExt::evaluate(&op, constant.value_as(&right.as_type())?, right);
+        }
+        if let Some(constant) = constant::untyped(right, context)? {
+            let left = left.evaluate(context).await?;
+            let differ = || constant.r#type() != left.as_type();
+            if let Some(unequal) = unequal(op, differ, || is_interface(left_expression, context)) {
+                return Ok(unequal);
+            }
+            let right = constant.value_as(&left.as_type())?;
+            return BinOpExt::evaluate(&op, left, right);
+        }
+    }
+    // Go evaluates operands left to right, which matters when they call functions or receive
+    let left = left.evaluate(context).await?;
+    let right = right.evaluate(context).await?;
+    let differ = || !left.comparable(&right);
+    if let Some(unequal) = unequal(op, differ, || {
+        is_interface(left_expression, context) || is_interface(right_expression, context)
+    }) {
+        return Ok(unequal);
+    }
+    BinOpExt::evaluate(&op, left, right)
+}
+
+/// The result of `==` or `!=` between values whose types `differ`, which is only allowed if either
+/// operand is an `interface`, since then they're never equal.
+///
+/// An untyped constant compared with an interface has its default type.
+fn unequal(
+    op: BinOp,
+    differ: impl FnOnce() -> bool,
+    interface: impl FnOnce() -> bool,
+) -> Option<Value> {
+    (matches!(op, BinOp::Eq | BinOp::Neq) && differ() && interface())
+        .then(|| Value::Boolean(op == BinOp::Neq))
+}
+
+/// Whether each of `count` variables declared with `values` has an interface type, where a single
+/// value may be a call giving each of them, or give a value along with whether it succeeded
+fn declared_interfaces(
+    count: usize,
+    values: &[Expression<'_>],
+    context: &dyn ExecutionContext,
+) -> Vec<bool> {
+    let mut interfaces = match values {
+        [value] => interfaces(value, context).unwrap_or_default(),
+        values => values
+            .iter()
+            .map(|value| is_interface(value, context))
+            .collect(),
+    };
+    interfaces.resize(count, false);
+    interfaces
+}
+
+/// Whether an expression's static type is an interface, as far as can be told without evaluating
+/// anything that might have side effects
+fn is_interface(expression: &Expression<'_>, context: &dyn ExecutionContext) -> bool {
+    matches!(interfaces(expression, context).as_deref(), Some([true]))
+}
+
+/// Whether each value an expression gives has an interface type, where that's known: each result
+/// of a call, or the single value of anything else
+fn interfaces(expression: &Expression<'_>, context: &dyn ExecutionContext) -> Option<Vec<bool>> {
+    let function = |value| match value {
+        Value::Function(function) => Some(
+            function
+                .results()
+                .ok()?
+                .iter()
+                .map(is_interface_type)
+                .collect(),
+        ),
+        _ => None,
+    };
+    match &expression.inner {
+        InnerExpression::Name(name) => Some(vec![context.variable(*name).ok()?.is_interface()]),
+        InnerExpression::TypeAssertion { r#type, .. } => {
+            Some(vec![is_interface_type(&r#type.resolve(context).ok()?)])
+        }
+        InnerExpression::Call { name, .. } => match context.value(*name) {
+            Ok(value) => function(value),
+            // A conversion
+            Err(_) => Some(vec![is_interface_type(&context.r#type(*name).ok()?)]),
+        },
+        InnerExpression::Apply {
+            function: callee, ..
+        } => function(peek(callee, context)?),
+        InnerExpression::BinOp {
+            left,
+            op: BinOp::Dot,
+            right,
+        } => match &right.inner {
+            InnerExpression::Name(field) => match peek(left, context)? {
+                Value::Struct(s) => {
+                    let fields = s.r#type().fields().ok()?;
+                    let (_, r#type) = fields.iter().find(|(name, _)| name == field)?;
+                    Some(vec![is_interface_type(r#type)])
+                }
+                _ => None,
+            },
+            InnerExpression::Call { name, .. } => function(method(left, *name, context)?),
+            _ => None,
+        },
+        InnerExpression::Index { exp, .. } => Some(vec![is_interface_type(&element_type(&peek(
+            exp, context,
+        )?)?)]),
+        InnerExpression::UniOp {
+            op: UniOp::Receive,
+            exp,
+        } => Some(vec![is_interface_type(&element_type(&peek(
+            exp, context,
+        )?)?)]),
+        _ => None,
+    }
+}
+
+fn is_interface_type(r#type: &Type) -> bool {
+    matches!(r#type, Type::Interface(_))
+}
+
+/// The type of the values held by a slice, array, map or channel, or the array a pointer points to
+fn element_type(container: &Value) -> Option<Type> {
+    match container {
+        Value::Slice(slice) => Some(slice.element().clone()),
+        Value::Array(array) => Some(array.element().clone()),
+        Value::Map(map) => Some(map.value().clone()),
+        Value::Channel(channel) => Some(channel.element().clone()),
+        Value::Pointer(pointer) => element_type(&pointer.place().ok()?.get().ok()?),
+        _ => None,
+    }
+}
+
+/// The value of an expression that's only made up of names and the fields they hold, which can be
+/// evaluated without side effects
+fn peek(expression: &Expression<'_>, context: &dyn ExecutionContext) -> Option<Value> {
+    match &expression.inner {
+        InnerExpression::Name(name) => context.value(*name).ok(),
+        InnerExpression::BinOp {
+            left,
+            op: BinOp::Dot,
+            right,
+        } => match &right.inner {
+            InnerExpression::Name(field) => match peek(left, context) {
+                Some(value) => dereference(value)?.field(*field).ok(),
+                None => exported(left, *field, context),
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The method `name` of the value of an expression, for which [peek] gives the value
+fn method(
+    expression: &Expression<'_>,
+    name: Name,
+    context: &dyn ExecutionContext,
+) -> Option<Value> {
+    let receiver = match peek(expression, context) {
+        Some(receiver) => receiver,
+        None => return exported(expression, name, context),
+    };
+    let named = match dereference(receiver.clone())? {
+        Value::Struct(s) => s.r#type(),
+        Value::Number(number) => number.named()?,
+        _ => return None,
+    };
+    named.method(name)?;
+    Some(Value::Function(Function::method(
+        named.package(),
+        named.name(),
+        name,
+        receiver,
+    )))
+}
+
+/// `pkg.Name`, where `expression` is the name of an imported package
+fn exported(
+    expression: &Expression<'_>,
+    name: Name,
+    context: &dyn ExecutionContext,
+) -> Option<Value> {
+    match &expression.inner {
+        InnerExpression::Name(package) => context.package()?.import(*package)?.exported(name).ok(),
+        _ => None,
+    }
+}
+
+/// The value a pointer points to, or any other value as it is
+fn dereference(value: Value) -> Option<Value> {
+    match value {
+        Value::Pointer(pointer) => pointer.place().ok()?.get().ok(),
+        value => Some(value),
+    }
+}
+
+/// Evaluate an expression whose value is about to be stored as `r#type`, where an untyped constant
+/// takes that type exactly, as `1 << 63` does for a `uint64`
+async fn evaluate_as(
+    exp: &Expression<'_>,
+    r#type: &Type,
+    context: &dyn ExecutionContext,
+) -> EvalResult {
+    match constant::untyped(exp, context)? {
+        Some(constant) => constant.value_as(r#type),
+        None => exp.evaluate(context).await,
+    }
+}
+
+/// `T(x)` for an `x` that isn't constant: a number converts to any numeric type, wrapping around
+/// or truncating to fit, while any other value has to have the type already.
+///
+/// Fails with `error` if `name` isn't a type either.
+async fn conversion(
+    name: Name,
+    error: RuntimeError,
+    parameters: &[Expression<'_>],
+    context: &dyn ExecutionContext,
+) -> EvalResult {
+    let r#type = context.r#type(name).map_err(|_| error)?;
+    let [parameter] = parameters else {
+        return Err(RuntimeError::ArgumentCount {
+            expected: 1,
+            found: parameters.len(),
+        });
+    };
+    let value = parameter.evaluate(context).await?;
+    match value.as_number() {
+        Some(number) if r#type.numeric().is_some() => Ok(number.convert(&r#type)?.value()),
+        _ => checked(r#type, value),
+    }
+}
+
+/// The value of `x op= value`, where a number takes the type of `x` as it would in `x = value`
+fn operate(place: &Place, op: BinOp, value: Value) -> EvalResult {
+    let current = place.get()?;
+    let value = numeric::assigned(&current, value);
+    BinOpExt::evaluate(&op, current, value)
+}
+
+/// The place of element `index` of the array, slice or map stored at `place`, which may also be a
+/// pointer to an array
+fn element(place: &Place, index: Value) -> RuntimeResult<Place> {
+    let pointee = place.with(|value| match value {
+        Value::Pointer(pointer) => Ok(Some(pointer.place()?.clone())),
+        _ => Ok(None),
+    })?;
+    if let Some(pointee) = pointee {
+        return element(&pointee, index);
+    }
+    place.with(|value| match value {
+        Value::Array(array) => array.place(index.as_int()?),
+        Value::Slice(slice) => slice.place(index.as_int()?),
+        Value::Map(map) => Ok(Place::entry(
+            map.clone(),
+            checked(map.key().clone(), index)?,
+        )),
+        value => Err(RuntimeError::NotIndexable(value.as_type())),
+    })
+}
+
+/// Slice the array stored at `place`, sharing its elements
+fn slice_array(
+    place: &Place,
+    low: Option<i64>,
+    high: Option<i64>,
+    max: Option<i64>,
+) -> RuntimeResult<Slice> {
+    place.with(|value| match value {
+        Value::Array(array) => array.slice(low, high, max),
+        value => Err(RuntimeError::NotIndexable(value.as_type())),
+    })
+}
+
+/// Evaluate one of the optional bounds of a slice expression
+async fn bound(
+    expression: &Option<Box<Expression<'_>>>,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Option<i64>> {
+    match expression {
+        Some(expression) => Ok(Some(expression.evaluate(context).await?.as_int()?)),
+        None => Ok(None),
+    }
+}
+
+/// `&x`, which is either the address of somewhere a value is stored, or of a new variable holding
+/// a composite literal
+async fn address(exp: &Expression<'_>, context: &dyn ExecutionContext) -> RuntimeResult<Pointer> {
+    let place = match &exp.inner {
+        InnerExpression::Composite { .. } => Variable::new(exp.evaluate(context).await?).into(),
+        _ => exp
+            .place(context)
+            .await
+            .ok()
+            .filter(Place::is_addressable)
+            .ok_or_else(|| RuntimeError::NotAddressable(exp.span.as_str().to_string()))?,
+    };
+    let pointee = place.get()?.as_type();
+    Ok(Pointer::new(place, pointee))
+}
+
+/// A type given as the argument to a builtin, which parses as an expression if it's only a name,
+/// or a pointer to one
+fn type_argument(
+    expression: &Expression<'_>,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Type> {
+    match &expression.inner {
+        InnerExpression::Type(r#type) => r#type.resolve(context),
+        InnerExpression::Name(name) => context.r#type(*name),
+        InnerExpression::UniOp {
+            op: UniOp::Deref,
+            exp,
+        } => Ok(Type::Pointer(Box::new(type_argument(exp, context)?))),
+        _ => Err(RuntimeError::UnknownType(
+            expression.span.as_str().to_string(),
+        )),
+    }
+}
+
+/// The message Go panics with when a type assertion fails
+fn assertion_failure(value: &Value, r#type: &Type) -> String {
+    match (value, r#type) {
+        (Value::Nil, _) => format!("interface conversion: interface is nil, not {}", r#type),
+        (value, Type::Interface(interface)) => format!(
+            "interface conversion: {} is not {}: missing method {}",
+            value.as_type(),
+            r#type,
+            interface
+                .missing_method(value)
+                .map(|m| m.to_string())
+                .unwrap_or_default()
+        ),
+        (value, r#type) => format!(
+            "interface conversion: interface is {}, not {}",
+            value.as_type(),
+            r#type
+        ),
+    }
+}
+
+/// Evaluate an expression that may report whether it succeeded, as in `v, ok := x.(T)` or
+/// `v, ok := m[k]`.
+///
+/// A failed type assertion gives the zero value of the asserted type rather than panicking, and a
+/// missing map entry gives the zero value of the map's values.
+async fn comma_ok(
+    expression: &Expression<'_>,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<(Value, bool)> {
+    match &expression.inner {
+        InnerExpression::TypeAssertion { exp, r#type } => {
+            let value = exp.evaluate(context).await?;
+            let r#type = r#type.resolve(context)?;
+            if r#type.matches(&value) {
+                Ok((r#type.implicit(value)?, true))
+            } else {
+                Ok((r#type.zero_value()?, false))
+            }
+        }
+        InnerExpression::Index { exp, index } => match exp.evaluate(context).await? {
+            Value::Map(map) => {
+                let key = checked(map.key().clone(), index.evaluate(context).await?)?;
+                match map.get(&key)? {
+                    Some(value) => Ok((value, true)),
+                    None => Ok((map.value().zero_value()?, false)),
+                }
+            }
+            // Only a map lookup gives a second value
+            _ => Err(RuntimeError::AssignmentMismatch {
+                expected: 2,
+                found: 1,
+            }),
+        },
+        InnerExpression::UniOp {
+            op: UniOp::Receive,
+            exp,
+        } => receive(exp, context).await,
+        _ => Ok((expression.evaluate(context).await?, true)),
+    }
+}
+
+/// `<-ch`, along with whether the value was sent rather than coming from a closed channel
+async fn receive(
+    channel: &Expression<'_>,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<(Value, bool)> {
+    channel_operand(channel, context).await?.receive().await
+}
+
+/// The channel that's sent to or received from with `<-`
+async fn channel_operand(
+    channel: &Expression<'_>,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Channel> {
+    match channel.evaluate(context).await? {
+        Value::Channel(channel) => Ok(channel),
+        value => Err(RuntimeError::InvalidArgument {
+            function: "<-",
+            r#type: value.as_type(),
+        }),
+    }
+}
+
+/// Evaluate the right hand side of an assignment to `count` targets.
+///
+/// Several targets with a single call unpack its results, while two targets with any other single
+/// value use the comma-ok form.
+async fn evaluate_values(
+    count: usize,
+    expressions: &[Expression<'_>],
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Vec<Value>> {
+    match expressions {
+        [expression] if expression.is_call() => {
+            let values = match expression.evaluate(context).await? {
+                Value::Tuple(values) => values,
+                value => vec![value],
+            };
+            if values.len() != count {
+                return Err(RuntimeError::AssignmentMismatch {
+                    expected: count,
+                    found: values.len(),
+                });
+            }
+            Ok(values)
+        }
+        [expression] if count == 2 => {
+            let (value, ok) = comma_ok(expression, context).await?;
+            Ok(vec![value, Value::Boolean(ok)])
+        }
+        expressions => {
+            let values = evaluate_all(expressions, context).await?;
+            check_single(&values, expressions)?;
+            Ok(values)
+        }
+    }
+}
+
+/// Evaluate the function and the arguments of a call without calling it yet, as `go` needs to
+async fn callee(
+    call: &Expression<'_>,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<(Value, Arguments)> {
+    let (function, parameters, spread) = match &call.inner {
+        InnerExpression::Call {
+            name,
+            parameters,
+            spread,
+        } => (context.value(*name)?, parameters, spread),
+        InnerExpression::Apply {
+            function,
+            parameters,
+            spread,
+        } => (function.evaluate(context).await?, parameters, spread),
+        InnerExpression::BinOp {
+            left,
+            op: BinOp::Dot,
+            right,
+        } => match &right.inner {
+            InnerExpression::Call {
+                name,
+                parameters,
+                spread,
+            } => (select(left, *name, context).await?, parameters, spread),
+            _ => return Err(RuntimeError::NotACall(call.span.as_str().to_string())),
+        },
+        _ => return Err(RuntimeError::NotACall(call.span.as_str().to_string())),
+    };
+    Ok((function, arguments(parameters, *spread, context).await?))
+}
+
+/// Evaluate the arguments to a call, where the last may be `spread` over a variadic parameter.
+///
+/// A single call as the only argument passes on all of its results, as in `g(f())`.
+async fn arguments(
+    parameters: &[Expression<'_>],
+    spread: bool,
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Arguments> {
+    let values = match parameters {
+        [parameter] if !spread => match parameter.evaluate(context).await? {
+            Value::Tuple(values) => values,
+            value => vec![value],
+        },
+        parameters => single_values(parameters, context).await?,
+    };
+    Ok(if spread {
+        Arguments::spread(values)
+    } else {
+        Arguments::new(values)
+    })
+}
+
+/// Evaluate expressions that each have to give exactly one value
+async fn single_values(
+    expressions: &[Expression<'_>],
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Vec<Value>> {
+    let values = evaluate_all(expressions, context).await?;
+    check_single(&values, expressions)?;
+    Ok(values)
+}
+
+/// Check that none of the values, which each came from the corresponding expression, are the
+/// results of a call that returned several
+fn check_single(values: &[Value], expressions: &[Expression<'_>]) -> RuntimeResult<()> {
+    match values
+        .iter()
+        .zip(expressions)
+        .find(|(value, _)| matches!(value, Value::Tuple(_)))
+    {
+        Some((_, expression)) => Err(RuntimeError::MultipleValues(
+            expression.span.as_str().to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Select a field or a method from the value of `left`.
+///
+/// Pointers to structs are dereferenced automatically, and a method with a pointer receiver takes
+/// the address of `left` if it's addressable.
+async fn select(left: &Expression<'_>, name: Name, context: &dyn ExecutionContext) -> EvalResult {
+    // `pkg.Name`, unless the package's name is shadowed
+    if let InnerExpression::Name(package) = &left.inner {
+        if context.value(*package).is_err() {
+            if let Some(imported) = context.package().and_then(|p| p.import(*package)) {
+                return imported.exported(name);
+            }
+        }
+    }
+    let (value, pointer) = match left.evaluate(context).await? {
+        Value::Pointer(pointer) if pointer.is_nil() => return nil_method(pointer, name),
+        Value::Pointer(pointer) => (pointer.place()?.get()?, Some(pointer.boxed(false))),
+        Value::Nil => return Err(RuntimeError::panic(NIL_DEREFERENCE)),
+        value => (value, None),
+    };
+    let named = match &value {
+        Value::Struct(s) => match s.field(name) {
+            Ok(field) => return Ok(field.clone()),
+            Err(_) => s.r#type(),
+        },
+        Value::Number(number) => match number.named() {
+            Some(named) => named,
+            None => return value.field(name),
+        },
+        Value::Error(message) if name == "Error".into() => {
+            return Ok(Value::Intrinsic(Intrinsic::Error(message.clone())))
+        }
+        _ => return value.field(name),
+    };
+    let receiver_base = named
+        .method(name)
+        .ok_or_else(|| RuntimeError::UnknownField {
+            r#type: Type::Named(named.clone()),
+            field: name,
+        })?
+        .receiver_base();
+    let receiver = match (receiver_base, pointer) {
+        (Some((_, true)), Some(pointer)) => Value::Pointer(pointer),
+        (Some((_, true)), None) => Value::Pointer(Pointer::new(
+            left.place(context)
+                .await
+                .ok()
+                .filter(Place::is_addressable)
+                .ok_or_else(|| RuntimeError::NotAddressable(left.span.as_str().to_string()))?,
+            value.as_type(),
+        )),
+        _ => value,
+    };
+    Ok(Value::Function(Function::method(
+        named.package(),
+        named.name(),
+        name,
+        receiver,
+    )))
+}
+
+/// Select a method from a nil pointer, which only has a receiver if the method takes a pointer,
+/// while selecting anything else dereferences it
+fn nil_method(pointer: Pointer, name: Name) -> EvalResult {
+    match pointer.pointee() {
+        Type::Named(named)
+            if matches!(
+                named.method(name).and_then(|method| method.receiver_base()),
+                Some((_, true))
+            ) =>
+        {
+            Ok(Value::Function(Function::method(
+                named.package(),
+                named.name(),
+                name,
+                Value::Pointer(pointer.boxed(false)),
+            )))
+        }
+        _ => Err(RuntimeError::panic(NIL_DEREFERENCE)),
+    }
+}
+
 pub(crate) trait UniOpExt {
     fn static_apply(&self, v: Value) -> EvalResult;
     fn evaluate(&self, value: Value) -> EvalResult;
@@ -84,21 +917,899 @@ pub(crate) trait UniOpExt {
 
 impl UniOpExt for UniOp {
     fn static_apply(&self, v: Value) -> EvalResult {
-        let v = v.as_int()?;
-        let v = match self {
-            UniOp::Negate => -v,
-        };
-        Ok(Value::Int(v))
+        Ok(match self {
+            UniOp::Negate => match v.as_number() {
+                Some(number) => number.negate().value(),
+                None => {
+                    return Err(RuntimeError::TypeError {
+                        expected: Type::Int,
+                        found: v.as_type(),
+                    })
+                }
+            },
+            UniOp::Not => Value::Boolean(!v.as_condition()?),
+            UniOp::Address | UniOp::Deref | UniOp::Receive => {
+                return Err(RuntimeError::StaticEvaluationFailure(format!(
+                    "{:?} {}",
+                    self, v
+                )))
+            }
+        })
     }
 
     fn evaluate(&self, value: Value) -> EvalResult {
-        self.static_apply(value).map_err(Into::into)
+        match self {
+            UniOp::Deref => match value {
+                Value::Pointer(pointer) => pointer.place()?.get(),
+                Value::Nil => Err(RuntimeError::panic(NIL_DEREFERENCE)),
+                value => Err(RuntimeError::TypeError {
+                    expected: Type::Pointer(Box::new(Type::Void)),
+                    found: value.as_type(),
+                }),
+            },
+            // Taking an address needs the expression rather than its value
+            UniOp::Address => Err(RuntimeError::NotAddressable(value.to_string())),
+            // Receiving has to wait, so it's evaluated along with the expression
+            UniOp::Receive => Err(RuntimeError::InvalidArgument {
+                function: "<-",
+                r#type: value.as_type(),
+            }),
+            op => op.static_apply(value),
+        }
     }
 }
 
 #[async_trait]
-impl Evaluable for SourceFunction<'_> {
-    async fn evaluate(&self, _: &dyn ExecutionContext) -> EvalResult {
-        Err(RuntimeError::UnsupportedFeature(ExecutingFunctions))
+impl Executable for Body<'_> {
+    async fn execute(&self, context: &dyn ExecutionContext) -> RuntimeResult<Flow> {
+        for statement in &self.statements {
+            match statement.execute(context).await {
+                Ok(Flow::Next) => {}
+                Ok(flow) => return Ok(flow),
+                Err(error) => {
+                    if let RuntimeError::Panic(_) = error {
+                        goroutine::panicked_at(statement.as_span().start_pos().line_col().0);
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        Ok(Flow::Next)
+    }
+}
+
+#[async_trait]
+impl Executable for Statement<'_> {
+    async fn execute(&self, context: &dyn ExecutionContext) -> RuntimeResult<Flow> {
+        match &self.inner {
+            InnerStatement::Expression(expression) => {
+                expression.evaluate(context).await?;
+                Ok(Flow::Next)
+            }
+            InnerStatement::Assignment {
+                targets,
+                op,
+                values,
+            } => {
+                let values = evaluate_values(targets.len(), values, context).await?;
+                let mut places = Vec::with_capacity(targets.len());
+                for target in targets {
+                    places.push(target.place(context).await?);
+                }
+                for (place, value) in places.into_iter().zip(values) {
+                    let value = match op {
+                        Some(op) => operate(&place, *op, value)?,
+                        None => value,
+                    };
+                    place.set(value)?;
+                }
+                Ok(Flow::Next)
+            }
+            InnerStatement::ShortVarDeclaration { names, values } => {
+                let interfaces = declared_interfaces(names.len(), values, context);
+                let values = evaluate_values(names.len(), values, context).await?;
+                if names
+                    .iter()
+                    .all(|name| name.is_blank() || context.local(*name).is_some())
+                {
+                    return Err(RuntimeError::NoNewVariables);
+                }
+                for ((name, value), interface) in names.iter().zip(values).zip(interfaces) {
+                    match context.local(*name) {
+                        Some(variable) => variable.set(value),
+                        None => context
+                            .declare_variable(*name, Variable::new(value).interface(interface))?,
+                    }
+                }
+                Ok(Flow::Next)
+            }
+            InnerStatement::VarDeclaration(specs) => {
+                for spec in specs {
+                    spec.execute(context).await?;
+                }
+                Ok(Flow::Next)
+            }
+            InnerStatement::If(statement) => statement.execute(context).await,
+            InnerStatement::For(statement) => statement.run(context, None).await,
+            InnerStatement::Switch(statement) => statement.run(context, None).await,
+            InnerStatement::TypeSwitch(statement) => statement.run(context, None).await,
+            InnerStatement::Select(statement) => statement.run(context, None).await,
+            InnerStatement::Labeled { label, statement } => match &statement.inner {
+                InnerStatement::For(statement) => statement.run(context, Some(*label)).await,
+                InnerStatement::Switch(statement) => statement.run(context, Some(*label)).await,
+                InnerStatement::TypeSwitch(statement) => statement.run(context, Some(*label)).await,
+                InnerStatement::Select(statement) => statement.run(context, Some(*label)).await,
+                _ => match statement.execute(context).await? {
+                    Flow::Break(Some(target)) if target == *label => Ok(Flow::Next),
+                    flow => Ok(flow),
+                },
+            },
+            InnerStatement::Break(label) => Ok(Flow::Break(*label)),
+            InnerStatement::Continue(label) => Ok(Flow::Continue(*label)),
+            InnerStatement::Fallthrough => Ok(Flow::Fallthrough),
+            InnerStatement::Send { channel, value } => {
+                let channel = channel_operand(channel, context).await?;
+                let value = checked(channel.element().clone(), value.evaluate(context).await?)?;
+                channel.send(value).await?;
+                Ok(Flow::Next)
+            }
+            InnerStatement::Go(call) => {
+                let (function, arguments) = callee(call, context).await?;
+                goroutine::spawn(function, arguments);
+                Ok(Flow::Next)
+            }
+            InnerStatement::Defer(call) => {
+                let (function, arguments) = callee(call, context).await?;
+                context.defer(function, arguments)?;
+                Ok(Flow::Next)
+            }
+            InnerStatement::Func => Err(RuntimeError::UnsupportedFeature(NestedFunctions)),
+            InnerStatement::Return(values) => Ok(Flow::Return(match values.as_slice() {
+                [] => Value::Void,
+                // Passes on every result of a call, as in `return f()`
+                [value] => value.evaluate(context).await?,
+                values => Value::Tuple(single_values(values, context).await?),
+            })),
+        }
+    }
+}
+
+#[async_trait]
+impl Executable for IfStatement<'_> {
+    async fn execute(&self, context: &dyn ExecutionContext) -> RuntimeResult<Flow> {
+        let context = LocalContext::new(context);
+        if let Some(init) = &self.init {
+            init.execute(&context).await?;
+        }
+        if self.condition.evaluate(&context).await?.as_condition()? {
+            self.then.execute(&LocalContext::new(&context)).await
+        } else {
+            match &self.otherwise {
+                Some(Else::If(statement)) => statement.execute(&context).await,
+                Some(Else::Block(body)) => body.execute(&LocalContext::new(&context)).await,
+                None => Ok(Flow::Next),
+            }
+        }
+    }
+}
+
+/// Statements that may be the target of a labeled `break` or `continue`
+#[async_trait]
+pub(crate) trait Breakable {
+    async fn run(&self, context: &dyn ExecutionContext, label: Option<Name>)
+        -> RuntimeResult<Flow>;
+}
+
+#[async_trait]
+impl Breakable for ForStatement<'_> {
+    /// As of Go 1.22, each iteration has its own copy of any variables declared by the init
+    /// statement, so closures that capture them see only that iteration's values.
+    async fn run(
+        &self,
+        context: &dyn ExecutionContext,
+        label: Option<Name>,
+    ) -> RuntimeResult<Flow> {
+        if let Some(range) = &self.range {
+            return range_loop(range, &self.body, context, label).await;
+        }
+        let mut iteration = LocalContext::new(context);
+        if let Some(init) = &self.init {
+            init.execute(&iteration).await?;
+        }
+        loop {
+            // Give other goroutines a chance to run, even if this loop never blocks
+            yield_now().await;
+            if let Some(condition) = &self.condition {
+                if !condition.evaluate(&iteration).await?.as_condition()? {
+                    return Ok(Flow::Next);
+                }
+            }
+            match self.body.execute(&LocalContext::new(&iteration)).await? {
+                Flow::Break(target) if Flow::targets(&target, label) => return Ok(Flow::Next),
+                Flow::Continue(target) if Flow::targets(&target, label) => {}
+                Flow::Next => {}
+                flow => return Ok(flow),
+            }
+            iteration = iteration.next_iteration();
+            if let Some(post) = &self.post {
+                post.execute(&iteration).await?;
+            }
+        }
+    }
+}
+
+/// `for k, v := range x { body }`, where `x` is only evaluated once.
+///
+/// Variables declared by the range clause are new for each iteration, just like those declared by
+/// the init statement of a three-clause loop.
+async fn range_loop(
+    clause: &RangeClause<'_>,
+    body: &Body<'_>,
+    context: &dyn ExecutionContext,
+    label: Option<Name>,
+) -> RuntimeResult<Flow> {
+    let value = clause.exp.evaluate(context).await?;
+    let interfaces = match &value {
+        Value::Map(map) => [is_interface_type(map.key()), is_interface_type(map.value())],
+        Value::Channel(channel) => [is_interface_type(channel.element()), false],
+        value => [
+            false,
+            matches!(element_type(value), Some(Type::Interface(_))),
+        ],
+    };
+    let mut range = Range::new(value)?;
+    let count = match &clause.targets {
+        RangeTargets::Declare(names) => names.len(),
+        RangeTargets::Assign(targets) => targets.len(),
+    };
+    if count == 2 && !range.has_values() {
+        return Err(RuntimeError::AssignmentMismatch {
+            expected: 2,
+            found: 1,
+        });
+    }
+    while let Some((key, value)) = range.next().await? {
+        yield_now().await;
+        let iteration = LocalContext::new(context);
+        store(&clause.targets, [key, value], interfaces, &iteration).await?;
+        match body.execute(&LocalContext::new(&iteration)).await? {
+            Flow::Break(target) if Flow::targets(&target, label) => return Ok(Flow::Next),
+            Flow::Continue(target) if Flow::targets(&target, label) => {}
+            Flow::Next => {}
+            flow => return Ok(flow),
+        }
+    }
+    Ok(Flow::Next)
+}
+
+/// Store a range clause's key and value, or a receive case's value and whether it was sent, in
+/// its targets: either new variables in `scope`, which may have interface types, or existing
+/// places.
+async fn store(
+    targets: &RangeTargets<'_>,
+    values: [Value; 2],
+    interfaces: [bool; 2],
+    scope: &dyn ExecutionContext,
+) -> RuntimeResult<()> {
+    match targets {
+        RangeTargets::Declare(names) => {
+            for ((name, value), interface) in names.iter().zip(values).zip(interfaces) {
+                scope.declare_variable(*name, Variable::new(value).interface(interface))?;
+            }
+        }
+        RangeTargets::Assign(targets) => {
+            for (target, value) in targets.iter().zip(values) {
+                target.place(scope).await?.set(value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Breakable for SelectStatement<'_> {
+    /// Every channel, and every value to be sent, is evaluated once in source order before a case
+    /// is chosen.
+    async fn run(
+        &self,
+        context: &dyn ExecutionContext,
+        label: Option<Name>,
+    ) -> RuntimeResult<Flow> {
+        let mut cases = vec![];
+        let mut clauses = vec![];
+        let mut default = None;
+        for clause in &self.clauses {
+            match &clause.communication {
+                Some(Communication::Send { channel, value }) => {
+                    let channel = channel_operand(channel, context).await?;
+                    let value = checked(channel.element().clone(), value.evaluate(context).await?)?;
+                    cases.push(Case::Send(channel, value));
+                    clauses.push((clause, false));
+                }
+                Some(Communication::Receive { channel, .. }) => {
+                    let channel = channel_operand(channel, context).await?;
+                    // Whether a value received from the channel is stored as an interface
+                    let interface = is_interface_type(channel.element());
+                    cases.push(Case::Receive(channel));
+                    clauses.push((clause, interface));
+                }
+                None => default = Some(clause),
+            }
+        }
+
+        let scope = LocalContext::new(context);
+        let clause = match channel::select(cases, default.is_some()).await? {
+            Selected::Default => match default {
+                Some(clause) => clause,
+                None => unreachable!("only a select with a default clause gives up"),
+            },
+            Selected::Sent(index) => clauses[index].0,
+            Selected::Received(index, value, ok) => {
+                let (clause, interface) = clauses[index];
+                if let Some(Communication::Receive { targets, .. }) = &clause.communication {
+                    store(
+                        targets,
+                        [value, Value::Boolean(ok)],
+                        [interface, false],
+                        &scope,
+                    )
+                    .await?;
+                }
+                clause
+            }
+        };
+        match clause.body.execute(&LocalContext::new(&scope)).await? {
+            Flow::Break(target) if Flow::targets(&target, label) => Ok(Flow::Next),
+            flow => Ok(flow),
+        }
+    }
+}
+
+#[async_trait]
+impl Breakable for SwitchStatement<'_> {
+    /// Cases are compared against the tag in order, stopping at the first match.  The `default`
+    /// clause runs only if no case matches, wherever it appears.
+    async fn run(
+        &self,
+        context: &dyn ExecutionContext,
+        label: Option<Name>,
+    ) -> RuntimeResult<Flow> {
+        let context = LocalContext::new(context);
+        if let Some(init) = &self.init {
+            init.execute(&context).await?;
+        }
+        let tag = match &self.tag {
+            Some(tag) => tag.evaluate(&context).await?,
+            None => Value::Boolean(true),
+        };
+
+        // Values of any type may be compared with an interface, as in `x == v` for each of them
+        let interface = matches!(&self.tag, Some(tag) if is_interface(tag, &context));
+
+        let mut chosen = None;
+        'clauses: for (index, clause) in self.clauses.iter().enumerate() {
+            for value in clause.values.iter().flatten() {
+                let value = match interface {
+                    true => value.evaluate(&context).await?,
+                    false => evaluate_as(value, &tag.as_type(), &context).await?,
+                };
+                if tag.clone().equals(value, interface)? {
+                    chosen = Some(index);
+                    break 'clauses;
+                }
+            }
+        }
+        let mut chosen =
+            match chosen.or_else(|| self.clauses.iter().position(|c| c.values.is_none())) {
+                Some(index) => index,
+                None => return Ok(Flow::Next),
+            };
+
+        loop {
+            let body = &self.clauses[chosen].body;
+            match body.execute(&LocalContext::new(&context)).await? {
+                Flow::Fallthrough => {
+                    let last = body.statements.last().map(|s| &s.inner);
+                    if !matches!(last, Some(InnerStatement::Fallthrough))
+                        || chosen + 1 == self.clauses.len()
+                    {
+                        return Err(RuntimeError::MisplacedFallthrough);
+                    }
+                    chosen += 1;
+                }
+                Flow::Break(target) if Flow::targets(&target, label) => return Ok(Flow::Next),
+                flow => return Ok(flow),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Breakable for TypeSwitchStatement<'_> {
+    /// Cases are checked in order against the dynamic type of the subject, and `case nil` matches
+    /// only a nil interface, not one holding a nil pointer.  The binding holds the subject's value
+    /// in whichever clause runs, as the clause's type if it lists only one.
+    async fn run(
+        &self,
+        context: &dyn ExecutionContext,
+        label: Option<Name>,
+    ) -> RuntimeResult<Flow> {
+        let context = LocalContext::new(context);
+        if let Some(init) = &self.init {
+            init.execute(&context).await?;
+        }
+        let value = self.subject.evaluate(&context).await?;
+
+        let mut chosen = None;
+        let mut as_type = None;
+        'clauses: for clause in &self.clauses {
+            let types = clause.types.as_deref().unwrap_or_default();
+            for r#type in types {
+                let matched = match &r#type.inner {
+                    InnerType::Name(name) if *name == "nil".into() => value == Value::Nil,
+                    _ => {
+                        let r#type = r#type.resolve(&context)?;
+                        let matched = r#type.matches(&value);
+                        if matched && types.len() == 1 {
+                            as_type = Some(r#type);
+                        }
+                        matched
+                    }
+                };
+                if matched {
+                    chosen = Some(clause);
+                    break 'clauses;
+                }
+            }
+        }
+        let Some(clause) = chosen.or_else(|| self.clauses.iter().find(|c| c.types.is_none()))
+        else {
+            return Ok(Flow::Next);
+        };
+
+        let scope = LocalContext::new(&context);
+        if let Some(binding) = self.binding {
+            // Otherwise the variable has the type of the interface being switched on
+            let (value, interface) = match as_type {
+                Some(r#type) => (r#type.implicit(value)?, is_interface_type(&r#type)),
+                None => (value, true),
+            };
+            scope.declare_variable(binding, Variable::new(value).interface(interface))?;
+        }
+        match clause.body.execute(&scope).await? {
+            Flow::Fallthrough => Err(RuntimeError::MisplacedFallthrough),
+            Flow::Break(target) if Flow::targets(&target, label) => Ok(Flow::Next),
+            flow => Ok(flow),
+        }
+    }
+}
+
+#[async_trait]
+impl Executable for VarSpec<'_> {
+    async fn execute(&self, context: &dyn ExecutionContext) -> RuntimeResult<Flow> {
+        let r#type = self
+            .r#type
+            .as_ref()
+            .map(|t| t.resolve(context))
+            .transpose()?;
+        let interfaces = match &r#type {
+            Some(r#type) => vec![is_interface_type(r#type); self.names.len()],
+            None => declared_interfaces(self.names.len(), &self.values, context),
+        };
+        let values = if self.values.is_empty() {
+            let r#type = r#type.as_ref().ok_or_else(|| {
+                RuntimeError::UnknownType(self.names.iter().map(|n| n.to_string()).collect())
+            })?;
+            self.names
+                .iter()
+                .map(|_| r#type.zero_value())
+                .collect::<RuntimeResult<Vec<_>>>()?
+        } else if let (Some(r#type), true) = (&r#type, self.values.len() == self.names.len()) {
+            let mut values = Vec::with_capacity(self.values.len());
+            for value in &self.values {
+                values.push(evaluate_as(value, r#type, context).await?);
+            }
+            values
+        } else {
+            evaluate_values(self.names.len(), &self.values, context).await?
+        };
+        for ((name, value), interface) in self.names.iter().zip(values).zip(interfaces) {
+            let value = match &r#type {
+                Some(expected) => checked(expected.clone(), value)?,
+                None => value,
+            };
+            context.declare_variable(*name, Variable::new(value).interface(interface))?;
+        }
+        Ok(Flow::Next)
+    }
+}
+
+/// Evaluate each expression in turn, left to right
+async fn evaluate_all(
+    expressions: &[Expression<'_>],
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Vec<Value>> {
+    let mut values = Vec::with_capacity(expressions.len());
+    for expression in expressions {
+        values.push(expression.evaluate(context).await?);
+    }
+    Ok(values)
+}
+
+/// Build a struct, a slice, an array or a map from the elements of a composite literal.
+///
+/// Fields or elements without a value are left at their zero value.
+async fn composite(
+    r#type: Type,
+    elements: &[Element<'_>],
+    context: &dyn ExecutionContext,
+) -> EvalResult {
+    match r#type {
+        Type::Slice(element) => {
+            let values = indexed_elements(&element, None, elements, context).await?;
+            return Ok(Value::Slice(Slice::new(*element, values)));
+        }
+        Type::Array(len, element) => {
+            let values = indexed_elements(&element, Some(len), elements, context).await?;
+            return Ok(Value::Array(Array::new(*element, values)));
+        }
+        Type::Map(key, value) => return map_literal(*key, *value, elements, context).await,
+        _ => {}
+    }
+    let Type::Named(named) = r#type.clone() else {
+        return Err(RuntimeError::InvalidCompositeLiteral(format!(
+            "{:?} is not a struct",
+            r#type
+        )));
+    };
+    let types = named.fields()?;
+    let mut values = Vec::with_capacity(types.len());
+    if elements.iter().all(|e| e.key.is_some()) {
+        values = types
+            .iter()
+            .map(|(name, r#type)| Ok((*name, r#type.zero_value()?)))
+            .collect::<RuntimeResult<_>>()?;
+        let mut seen = Vec::with_capacity(elements.len());
+        for element in elements {
+            let Some(InnerExpression::Name(field)) = element.key.as_ref().map(|k| &k.inner) else {
+                return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                    "Expected a field name in {:?}",
+                    r#type
+                )));
+            };
+            if seen.contains(field) {
+                return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                    "Duplicate field {} in {:?}",
+                    field, r#type
+                )));
+            }
+            seen.push(*field);
+            let (_, expected) = types
+                .iter()
+                .find(|(name, _)| name == field)
+                .ok_or_else(|| RuntimeError::UnknownField {
+                    r#type: r#type.clone(),
+                    field: *field,
+                })?;
+            let value = checked(expected.clone(), element.value.evaluate(context).await?)?;
+            if let Some((_, stored)) = values.iter_mut().find(|(name, _)| name == field) {
+                *stored = value;
+            }
+        }
+    } else {
+        // Only a literal with its type elided can get this far with some of its fields named
+        if elements.iter().any(|e| e.key.is_some()) {
+            return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                "Mixture of keyed and positional elements in {:?}",
+                r#type
+            )));
+        }
+        if elements.len() != types.len() {
+            return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                "Expected {} values for {:?}, found {}",
+                types.len(),
+                r#type,
+                elements.len()
+            )));
+        }
+        for ((name, expected), element) in types.into_iter().zip(elements) {
+            values.push((
+                name,
+                checked(expected, element.value.evaluate(context).await?)?,
+            ));
+        }
+    }
+    Ok(Value::Struct(Struct::new(named, values)))
+}
+
+/// The entries of a map literal, every one of which needs a key.
+///
+/// Constant keys have to be distinct, while a repeated key that's only known at run time replaces
+/// the earlier entry.
+async fn map_literal(
+    key: Type,
+    value: Type,
+    elements: &[Element<'_>],
+    context: &dyn ExecutionContext,
+) -> EvalResult {
+    let map = Map::new(key.clone(), value.clone());
+    let mut constants = Vec::with_capacity(elements.len());
+    for element in elements {
+        let Some(key_expression) = &element.key else {
+            return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                "Missing key in map literal: {}",
+                element.value.span.as_str()
+            )));
+        };
+        let k = checked(
+            key.clone(),
+            element_value(key_expression, &key, context).await?,
+        )?;
+        if constant::of(key_expression, context)?.is_some() {
+            if constants.contains(&k) {
+                return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                    "Duplicate key {} in map literal",
+                    key_expression.span.as_str()
+                )));
+            }
+            constants.push(k.clone());
+        }
+        let v = checked(
+            value.clone(),
+            element_value(&element.value, &value, context).await?,
+        )?;
+        map.insert(k, v)?;
+    }
+    Ok(Value::Map(map))
+}
+
+/// The values of a slice or array literal.
+///
+/// An element may give its index, and each element without one follows the element before it.
+/// Any indexes that are skipped hold the zero value, as does the rest of an array with a `len`.
+/// Evaluate an element or key of an array, slice or map literal, which is about to be stored as
+/// `r#type`.
+///
+/// A literal with its type elided has that type, or the type it points to, as `{1, 2}` does in
+/// `[]*Point{{1, 2}}`.
+fn element_value<'a>(
+    expression: &'a Expression<'_>,
+    r#type: &'a Type,
+    context: &'a dyn ExecutionContext,
+) -> Pin<Box<dyn Future<Output = EvalResult> + Send + 'a>> {
+    Box::pin(async move {
+        let InnerExpression::Elided(elements) = &expression.inner else {
+            return expression.evaluate(context).await;
+        };
+        match r#type {
+            Type::Pointer(pointee) => {
+                let value = composite((**pointee).clone(), elements, context).await?;
+                Ok(Value::Pointer(Pointer::new(
+                    Variable::new(value).into(),
+                    (**pointee).clone(),
+                )))
+            }
+            r#type => composite(r#type.clone(), elements, context).await,
+        }
+    })
+}
+
+async fn indexed_elements(
+    element: &Type,
+    len: Option<usize>,
+    elements: &[Element<'_>],
+    context: &dyn ExecutionContext,
+) -> RuntimeResult<Vec<Value>> {
+    let mut values: Vec<Option<Value>> = Vec::with_capacity(elements.len());
+    let mut index = 0;
+    for item in elements {
+        if let Some(key) = &item.key {
+            index = usize::try_from(key.evaluate(context).await?.as_int()?).map_err(|_| {
+                RuntimeError::InvalidCompositeLiteral(format!(
+                    "Index {} must be non-negative",
+                    key.span.as_str()
+                ))
+            })?;
+        }
+        if let Some(len) = len.filter(|len| index >= *len) {
+            return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                "Index {} out of bounds [0:{}]",
+                index, len
+            )));
+        }
+        let value = checked(
+            element.clone(),
+            element_value(&item.value, element, context).await?,
+        )?;
+        if values.len() <= index {
+            values.resize(index + 1, None);
+        }
+        if values[index].replace(value).is_some() {
+            return Err(RuntimeError::InvalidCompositeLiteral(format!(
+                "Duplicate index {} in literal",
+                index
+            )));
+        }
+        index += 1;
+    }
+    if let Some(len) = len {
+        values.resize(len, None);
+    }
+    let zero = element.zero_value()?;
+    Ok(values
+        .into_iter()
+        .map(|value| value.unwrap_or_else(|| zero.clone()))
+        .collect())
+}
+
+/// Check that a value has the type it's about to be stored as
+pub(crate) fn checked(expected: Type, value: Value) -> EvalResult {
+    let value = expected.implicit(value)?;
+    if value == Value::Nil && matches!(expected, Type::Slice(_) | Type::Map(..) | Type::Channel(..))
+    {
+        // `nil` takes the type of the slice, map or channel it's stored as
+        return expected.zero_value();
+    }
+    if expected.accepts(&value) {
+        Ok(match (value, &expected) {
+            (Value::Channel(channel), Type::Channel(direction, _)) => {
+                Value::Channel(channel.with_direction(*direction))
+            }
+            (value, _) => value,
+        })
+    } else {
+        Err(RuntimeError::TypeError {
+            expected,
+            found: value.as_type(),
+        })
+    }
+}
+
+/// Expressions that may appear on the left hand side of an assignment
+#[async_trait]
+pub(crate) trait Assignable {
+    async fn place(&self, context: &dyn ExecutionContext) -> RuntimeResult<Place>;
+}
+
+#[async_trait]
+impl Assignable for Expression<'_> {
+    async fn place(&self, context: &dyn ExecutionContext) -> RuntimeResult<Place> {
+        match &self.inner {
+            // Anything assigned to `_` is thrown away
+            InnerExpression::Name(name) if name.is_blank() => Ok(Variable::new(Value::Void).into()),
+            InnerExpression::Name(name) => Ok(context.variable(*name)?.into()),
+            InnerExpression::UniOp {
+                op: UniOp::Deref,
+                exp,
+            } => match exp.evaluate(context).await? {
+                Value::Pointer(pointer) => Ok(pointer.place()?.clone()),
+                Value::Nil => Err(RuntimeError::panic(NIL_DEREFERENCE)),
+                _ => Err(RuntimeError::NotAssignable(self.span.as_str().to_string())),
+            },
+            InnerExpression::Index { exp, index } => {
+                // Arrays are stored in place, while a slice may be the result of any expression
+                let place = match exp.place(context).await {
+                    Ok(place) => place,
+                    Err(RuntimeError::NotAssignable(_)) => match exp.evaluate(context).await? {
+                        Value::Slice(slice) => {
+                            return slice.place(index.evaluate(context).await?.as_int()?)
+                        }
+                        Value::Map(map) => {
+                            let key = checked(map.key().clone(), index.evaluate(context).await?)?;
+                            return Ok(Place::entry(map, key));
+                        }
+                        Value::Pointer(pointer) => pointer.place()?.clone(),
+                        _ => {
+                            return Err(RuntimeError::NotAssignable(self.span.as_str().to_string()))
+                        }
+                    },
+                    Err(e) => return Err(e),
+                };
+                element(&place, index.evaluate(context).await?)
+            }
+            InnerExpression::BinOp {
+                left,
+                op: BinOp::Dot,
+                right,
+            } => match &right.inner {
+                InnerExpression::Name(field) => {
+                    let place = match left.place(context).await {
+                        // A struct stored in a map may only be replaced as a whole
+                        Ok(place) if !place.is_addressable() => {
+                            return Err(RuntimeError::NotAssignable(self.span.as_str().to_string()))
+                        }
+                        Ok(place) => match place.get()? {
+                            Value::Pointer(pointer) => pointer.place()?.clone(),
+                            Value::Nil => return Err(RuntimeError::panic(NIL_DEREFERENCE)),
+                            _ => place,
+                        },
+                        Err(RuntimeError::NotAssignable(_)) => {
+                            match left.evaluate(context).await? {
+                                Value::Pointer(pointer) => pointer.place()?.clone(),
+                                _ => {
+                                    return Err(RuntimeError::NotAssignable(
+                                        self.span.as_str().to_string(),
+                                    ))
+                                }
+                            }
+                        }
+                        Err(e) => return Err(e),
+                    };
+                    Ok(place.field(*field))
+                }
+                _ => Err(RuntimeError::InvalidSelector(
+                    self.span.as_str().to_string(),
+                )),
+            },
+            _ => Err(RuntimeError::NotAssignable(self.span.as_str().to_string())),
+        }
+    }
+}
+
+pub(crate) trait TypeExt {
+    fn resolve(&self, context: &dyn ExecutionContext) -> RuntimeResult<Type>;
+}
+
+impl TypeExt for TypeExpression<'_> {
+    fn resolve(&self, context: &dyn ExecutionContext) -> RuntimeResult<Type> {
+        match &self.inner {
+            InnerType::Name(name) => context.r#type(*name),
+            InnerType::Qualified { .. } => {
+                Err(RuntimeError::UnknownType(self.span.as_str().to_string()))
+            }
+            InnerType::Struct(_) => Err(RuntimeError::UnsupportedFeature(AnonymousStructs)),
+            InnerType::Pointer(pointee) => Ok(Type::Pointer(Box::new(pointee.resolve(context)?))),
+            InnerType::Slice(element) => Ok(Type::Slice(Box::new(element.resolve(context)?))),
+            InnerType::Array {
+                len: Some(len),
+                element,
+            } => {
+                let invalid = || RuntimeError::InvalidArrayLength(len.span.as_str().to_string());
+                let len = constant::evaluate(len, context)
+                    .and_then(Constant::value)
+                    .map_err(|_| invalid())?
+                    .as_int()?;
+                Ok(Type::Array(
+                    usize::try_from(len).map_err(|_| invalid())?,
+                    Box::new(element.resolve(context)?),
+                ))
+            }
+            InnerType::Map { key, value } => {
+                let key = key.resolve(context)?;
+                if !key.is_comparable() {
+                    return Err(RuntimeError::InvalidMapKey(key));
+                }
+                Ok(Type::Map(Box::new(key), Box::new(value.resolve(context)?)))
+            }
+            // Function types aren't distinguished by their signatures yet
+            InnerType::Func(_) => Ok(Type::Function),
+            InnerType::Channel { direction, element } => Ok(Type::Channel(
+                *direction,
+                Box::new(element.resolve(context)?),
+            )),
+            InnerType::Array { len: None, .. } => Err(RuntimeError::InvalidArrayLength(
+                self.span.as_str().to_string(),
+            )),
+            InnerType::Interface(elements) => {
+                let mut methods = vec![];
+                for element in elements {
+                    match element {
+                        InterfaceElement::Method { name, .. } => methods.push(*name),
+                        InterfaceElement::Embedded(embedded) => match embedded.resolve(context)? {
+                            Type::Interface(interface) => {
+                                methods.extend_from_slice(interface.methods())
+                            }
+                            r#type => {
+                                return Err(RuntimeError::UnknownType(format!(
+                                    "{} is not an interface",
+                                    r#type
+                                )))
+                            }
+                        },
+                    }
+                }
+                Ok(Type::Interface(InterfaceType::new(None, methods)))
+            }
+        }
     }
 }
@@ -0,0 +1,461 @@
+use crate::extensions::{checked, Executable, Flow, TypeExt};
+use crate::goroutine;
+use crate::{
+    Arguments, Constant, EvalResult, ExecutionContext, LocalContext, PackageContext, RuntimeError,
+    RuntimeResult, Type, Value, Variable,
+};
+use gor_ast::func::{Body, FuncLiteral, Parameter, Signature, SourceFunction};
+use gor_ast::name::Name;
+use gor_ast::Located;
+use gor_loader::ModuleDescriptor;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+/// A function declared in Go source, along with the package it was declared in, or a closure
+/// created from a function literal.
+///
+/// Methods are bound to the receiver they were selected from.
+#[derive(Clone)]
+pub struct Function {
+    package: PackageContext,
+    source: Source,
+    receiver: Option<Box<Value>>,
+}
+
+/// Where the function is found in its package's source
+#[derive(Clone)]
+enum Source {
+    Function(Name),
+    /// A method declared on the named type
+    Method(Name, Name),
+    /// An `init` function, by its position among them
+    Init(usize),
+    /// A function literal, by where it starts, along with the variables it refers to from where
+    /// it was evaluated
+    Literal {
+        offset: usize,
+        captured: Arc<HashMap<Name, Variable>>,
+    },
+}
+
+impl Function {
+    /// The function declared in `package` with this name
+    pub(crate) fn new(package: PackageContext, name: Name) -> Self {
+        Function {
+            package,
+            source: Source::Function(name),
+            receiver: None,
+        }
+    }
+
+    /// The method declared on `r#type` in `package`, bound to `receiver`
+    pub(crate) fn method(
+        package: PackageContext,
+        r#type: Name,
+        name: Name,
+        receiver: Value,
+    ) -> Self {
+        Function {
+            package,
+            source: Source::Method(r#type, name),
+            receiver: Some(Box::new(receiver)),
+        }
+    }
+
+    /// The `index`th `init` function declared in `package`
+    pub(crate) fn init(package: PackageContext, index: usize) -> Self {
+        Function {
+            package,
+            source: Source::Init(index),
+            receiver: None,
+        }
+    }
+
+    /// A closure, which shares every variable that's visible from `context` rather than copying
+    /// their values.
+    ///
+    /// Function literals can only be evaluated within a package, which holds their source.
+    pub(crate) fn closure(
+        literal: &FuncLiteral<'_>,
+        context: &dyn ExecutionContext,
+    ) -> RuntimeResult<Self> {
+        let package = context.package().ok_or_else(|| {
+            RuntimeError::StaticEvaluationFailure(literal.as_span().as_str().to_string())
+        })?;
+        let mut captured = HashMap::new();
+        context.capture(&mut captured);
+        Ok(Function {
+            package,
+            source: Source::Literal {
+                offset: literal.as_span().start(),
+                captured: Arc::new(captured),
+            },
+            receiver: None,
+        })
+    }
+
+    /// The type of each of the function's results
+    pub(crate) fn results(&self) -> RuntimeResult<Vec<Type>> {
+        self.signature()
+            .results
+            .parameters
+            .iter()
+            .map(|result| result.r#type.resolve(&self.package))
+            .collect()
+    }
+
+    /// The declaration of the function, unless it's a closure
+    fn declaration(&self) -> Option<&SourceFunction<'_>> {
+        let source = self.package.source();
+        let declaration = match &self.source {
+            Source::Function(name) => source.function(*name),
+            Source::Method(r#type, name) => source.method(*r#type, *name),
+            Source::Init(index) => source.init_functions().nth(*index),
+            Source::Literal { .. } => return None,
+        };
+        Some(declaration.unwrap_or_else(|| unreachable!("functions are made from their source")))
+    }
+
+    fn signature(&self) -> &Signature<'_> {
+        match (self.declaration(), &self.source) {
+            (Some(declaration), _) => &declaration.signature,
+            (None, Source::Literal { offset, .. }) => &self.literal(*offset).signature,
+            (None, _) => unreachable!("only closures are undeclared"),
+        }
+    }
+
+    fn body(&self) -> &Body<'_> {
+        match (self.declaration(), &self.source) {
+            (Some(declaration), _) => &declaration.body,
+            (None, Source::Literal { offset, .. }) => &self.literal(*offset).body,
+            (None, _) => unreachable!("only closures are undeclared"),
+        }
+    }
+
+    fn literal(&self, offset: usize) -> &FuncLiteral<'_> {
+        self.package
+            .source()
+            .literal(offset)
+            .unwrap_or_else(|| unreachable!("closures are made from their source"))
+    }
+
+    /// The name Go gives the function in a stack trace, such as `main.(*T).Close`
+    fn name(&self) -> String {
+        let package = self.package.source().package;
+        match self.declaration() {
+            Some(source) => match source.receiver_base() {
+                Some((r#type, true)) => format!("{}.(*{}).{}", package, r#type, source.name),
+                Some((r#type, false)) => format!("{}.{}.{}", package, r#type, source.name),
+                None => format!("{}.{}", package, source.name),
+            },
+            None => format!("{}.func", package),
+        }
+    }
+
+    /// Bind the arguments to the function's parameters, then run its body.
+    ///
+    /// A variadic parameter gets a slice of the arguments left over after the others, or the
+    /// slice that was spread over it.
+    pub(crate) async fn call(&self, arguments: Arguments) -> EvalResult {
+        self.call_with(arguments, None).await
+    }
+
+    /// Call the function, which may recover from the panic in `recovering` if it's being called
+    /// as a deferred function.
+    ///
+    /// Deferred calls run in the reverse of the order they were deferred, once the body has
+    /// returned or panicked.  They can change the values of named results, and a panic in one
+    /// replaces any panic that was already unwinding.
+    async fn call_with(
+        &self,
+        arguments: Arguments,
+        recovering: Option<&Mutex<Option<Value>>>,
+    ) -> EvalResult {
+        let signature = self.signature();
+        let parameters = &signature.parameters.parameters;
+        let (arguments, variadic) = if signature.is_variadic() {
+            let (fixed, variadic) = arguments.variadic(parameters.len() - 1)?;
+            (fixed, Some(variadic))
+        } else {
+            let arguments = arguments.values()?;
+            if parameters.len() != arguments.len() {
+                return Err(RuntimeError::ArgumentCount {
+                    expected: parameters.len(),
+                    found: arguments.len(),
+                });
+            }
+            (arguments, None)
+        };
+
+        let scope = Scope {
+            package: &self.package,
+            captured: match &self.source {
+                Source::Literal { captured, .. } => Some(captured),
+                _ => None,
+            },
+            deferred: Mutex::new(vec![]),
+            recovering,
+        };
+        let context = LocalContext::new(&scope);
+        if let (Some(receiver), Some(source)) = (&self.receiver, self.declaration()) {
+            if let Some(name) = source.receiver.as_ref().and_then(|r| r.name) {
+                context.declare(name, (**receiver).clone())?;
+            }
+        }
+        for (parameter, argument) in parameters.iter().zip(arguments) {
+            let r#type = parameter.r#type.resolve(&context)?;
+            let interface = matches!(r#type, Type::Interface(_));
+            let argument = checked(r#type, argument)?;
+            if let Some(name) = parameter.name {
+                context.declare_variable(name, Variable::new(argument).interface(interface))?;
+            }
+        }
+        if let (Some(variadic), Some(parameter)) = (variadic, parameters.last()) {
+            let value = variadic.into_slice(parameter.r#type.resolve(&context)?)?;
+            if let Some(name) = parameter.name {
+                context.declare(name, value)?;
+            }
+        }
+        // Named results are variables that start out as zero values
+        for result in &signature.results.parameters {
+            if let Some(name) = result.name {
+                let r#type = result.r#type.resolve(&context)?;
+                let variable = Variable::new(r#type.zero_value()?);
+                context.declare_variable(
+                    name,
+                    variable.interface(matches!(r#type, Type::Interface(_))),
+                )?;
+            }
+        }
+
+        let outcome = self.body().execute(&context).await;
+        Box::pin(self.finish(outcome, &scope, &context)).await
+    }
+
+    /// Run the deferred calls once the body has returned or panicked, then give the results, or
+    /// carry on unwinding if they didn't recover.
+    ///
+    /// Kept apart from [Function::call_with] and boxed, so that it doesn't add to the stack used
+    /// by each level of a recursive call.
+    async fn finish(
+        &self,
+        outcome: RuntimeResult<Flow>,
+        scope: &Scope<'_>,
+        context: &LocalContext<'_>,
+    ) -> EvalResult {
+        let signature = self.signature();
+        let results = &signature.results.parameters;
+        let named = results.iter().any(|r| r.name.is_some());
+
+        let panicking = Mutex::new(None);
+        let returned = match outcome {
+            Ok(Flow::Return(value)) => value,
+            Ok(Flow::Next) => Value::Void,
+            Ok(Flow::Break(label)) => {
+                return Err(RuntimeError::BranchOutsideLoop(branch("break", label)))
+            }
+            Ok(Flow::Continue(label)) => {
+                return Err(RuntimeError::BranchOutsideLoop(branch("continue", label)))
+            }
+            Ok(Flow::Fallthrough) => return Err(RuntimeError::MisplacedFallthrough),
+            Err(RuntimeError::Panic(value)) => {
+                *panicking.lock().unwrap() = Some(value);
+                Value::Void
+            }
+            Err(error) => return Err(error),
+        };
+        // `return x` sets the named results before the deferred calls run, so they can see it
+        if named && returned != Value::Void {
+            let values = match returned.clone() {
+                Value::Tuple(values) => values,
+                value => vec![value],
+            };
+            for (result, value) in results.iter().zip(values) {
+                if let Some(variable) = result.name.and_then(|name| context.local(name)) {
+                    variable.set(implicit(result, value, context)?);
+                }
+            }
+        }
+
+        let trace = goroutine::suspend();
+        loop {
+            let Some((function, arguments)) = scope.deferred.lock().unwrap().pop() else {
+                break;
+            };
+            let result = match function {
+                Value::Function(function) => function.call_with(arguments, Some(&panicking)).await,
+                function => function.call(arguments).await,
+            };
+            match result {
+                Ok(_) => {}
+                Err(RuntimeError::Panic(value)) => *panicking.lock().unwrap() = Some(value),
+                Err(error) => return Err(error),
+            }
+        }
+        if let Some(value) = panicking.into_inner().unwrap() {
+            let arguments = self.receiver.is_some() || !signature.parameters.parameters.is_empty();
+            goroutine::unwind(trace, self.name(), arguments);
+            return Err(RuntimeError::Panic(value));
+        }
+
+        // A bare `return`, or the end of the body, gives the values of any named results, while a
+        // function that recovered without them gives zero values
+        if !named && (returned != Value::Void || results.is_empty()) {
+            return match (returned, results.as_slice()) {
+                (Value::Tuple(values), results) if values.len() == results.len() => {
+                    Ok(Value::Tuple(
+                        results
+                            .iter()
+                            .zip(values)
+                            .map(|(result, value)| implicit(result, value, context))
+                            .collect::<RuntimeResult<_>>()?,
+                    ))
+                }
+                (value, [result]) => implicit(result, value, context),
+                (value, _) => Ok(value),
+            };
+        }
+        let mut values = results
+            .iter()
+            .map(
+                |result| match result.name.and_then(|name| context.local(name)) {
+                    Some(variable) => Ok(variable.get()),
+                    None => result.r#type.resolve(context)?.zero_value(),
+                },
+            )
+            .collect::<RuntimeResult<Vec<_>>>()?;
+        Ok(match values.len() {
+            1 => values.remove(0),
+            _ => Value::Tuple(values),
+        })
+    }
+}
+
+/// Pass a value as a parameter or result, checking it against the declared type just as storing
+/// it in a variable of that type would
+fn implicit(parameter: &Parameter<'_>, value: Value, context: &dyn ExecutionContext) -> EvalResult {
+    checked(parameter.r#type.resolve(context)?, value)
+}
+
+fn branch(keyword: &str, label: Option<Name>) -> String {
+    match label {
+        Some(label) => format!("{} {}", keyword, label),
+        None => keyword.to_string(),
+    }
+}
+
+/// The scope a function body runs in: the variables captured by a closure, then the package it
+/// was declared in.
+#[derive(Debug)]
+struct Scope<'f> {
+    package: &'f PackageContext,
+    captured: Option<&'f HashMap<Name, Variable>>,
+    /// Calls to make when the function returns, in the order they were deferred
+    deferred: Mutex<Vec<(Value, Arguments)>>,
+    /// The panic that the function may recover from, if it's a deferred call
+    recovering: Option<&'f Mutex<Option<Value>>>,
+}
+
+impl Scope<'_> {
+    fn outer(&self) -> &dyn ExecutionContext {
+        self.package
+    }
+
+    fn captured(&self, name: Name) -> Option<&Variable> {
+        self.captured.and_then(|captured| captured.get(&name))
+    }
+}
+
+impl ExecutionContext for Scope<'_> {
+    fn value(&self, name: Name) -> RuntimeResult<Value> {
+        match self.captured(name) {
+            Some(variable) => Ok(variable.get()),
+            None => self.outer().value(name),
+        }
+    }
+
+    fn module(&self, name: Name) -> RuntimeResult<&ModuleDescriptor> {
+        self.outer().module(name)
+    }
+
+    fn constant(&self, name: Name) -> RuntimeResult<Constant> {
+        match self.captured(name) {
+            Some(_) => Err(RuntimeError::StaticEvaluationFailure(name.to_string())),
+            None => self.outer().constant(name),
+        }
+    }
+
+    fn r#type(&self, name: Name) -> RuntimeResult<Type> {
+        self.outer().r#type(name)
+    }
+
+    fn variable(&self, name: Name) -> RuntimeResult<Variable> {
+        match self.captured(name) {
+            Some(variable) => Ok(variable.clone()),
+            None => self.outer().variable(name),
+        }
+    }
+
+    fn package(&self) -> Option<PackageContext> {
+        Some(self.package.clone())
+    }
+
+    fn capture(&self, variables: &mut HashMap<Name, Variable>) {
+        if let Some(captured) = self.captured {
+            for (name, variable) in captured {
+                variables.entry(*name).or_insert_with(|| variable.clone());
+            }
+        }
+    }
+
+    fn defer(&self, function: Value, arguments: Arguments) -> RuntimeResult<()> {
+        self.deferred.lock().unwrap().push((function, arguments));
+        Ok(())
+    }
+
+    /// Functions called by this one don't see its panic, so this never looks any further out
+    fn recover(&self) -> Value {
+        self.recovering
+            .and_then(|panicking| panicking.lock().unwrap().take())
+            .unwrap_or(Value::Nil)
+    }
+}
+
+impl Debug for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            Source::Function(name) | Source::Method(_, name) => write!(f, "func {}", name),
+            Source::Init(_) => write!(f, "func init"),
+            Source::Literal { .. } => write!(f, "func literal"),
+        }
+    }
+}
+
+/// Functions are the same if they come from the same source, and closures only if they were
+/// created by the same evaluation of a function literal
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        if self.package != other.package {
+            return false;
+        }
+        match (&self.source, &other.source) {
+            (Source::Function(left), Source::Function(right)) => left == right,
+            (Source::Method(left_type, left), Source::Method(right_type, right)) => {
+                left_type == right_type && left == right
+            }
+            (Source::Init(left), Source::Init(right)) => left == right,
+            (
+                Source::Literal {
+                    offset: left,
+                    captured: left_captured,
+                },
+                Source::Literal {
+                    offset: right,
+                    captured: right_captured,
+                },
+            ) => left == right && Arc::ptr_eq(left_captured, right_captured),
+            _ => false,
+        }
+    }
+}
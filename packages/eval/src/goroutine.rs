@@ -0,0 +1,288 @@
+use crate::function::Function;
+use crate::panic::describe;
+use crate::{Arguments, EvalResult, Failure, Frame, RuntimeError, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::task::JoinHandle;
+
+tokio::task_local! {
+    /// The goroutine that's running on the current task, which may be one of its calls
+    static GOROUTINE: Arc<Goroutine>;
+}
+
+/// How many calls a goroutine may be in the middle of, past which the program fails just as a Go
+/// program does once a goroutine's stack outgrows its limit
+pub const MAX_DEPTH: usize = 100_000;
+
+/// The goroutines started by a call to [run_main]
+#[derive(Debug)]
+struct Program {
+    /// Where a goroutine reports an error, which ends the whole program just as an unrecovered
+    /// panic does in Go
+    failures: UnboundedSender<Failure>,
+    counts: Mutex<Counts>,
+    /// The id of the next goroutine to start, where `main` is goroutine 1
+    next_id: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Counts {
+    /// Goroutines that haven't returned yet, including `main`
+    running: usize,
+    /// How many of them are waiting on a channel
+    blocked: usize,
+}
+
+#[derive(Debug)]
+struct Goroutine {
+    id: u64,
+    program: Arc<Program>,
+    /// Where the panic that's currently unwinding has been so far
+    trace: Mutex<Trace>,
+    /// How many calls are in progress
+    depth: AtomicUsize,
+}
+
+/// The calls that a panic has unwound out of, and the line it reached in the call it's in now
+#[derive(Debug, Default)]
+pub(crate) struct Trace {
+    frames: Vec<Frame>,
+    line: Option<usize>,
+}
+
+impl Program {
+    fn fail(&self, failure: Failure) {
+        // The program has already ended if nothing's listening
+        let _ = self.failures.send(failure);
+    }
+
+    /// Once every goroutine is blocked, none of them can ever be woken
+    fn check(&self, counts: &Counts) {
+        if counts.running > 0 && counts.blocked == counts.running {
+            self.fail(Failure {
+                error: RuntimeError::Deadlock,
+                goroutine: 1,
+                trace: vec![],
+                panic: None,
+            });
+        }
+    }
+}
+
+impl Goroutine {
+    fn new(id: u64, program: Arc<Program>) -> Arc<Self> {
+        Arc::new(Goroutine {
+            id,
+            program,
+            trace: Default::default(),
+            depth: AtomicUsize::new(0),
+        })
+    }
+}
+
+/// Run `main` until it returns, or until any goroutine it starts fails.
+///
+/// Goroutines that are still running when `main` returns are left behind, so the program may exit
+/// without waiting for them.
+pub(crate) async fn run_main(main: impl Future<Output = EvalResult>) -> Result<Value, Failure> {
+    let (failures, mut receiver) = unbounded_channel();
+    let program = Arc::new(Program {
+        failures,
+        counts: Mutex::new(Counts {
+            running: 1,
+            blocked: 0,
+        }),
+        next_id: AtomicU64::new(2),
+    });
+    let main = async {
+        match main.await {
+            Ok(value) => Ok(value),
+            Err(error) => Err(failed(error).await),
+        }
+    };
+    tokio::select! {
+        result = GOROUTINE.scope(Goroutine::new(1, program), main) => result,
+        Some(failure) = receiver.recv() => Err(failure),
+    }
+}
+
+/// `go f(x)`: run the call as a new tokio task, without waiting for it.
+///
+/// A goroutine started outside of [run_main] has nowhere to report an error, so it's dropped.
+pub(crate) fn spawn(function: Value, arguments: Arguments) {
+    match GOROUTINE.try_with(|goroutine| goroutine.program.clone()) {
+        Ok(program) => {
+            // Counted before it starts, so that it can wake whoever's waiting for it
+            program.counts.lock().unwrap().running += 1;
+            let id = program.next_id.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(
+                GOROUTINE.scope(Goroutine::new(id, program.clone()), async move {
+                    if let Err(error) = function.call(arguments).await {
+                        program.fail(failed(error).await);
+                    }
+                    let mut counts = program.counts.lock().unwrap();
+                    counts.running -= 1;
+                    program.check(&counts);
+                }),
+            );
+        }
+        Err(_) => {
+            tokio::spawn(async move { function.call(arguments).await });
+        }
+    }
+}
+
+/// Call `function`, as a tokio task of its own every few levels of calls, and wait for it to return.
+///
+/// Awaiting a call directly polls the callee from within the caller's poll, so each level of a
+/// recursive Go call would take more of the thread's stack.  As a task of its own, the call's frame
+/// stays on the heap, and it's polled by the runtime instead.
+pub(crate) fn call(
+    function: Function,
+    arguments: Arguments,
+) -> Pin<Box<dyn Future<Output = EvalResult> + Send>> {
+    Box::pin(async move {
+        let goroutine = match GOROUTINE.try_with(Arc::clone) {
+            Ok(goroutine) => goroutine,
+            Err(_) => return function.call(arguments).await,
+        };
+        let depth = goroutine.depth.fetch_add(1, Ordering::SeqCst);
+        let _call = Call(goroutine.clone());
+        if depth >= MAX_DEPTH {
+            return Err(RuntimeError::StackOverflow);
+        }
+        if depth % CALLS_PER_TASK != 0 {
+            return function.call(arguments).await;
+        }
+        let mut task =
+            Task(tokio::spawn(GOROUTINE.scope(goroutine, async move {
+                function.call(arguments).await
+            })));
+        match (&mut task.0).await {
+            Ok(result) => result,
+            Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+            // Only the runtime shutting down cancels a call, and then nothing is waiting for it
+            Err(_) => std::future::pending().await,
+        }
+    })
+}
+
+/// How many levels of calls [call] polls from within the same poll, which is as many as will fit on
+/// a tokio worker's stack with room to spare
+const CALLS_PER_TASK: usize = 8;
+
+/// A call in progress, counted towards its goroutine's depth until it returns or is dropped
+struct Call(Arc<Goroutine>);
+
+impl Drop for Call {
+    fn drop(&mut self) {
+        self.0.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A call started as a task of its own, which is cancelled along with its caller
+struct Task(JoinHandle<EvalResult>);
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// The failure of the current goroutine, along with the trace of a panic
+async fn failed(error: RuntimeError) -> Failure {
+    let goroutine = GOROUTINE.try_with(|goroutine| goroutine.id).unwrap_or(1);
+    let trace = suspend();
+    let panic = match &error {
+        RuntimeError::Panic(value) => Some(describe(value).await),
+        _ => None,
+    };
+    Failure {
+        error,
+        goroutine,
+        trace: trace.frames,
+        panic,
+    }
+}
+
+/// Note the line of the statement that a panic is unwinding out of, unless it's already reached
+/// a line in the current call
+pub(crate) fn panicked_at(line: usize) {
+    let _ = GOROUTINE.try_with(|goroutine| {
+        goroutine.trace.lock().unwrap().line.get_or_insert(line);
+    });
+}
+
+/// Set aside the trace of a panic while the deferred calls of the function it's unwinding out of
+/// run, since they may panic in turn
+pub(crate) fn suspend() -> Trace {
+    GOROUTINE
+        .try_with(|goroutine| std::mem::take(&mut *goroutine.trace.lock().unwrap()))
+        .unwrap_or_default()
+}
+
+/// Carry on unwinding out of the call to `function`, once its deferred calls haven't recovered.
+///
+/// A panic raised by a deferred call replaces the one that was set aside, along with its trace.
+pub(crate) fn unwind(suspended: Trace, function: String, arguments: bool) {
+    let _ = GOROUTINE.try_with(|goroutine| {
+        let mut trace = goroutine.trace.lock().unwrap();
+        if trace.frames.is_empty() {
+            trace.frames = suspended.frames;
+        }
+        trace.frames.push(Frame {
+            function,
+            arguments,
+            line: suspended.line,
+        });
+        trace.line = None;
+    });
+}
+
+/// Count the current goroutine as blocked until the result is woken or dropped.
+///
+/// The program fails with a deadlock if this was the last goroutine that could run.
+pub(crate) fn block() -> Blocked {
+    let program = GOROUTINE
+        .try_with(|goroutine| goroutine.program.clone())
+        .ok();
+    if let Some(program) = &program {
+        let mut counts = program.counts.lock().unwrap();
+        counts.blocked += 1;
+        program.check(&counts);
+    }
+    Blocked {
+        program,
+        woken: AtomicBool::new(false),
+    }
+}
+
+/// A goroutine that's waiting on a channel, as counted by [block]
+#[derive(Debug)]
+pub(crate) struct Blocked {
+    program: Option<Arc<Program>>,
+    woken: AtomicBool,
+}
+
+impl Blocked {
+    /// Count the goroutine as running again.
+    ///
+    /// This is called by whichever goroutine wakes it, before it has a chance to block in turn.
+    pub(crate) fn wake(&self) {
+        if self.woken.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if let Some(program) = &self.program {
+            program.counts.lock().unwrap().blocked -= 1;
+        }
+    }
+}
+
+impl Drop for Blocked {
+    fn drop(&mut self) {
+        self.wake()
+    }
+}
@@ -1,6 +1,5 @@
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     missing_debug_implementations,
@@ -9,7 +8,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unreachable_pub,
     unused,
@@ -27,15 +25,17 @@ use lazy_static::lazy_static;
 use std::any::Any;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
-use gor_ast::expression::{Expression, InnerExpression};
+use gor_ast::expression::Expression;
+use gor_ast::module::SourceModule;
 use gor_ast::name::Name;
+use gor_ast::types::{ChannelDirection, InnerType};
 use gor_parse::ParseError;
 use RuntimeError::{TypeMismatch, TypeOpMismatch};
 
-use crate::extensions::{Evaluable, UniOpExt};
-use extensions::BinOpExt;
+use crate::extensions::{Executable, TypeExt};
 use gor_ast::AstError;
 use gor_core::parse_error::InternalError;
 use gor_linker::{Linker, LinkerError};
@@ -47,7 +47,10 @@ pub mod test;
 #[derive(Debug, PartialEq)]
 #[non_exhaustive]
 pub enum LanguageFeature {
-    ExecutingFunctions,
+    NestedFunctions,
+    AnonymousStructs,
+    /// Methods on a named type other than a struct or a number, such as `type List []int`
+    NamedTypeMethods,
 }
 
 impl TryFrom<&str> for LanguageFeature {
@@ -55,7 +58,9 @@ impl TryFrom<&str> for LanguageFeature {
 
     fn try_from(value: &str) -> Result<Self, <Self as TryFrom<&str>>::Error> {
         match value {
-            "ExecutingFunctions" => Ok(LanguageFeature::ExecutingFunctions),
+            "NestedFunctions" => Ok(LanguageFeature::NestedFunctions),
+            "AnonymousStructs" => Ok(LanguageFeature::AnonymousStructs),
+            "NamedTypeMethods" => Ok(LanguageFeature::NamedTypeMethods),
             _ => Err(InternalError::Error(format!(
                 "Unknown language feature: {}",
                 value
@@ -69,10 +74,82 @@ impl TryFrom<&str> for LanguageFeature {
 pub enum RuntimeError {
     #[error("Not a function: {0:?}")]
     NotAFunction(Value),
+    #[error("Expected a function call: {0}")]
+    NotACall(String),
     #[error("Name not found: {0}")]
     NameError(Name),
     #[error("Can't static eval {0:?}")]
     StaticEvaluationFailure(String),
+    #[error("Cannot assign to {0}")]
+    NotAssignable(String),
+    #[error("No new variables on left side of :=")]
+    NoNewVariables,
+    #[error("Unknown type: {0}")]
+    UnknownType(String),
+    #[error("{0} outside of a loop")]
+    BranchOutsideLoop(String),
+    #[error("fallthrough statement out of place")]
+    MisplacedFallthrough,
+    #[error("Unknown field {field} in {r#type:?}")]
+    UnknownField { r#type: Type, field: Name },
+    /// A Go panic, which unwinds the stack of the goroutine that raised it
+    #[error("panic: {0}")]
+    Panic(Value),
+    #[error("Cannot take the address of {0}")]
+    NotAddressable(String),
+    #[error("Invalid selector: {0}")]
+    InvalidSelector(String),
+    #[error("Invalid composite literal: {0}")]
+    InvalidCompositeLiteral(String),
+    #[error("Invalid argument of type {type} for {function}", type = r#type)]
+    InvalidArgument {
+        function: &'static str,
+        r#type: Type,
+    },
+    #[error("{0} is a type, not a value")]
+    NotAValue(String),
+    #[error("Invalid array length: {0}")]
+    InvalidArrayLength(String),
+    #[error("Cannot index {0}")]
+    NotIndexable(Type),
+    #[error("Invalid map key type {0}")]
+    InvalidMapKey(Type),
+    #[error("Assignment mismatch: {expected} variables but {found} values")]
+    AssignmentMismatch { expected: usize, found: usize },
+    #[error("Multiple-value {0} in single-value context")]
+    MultipleValues(String),
+    #[error("Cannot use ... in call to a non-variadic function")]
+    NotVariadic,
+    #[error("Invalid operation: cannot {operation} {type}", type = r#type)]
+    ChannelDirection {
+        operation: &'static str,
+        r#type: Type,
+    },
+    #[error("Constant {value} overflows {type}", type = r#type)]
+    ConstantOverflow { value: String, r#type: Type },
+    #[error("Constant {value} truncated to {type}", type = r#type)]
+    ConstantTruncated { value: String, r#type: Type },
+    /// An untyped constant that's too large to represent exactly
+    #[error("Constant overflow: {0}")]
+    PrecisionOverflow(String),
+    #[error("Invalid operation: division by zero")]
+    DivisionByZero,
+    #[error("Invalid shift count {0}")]
+    InvalidShiftCount(String),
+    #[error("Initialization cycle: {0} refers to itself")]
+    InitializationCycle(Name),
+    #[error("Cannot refer to unexported name {0}")]
+    Unexported(String),
+    #[error("defer outside of a function")]
+    DeferOutsideFunction,
+    /// Every goroutine is waiting on a channel, so none of them can continue
+    #[error("all goroutines are asleep - deadlock!")]
+    Deadlock,
+    /// A goroutine is in the middle of more than [MAX_DEPTH] calls
+    #[error("stack overflow")]
+    StackOverflow,
+    #[error("Wrong number of arguments: expected {expected}, found {found}")]
+    ArgumentCount { expected: usize, found: usize },
     #[error("Type Mismatch: expected {expected:?}, not {found:?}")]
     TypeError { expected: Type, found: Type },
     #[error("Type Mismatch: expected the same type, found {left:?} {op:?} {right:?}")]
@@ -90,16 +167,174 @@ pub enum RuntimeError {
     UnsupportedFeature(LanguageFeature),
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+impl RuntimeError {
+    /// A panic raised by the runtime itself, rather than by a call to `panic`
+    pub(crate) fn panic(message: impl Into<String>) -> Self {
+        RuntimeError::Panic(Value::Error(message.into()))
+    }
+}
+
+/// The message Go panics with when dereferencing a nil pointer or interface
+pub(crate) const NIL_DEREFERENCE: &str =
+    "runtime error: invalid memory address or nil pointer dereference";
+
+#[derive(Debug, Eq, PartialEq, Clone)]
 #[non_exhaustive]
 pub enum Type {
     Int,
+    /// Any of the other predeclared numeric types
+    Numeric(Numeric),
     Boolean,
     String,
     Function,
+    Named(NamedType),
+    Pointer(Box<Type>),
+    Slice(Box<Type>),
+    Array(usize, Box<Type>),
+    /// `map[K]V`
+    Map(Box<Type>, Box<Type>),
+    /// `chan T`, `chan<- T` or `<-chan T`
+    Channel(ChannelDirection, Box<Type>),
+    Interface(InterfaceType),
+    /// `runtime.Error`, the type of the value that a panic raised by the runtime itself holds
+    Error,
+    /// The results of a function that returns more than one
+    Tuple(Vec<Type>),
+    /// The type of the untyped `nil`
+    Nil,
     Void,
 }
 
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Numeric(numeric) => write!(f, "{}", numeric.name()),
+            Type::Boolean => write!(f, "bool"),
+            Type::String => write!(f, "string"),
+            Type::Function => write!(f, "func"),
+            Type::Named(named) => write!(f, "{}", named.name()),
+            Type::Pointer(pointee) => write!(f, "*{}", pointee),
+            Type::Slice(element) => write!(f, "[]{}", element),
+            Type::Array(len, element) => write!(f, "[{}]{}", len, element),
+            Type::Map(key, value) => write!(f, "map[{}]{}", key, value),
+            Type::Channel(direction, element) => {
+                match direction {
+                    ChannelDirection::Both => write!(f, "chan ")?,
+                    ChannelDirection::Send => write!(f, "chan<- ")?,
+                    ChannelDirection::Receive => write!(f, "<-chan ")?,
+                }
+                match **element {
+                    // `chan <-chan T` would be read as `chan<- chan T`
+                    Type::Channel(ChannelDirection::Receive, _)
+                        if *direction == ChannelDirection::Both =>
+                    {
+                        write!(f, "({})", element)
+                    }
+                    _ => write!(f, "{}", element),
+                }
+            }
+            Type::Interface(interface) => Display::fmt(interface, f),
+            Type::Tuple(types) => write!(f, "({})", join(types)),
+            Type::Error => write!(f, "runtime.Error"),
+            Type::Nil => write!(f, "nil"),
+            Type::Void => write!(f, "void"),
+        }
+    }
+}
+
+impl Type {
+    /// Whether a value may be stored in a variable of this type
+    pub fn accepts(&self, value: &Value) -> bool {
+        match self {
+            Type::Interface(interface) => interface.is_implemented_by(value),
+            Type::Pointer(_) | Type::Function if *value == Value::Nil => true,
+            // A bidirectional channel may be restricted to either direction
+            Type::Channel(direction, element) => match value {
+                Value::Channel(channel) => {
+                    channel.element() == &**element
+                        && (channel.direction() == *direction
+                            || channel.direction() == ChannelDirection::Both)
+                }
+                _ => false,
+            },
+            r#type => *r#type == value.as_type(),
+        }
+    }
+
+    /// Whether a type assertion of the value to this type succeeds.
+    ///
+    /// Unlike [Type::accepts], no type matches `nil`.
+    pub fn matches(&self, value: &Value) -> bool {
+        *value != Value::Nil && self.accepts(value)
+    }
+
+    /// The kind of number that values of this type are, if they're numbers
+    pub fn numeric(&self) -> Option<Numeric> {
+        match self {
+            Type::Int => Some(Numeric::Int),
+            Type::Numeric(numeric) => Some(*numeric),
+            Type::Named(named) => named.numeric(),
+            _ => None,
+        }
+    }
+
+    /// Whether values of this type may be compared with `==`, and so used as map keys
+    pub fn is_comparable(&self) -> bool {
+        match self {
+            Type::Int
+            | Type::Numeric(_)
+            | Type::Boolean
+            | Type::String
+            | Type::Pointer(_)
+            | Type::Channel(..)
+            | Type::Interface(_)
+            | Type::Error => true,
+            Type::Array(_, element) => element.is_comparable(),
+            Type::Named(named) => named
+                .fields()
+                .map(|fields| fields.iter().all(|(_, field)| field.is_comparable()))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// The value held by a variable of this type that hasn't been explicitly initialised
+    pub fn zero_value(&self) -> EvalResult {
+        match self {
+            Type::Int => Ok(Value::Int(0)),
+            Type::Numeric(_) => Ok(Number::integer(self, 0)?.value()),
+            Type::Boolean => Ok(Value::Boolean(false)),
+            Type::String => Ok(Value::String(String::new())),
+            Type::Named(named) => named.zero_value(),
+            Type::Pointer(pointee) => Ok(Value::Pointer(Pointer::nil((**pointee).clone()))),
+            Type::Interface(_) | Type::Function => Ok(Value::Nil),
+            Type::Slice(element) => Ok(Value::Slice(Slice::nil((**element).clone()))),
+            Type::Array(len, element) => Ok(Value::Array(Array::zero((**element).clone(), *len)?)),
+            Type::Map(key, value) => Ok(Value::Map(Map::nil((**key).clone(), (**value).clone()))),
+            Type::Channel(direction, element) => Ok(Value::Channel(
+                Channel::nil((**element).clone()).with_direction(*direction),
+            )),
+            _ => Err(RuntimeError::UnknownType(format!("{:?}", self))),
+        }
+    }
+
+    /// Convert a value that's about to be stored as this type: an untyped number as
+    /// [numeric::implicit] converts it, `nil` to a nil pointer, and a pointer to one held by an
+    /// interface, which isn't nil even if the pointer is
+    pub(crate) fn implicit(&self, value: Value) -> EvalResult {
+        Ok(match (numeric::implicit(self, value)?, self) {
+            (Value::Nil, Type::Pointer(pointee)) => {
+                Value::Pointer(Pointer::nil((**pointee).clone()))
+            }
+            (Value::Pointer(pointer), r#type) => {
+                Value::Pointer(pointer.boxed(matches!(r#type, Type::Interface(_))))
+            }
+            (value, _) => value,
+        })
+    }
+}
+
 /// A primative value that may be the result of a Go [expression].
 ///
 /// [expression]: ../ast/expression.html
@@ -108,32 +343,95 @@ pub enum Type {
 pub enum Value {
     /// A 64-bit signed int
     Int(i64),
+    /// A number of any other numeric type, or of a type declared as a number
+    Number(Number),
     /// A boolean
     Boolean(bool),
     /// A string literal
     String(String),
     /// An intrinsic -- globally scoped, known to Rust code.
     Intrinsic(Intrinsic),
+    /// A function declared in Go source.
+    Function(Function),
+    /// A struct, which is copied along with all its fields
+    Struct(Struct),
+    /// The address of a variable, or of a field within one
+    Pointer(Pointer),
+    /// A window onto an array, sharing its elements with any other slice of the same array
+    Slice(Slice),
+    /// A fixed number of elements, which are all copied along with the array
+    Array(Array),
+    /// A reference to a table of entries, which is shared by every copy of the map
+    Map(Map),
+    /// A reference to a queue of values, which is shared by every copy of the channel
+    Channel(Channel),
+    /// A panic raised by the runtime itself, such as an index out of range, which is an `error`
+    /// whose `Error` method gives its message
+    Error(String),
+    /// The results of a call to a function that returns more than one, which have to be unpacked
+    /// by an assignment or passed straight on as the arguments to another call
+    Tuple(Vec<Value>),
+    /// The untyped `nil`, which is also the value of an interface that doesn't hold anything
+    Nil,
     /// The "bottom" type, no value.
     Void,
 }
 
+/// Display each item, separated by commas
+fn join<T: Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 pub type EvalResult = Result<Value, RuntimeError>;
 type RuntimeResult<R> = Result<R, RuntimeError>;
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 #[non_exhaustive]
 pub enum Intrinsic {
     Print,
+    Len,
+    Cap,
+    Append,
+    Copy,
+    Delete,
+    Clear,
+    Close,
+    /// Takes a type as its first argument, so it's only ever called directly by name
+    Make,
+    /// Takes a type as its only argument, like [Intrinsic::Make]
+    New,
+    Panic,
+    /// Only recovers when called by name, since it needs to know which function called it
+    Recover,
+    Complex,
+    Real,
+    Imag,
+    /// The `Error` method of a [Value::Error], bound to its message
+    Error(String),
 }
 
 impl Display for Value {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Int(n) => Display::fmt(&n, f),
+            Value::Number(n) => Display::fmt(n, f),
             Value::Boolean(b) => Display::fmt(&b, f),
             Value::String(s) => Display::fmt(&s, f),
             Value::Intrinsic(n) => Debug::fmt(&n.type_id(), f),
+            Value::Function(function) => Debug::fmt(function, f),
+            Value::Struct(s) => Display::fmt(s, f),
+            Value::Pointer(pointer) => Display::fmt(pointer, f),
+            Value::Slice(slice) => Display::fmt(slice, f),
+            Value::Array(array) => Display::fmt(array, f),
+            Value::Map(map) => Display::fmt(map, f),
+            Value::Channel(channel) => Display::fmt(channel, f),
+            Value::Error(message) => Display::fmt(message, f),
+            Value::Tuple(values) => write!(f, "({})", join(values)),
+            Value::Nil => Display::fmt("<nil>", f),
             Value::Void => Display::fmt("<void>", f),
         }
     }
@@ -141,20 +439,40 @@ impl Display for Value {
 
 impl Value {
     /// Acquire the type of the value
-    pub const fn as_type(&self) -> Type {
+    pub fn as_type(&self) -> Type {
         match self {
             Value::Int(_) => Type::Int,
+            Value::Number(n) => n.r#type(),
             Value::Boolean(_) => Type::Boolean,
             Value::String(_) => Type::String,
-            Value::Intrinsic(_) => Type::Function,
+            Value::Intrinsic(_) | Value::Function(_) => Type::Function,
+            Value::Struct(s) => Type::Named(s.r#type()),
+            Value::Pointer(pointer) => Type::Pointer(Box::new(pointer.pointee().clone())),
+            Value::Slice(slice) => Type::Slice(Box::new(slice.element().clone())),
+            Value::Array(array) => Type::Array(array.len(), Box::new(array.element().clone())),
+            Value::Map(map) => {
+                Type::Map(Box::new(map.key().clone()), Box::new(map.value().clone()))
+            }
+            Value::Channel(channel) => {
+                Type::Channel(channel.direction(), Box::new(channel.element().clone()))
+            }
+            Value::Error(_) => Type::Error,
+            Value::Tuple(values) => Type::Tuple(values.iter().map(Value::as_type).collect()),
+            Value::Nil => Type::Nil,
             Value::Void => Type::Void,
         }
     }
 
     /// If this value is able to be represented as a signed integer, return it.
-    pub const fn as_int(&self) -> RuntimeResult<i64> {
+    ///
+    /// An integer of another type that's too large for an `i64` gives its largest value.
+    pub fn as_int(&self) -> RuntimeResult<i64> {
         match self {
             Value::Int(n) => Ok(*n),
+            Value::Number(number) if number.as_integer().is_some() => Ok(number
+                .as_integer()
+                .and_then(|n| i64::try_from(n).ok())
+                .unwrap_or(i64::MAX)),
             Value::Boolean(b) => Ok(if *b { 1 } else { 0 }),
             _ => Err(RuntimeError::TypeError {
                 expected: Type::Int,
@@ -163,8 +481,17 @@ impl Value {
         }
     }
 
+    /// If this value is a number of any type, return it.
+    pub(crate) fn as_number(&self) -> Option<Number> {
+        match self {
+            Value::Int(n) => Number::integer(&Type::Int, *n as i128).ok(),
+            Value::Number(number) => Some(number.clone()),
+            _ => None,
+        }
+    }
+
     /// If this value is able to be represented as a boolean, return it.
-    pub const fn as_bool(&self) -> RuntimeResult<bool> {
+    pub fn as_bool(&self) -> RuntimeResult<bool> {
         match self {
             Value::Int(n) => Ok(*n != 0),
             Value::Boolean(b) => Ok(*b),
@@ -175,52 +502,150 @@ impl Value {
         }
     }
 
-    /// If this value has function type, apply the parameters to the function
-    pub fn call(&self, parameters: &[Value]) -> EvalResult {
-        if let Value::Intrinsic(function) = self {
-            match function {
+    /// If this value is a boolean, return it.
+    ///
+    /// Go requires conditions to be booleans, so unlike [Value::as_bool] there's no coercion.
+    pub fn as_condition(&self) -> RuntimeResult<bool> {
+        match self {
+            Value::Boolean(b) => Ok(*b),
+            _ => Err(RuntimeError::TypeError {
+                expected: Type::Boolean,
+                found: self.as_type(),
+            }),
+        }
+    }
+
+    /// Select the named field of a struct, or of the struct a pointer points to
+    pub fn field(&self, name: Name) -> EvalResult {
+        match self {
+            Value::Struct(s) => Ok(s.field(name)?.clone()),
+            Value::Pointer(pointer) => pointer.place()?.get()?.field(name),
+            Value::Nil => Err(RuntimeError::panic(NIL_DEREFERENCE)),
+            _ => Err(RuntimeError::UnknownField {
+                r#type: self.as_type(),
+                field: name,
+            }),
+        }
+    }
+
+    /// Whether both values have the same type, where a channel restricted to one direction is still
+    /// the same channel
+    pub(crate) fn same_type(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Channel(left), Value::Channel(right)) => left.element() == right.element(),
+            _ => self.as_type() == other.as_type(),
+        }
+    }
+
+    /// Whether the values can be compared without either of them being an interface: they have the
+    /// same type, or one of them is `nil`
+    pub(crate) fn comparable(&self, other: &Value) -> bool {
+        *self == Value::Nil || *other == Value::Nil || self.same_type(other)
+    }
+
+    /// `==`, where values held by an `interface` may have different types, and then they're never
+    /// equal
+    pub(crate) fn equals(self, right: Value, interface: bool) -> RuntimeResult<bool> {
+        if interface && !self.comparable(&right) {
+            return Ok(false);
+        }
+        self.bin_op(BinOp::Eq, right)?.as_bool()
+    }
+
+    /// Whether this is `nil`, or a nil value of a type that has one.
+    ///
+    /// An interface holding a nil pointer isn't nil.
+    pub fn is_nil(&self) -> bool {
+        match self {
+            Value::Nil => true,
+            Value::Pointer(pointer) => pointer.is_nil() && !pointer.is_boxed(),
+            Value::Slice(slice) => slice.is_nil(),
+            Value::Map(map) => map.is_nil(),
+            Value::Channel(channel) => channel.is_nil(),
+            _ => false,
+        }
+    }
+
+    /// If this value has function type, apply the arguments to the function
+    pub async fn call(&self, arguments: Arguments) -> EvalResult {
+        match self {
+            Value::Intrinsic(function) => match function {
                 Intrinsic::Print => {
-                    parameters.iter().for_each(|v| print!("{}", v));
+                    let (_, values) = arguments.variadic(0)?;
+                    values.into_values()?.iter().for_each(|v| print!("{}", v));
                     println!();
                     Ok(Value::Void)
                 }
-            }
-        } else {
-            Err(RuntimeError::NotAFunction(self.clone()))
+                Intrinsic::Len => builtins::len(arguments),
+                Intrinsic::Cap => builtins::cap(arguments),
+                Intrinsic::Append => builtins::append(arguments),
+                Intrinsic::Copy => builtins::copy(arguments),
+                Intrinsic::Delete => builtins::delete(arguments),
+                Intrinsic::Clear => builtins::clear(arguments),
+                Intrinsic::Close => builtins::close(arguments),
+                Intrinsic::Panic => builtins::panic(arguments),
+                Intrinsic::Complex => builtins::complex(arguments),
+                Intrinsic::Real => builtins::real(arguments),
+                Intrinsic::Imag => builtins::imag(arguments),
+                Intrinsic::Error(message) => Ok(Value::String(message.clone())),
+                // Called by `defer recover()`, which isn't directly inside a deferred function
+                Intrinsic::Recover => Ok(Value::Nil),
+                Intrinsic::Make | Intrinsic::New => Err(RuntimeError::NotAFunction(self.clone())),
+            },
+            Value::Function(function) => goroutine::call(function.clone(), arguments).await,
+            Value::Nil => Err(RuntimeError::panic(NIL_DEREFERENCE)),
+            _ => Err(RuntimeError::NotAFunction(self.clone())),
         }
     }
 
     /// Attempt to apply `right` to this value using `op`.
     pub fn bin_op(self, op: BinOp, right: Value) -> EvalResult {
-        if self.as_type() != right.as_type() {
+        // An interface holding a value is never equal to nil
+        if matches!(op, BinOp::Eq | BinOp::Neq) && (self == Value::Nil || right == Value::Nil) {
+            return Ok(Value::Boolean(
+                (self.is_nil() && right.is_nil()) == (op == BinOp::Eq),
+            ));
+        }
+        // The count of a shift may be any type of integer
+        if matches!(op, BinOp::Shl | BinOp::Shr) {
+            if let (Some(left), Some(right)) = (self.as_number(), right.as_number()) {
+                return left.shift(op, right);
+            }
+        }
+        if !self.same_type(&right) {
             return Err(TypeMismatch {
                 left: self.as_type(),
                 op,
                 right: right.as_type(),
             });
         }
+        if let (Some(left), Some(right)) = (self.as_number(), right.as_number()) {
+            return left.bin_op(op, right);
+        }
+        // Only interfaces holding them can be compared, and that panics
+        if matches!(op, BinOp::Eq | BinOp::Neq) && !self.as_type().is_comparable() {
+            return Err(RuntimeError::panic(format!(
+                "runtime error: comparing uncomparable type {}",
+                self.as_type()
+            )));
+        }
         match self.as_type() {
-            Type::Int => {
-                let left = self.as_int()?;
-                let right = right.as_int()?;
+            Type::Boolean => {
+                let left = self.as_bool()?;
+                let right = right.as_bool()?;
                 Ok(match op {
                     BinOp::Eq => Value::Boolean(left == right),
                     BinOp::Neq => Value::Boolean(left != right),
-                    BinOp::Lt => Value::Boolean(left < right),
+                    BinOp::Lt => Value::Boolean(!left & right),
                     BinOp::Leq => Value::Boolean(left <= right),
-                    BinOp::Gt => Value::Boolean(left > right),
+                    BinOp::Gt => Value::Boolean(left & !right),
                     BinOp::Geq => Value::Boolean(left >= right),
-                    BinOp::Add => Value::Int(left + right),
-                    BinOp::Sub => Value::Int(left - right),
-                    BinOp::BitOr => Value::Int(left | right),
-                    BinOp::BitXor => Value::Int(left ^ right),
-                    BinOp::Mul => Value::Int(left * right),
-                    BinOp::Div => Value::Int(left / right),
-                    BinOp::Modulo => Value::Int(left % right),
-                    BinOp::Shl => Value::Int(left << right),
-                    BinOp::Shr => Value::Int(left >> right),
-                    BinOp::BitAnd => Value::Int(left & right),
-                    BinOp::BitClear => Value::Int(left & !right),
+                    BinOp::BitOr => Value::Boolean(left | right),
+                    BinOp::BitXor => Value::Boolean(left ^ right),
+                    BinOp::BitAnd => Value::Boolean(left & right),
+                    BinOp::BitClear => Value::Boolean(left & !right),
+                    BinOp::LogicalAnd => Value::Boolean(left && right),
+                    BinOp::LogicalOr => Value::Boolean(left || right),
                     _ => {
                         return Err(TypeOpMismatch {
                             op,
@@ -229,22 +654,93 @@ impl Value {
                     }
                 })
             }
-            Type::Boolean => {
-                let left = self.as_bool()?;
-                let right = right.as_bool()?;
+            Type::String => {
+                let (Value::String(left), Value::String(right)) = (&self, &right) else {
+                    unreachable!("both sides have string type")
+                };
                 Ok(match op {
                     BinOp::Eq => Value::Boolean(left == right),
                     BinOp::Neq => Value::Boolean(left != right),
-                    BinOp::Lt => Value::Boolean(!left & right),
+                    BinOp::Lt => Value::Boolean(left < right),
                     BinOp::Leq => Value::Boolean(left <= right),
-                    BinOp::Gt => Value::Boolean(left & !right),
+                    BinOp::Gt => Value::Boolean(left > right),
                     BinOp::Geq => Value::Boolean(left >= right),
-                    BinOp::BitOr => Value::Boolean(left | right),
-                    BinOp::BitXor => Value::Boolean(left ^ right),
-                    BinOp::BitAnd => Value::Boolean(left & right),
-                    BinOp::BitClear => Value::Boolean(left & !right),
-                    BinOp::LogicalAnd => Value::Boolean(left && right),
-                    BinOp::LogicalOr => Value::Boolean(left || right),
+                    BinOp::Add => Value::String(format!("{}{}", left, right)),
+                    _ => {
+                        return Err(TypeOpMismatch {
+                            op,
+                            r#type: self.as_type(),
+                        })
+                    }
+                })
+            }
+            Type::Named(_) => {
+                let (Value::Struct(left), Value::Struct(right)) = (&self, &right) else {
+                    unreachable!("named types other than numbers are structs")
+                };
+                Ok(match op {
+                    BinOp::Eq => Value::Boolean(left.equals(right)?),
+                    BinOp::Neq => Value::Boolean(!left.equals(right)?),
+                    _ => {
+                        return Err(TypeOpMismatch {
+                            op,
+                            r#type: self.as_type(),
+                        })
+                    }
+                })
+            }
+            Type::Error => {
+                let (Value::Error(left), Value::Error(right)) = (&self, &right) else {
+                    unreachable!("both sides are runtime errors")
+                };
+                Ok(match op {
+                    BinOp::Eq => Value::Boolean(left == right),
+                    BinOp::Neq => Value::Boolean(left != right),
+                    _ => {
+                        return Err(TypeOpMismatch {
+                            op,
+                            r#type: self.as_type(),
+                        })
+                    }
+                })
+            }
+            Type::Array(..) => {
+                let (Value::Array(left), Value::Array(right)) = (&self, &right) else {
+                    unreachable!("both sides have array type")
+                };
+                Ok(match op {
+                    BinOp::Eq => Value::Boolean(left.equals(right)?),
+                    BinOp::Neq => Value::Boolean(!left.equals(right)?),
+                    _ => {
+                        return Err(TypeOpMismatch {
+                            op,
+                            r#type: self.as_type(),
+                        })
+                    }
+                })
+            }
+            Type::Channel(..) => {
+                let (Value::Channel(left), Value::Channel(right)) = (&self, &right) else {
+                    unreachable!("both sides have channel type")
+                };
+                Ok(match op {
+                    BinOp::Eq => Value::Boolean(left == right),
+                    BinOp::Neq => Value::Boolean(left != right),
+                    _ => {
+                        return Err(TypeOpMismatch {
+                            op,
+                            r#type: self.as_type(),
+                        })
+                    }
+                })
+            }
+            Type::Pointer(_) => {
+                let (Value::Pointer(left), Value::Pointer(right)) = (&self, &right) else {
+                    unreachable!("both sides have pointer type")
+                };
+                Ok(match op {
+                    BinOp::Eq => Value::Boolean(left == right),
+                    BinOp::Neq => Value::Boolean(left != right),
                     _ => {
                         return Err(TypeOpMismatch {
                             op,
@@ -264,46 +760,148 @@ impl Value {
 /// Attempt to synchronously evaluate an expression
 ///
 /// If an expression doesn't contain any external references then we can evaluate it without context
-/// or any async calls.
+/// or any async calls.  It's evaluated exactly as a constant, and only has to fit in an `int` at
+/// the end: `1 << 100 >> 98` is 4.
+///
+/// Whether the expression is constant is remembered as it is for any other context, so an
+/// expression that's also evaluated within a package mustn't refer to any names.
 pub fn try_static_eval<'i>(exp: &'i Expression<'i>) -> EvalResult {
-    match &exp.inner {
-        InnerExpression::BinOp { left, op, right } => match op {
-            BinOp::LogicalAnd => Ok(Value::Boolean(
-                try_static_eval(left)?.as_bool()? && try_static_eval(right)?.as_bool()?,
-            )),
-            BinOp::LogicalOr => Ok(Value::Boolean(
-                try_static_eval(left)?.as_bool()? || try_static_eval(right)?.as_bool()?,
-            )),
-            op => Ok(op.static_apply(try_static_eval(left)?, try_static_eval(right)?)?),
-        },
-        InnerExpression::String(_) | InnerExpression::Name(_) | InnerExpression::Call { .. } => {
-            Err(RuntimeError::StaticEvaluationFailure(
-                exp.span.as_str().to_string(),
-            ))
-        }
-        InnerExpression::Number(n) => Ok(Value::Int(*n)),
-        InnerExpression::UniOp { op, exp } => Ok(op.static_apply(try_static_eval(exp)?)?),
-    }
+    constant::evaluate(exp, &*GLOBAL_CONTEXT)?.value()
 }
 
 pub trait ExecutionContext: Sync + Debug {
-    fn value(&self, name: Name) -> RuntimeResult<&Value> {
+    fn value(&self, name: Name) -> RuntimeResult<Value> {
         Err(RuntimeError::NameError(name))
     }
 
     fn module(&self, name: Name) -> RuntimeResult<&ModuleDescriptor> {
         Err(RuntimeError::NameError(name))
     }
+
+    /// The constant declared with this name, which isn't constant if it's shadowed by a variable
+    fn constant(&self, name: Name) -> RuntimeResult<Constant> {
+        Err(RuntimeError::StaticEvaluationFailure(name.to_string()))
+    }
+
+    /// The type declared with this name
+    fn r#type(&self, name: Name) -> RuntimeResult<Type> {
+        Err(RuntimeError::UnknownType(name.to_string()))
+    }
+
+    /// The storage for a variable that's visible from this context
+    fn variable(&self, name: Name) -> RuntimeResult<Variable> {
+        Err(RuntimeError::NameError(name))
+    }
+
+    /// The storage for a variable, only if it was declared in the innermost scope
+    fn local(&self, _name: Name) -> Option<Variable> {
+        None
+    }
+
+    /// Declare a new variable in the innermost scope, shadowing any outer declaration
+    fn declare(&self, name: Name, value: Value) -> RuntimeResult<()> {
+        self.declare_variable(name, Variable::new(value))
+    }
+
+    /// Declare a new variable in the innermost scope with storage that's already been made, such
+    /// as a variable of an interface type
+    fn declare_variable(&self, name: Name, _variable: Variable) -> RuntimeResult<()> {
+        Err(RuntimeError::NotAssignable(name.to_string()))
+    }
+
+    /// The package whose declarations are visible from this context, if there is one
+    fn package(&self) -> Option<PackageContext> {
+        None
+    }
+
+    /// Add every variable visible from this context to `variables`, unless it's shadowed by one
+    /// that's already there, so that a closure can share them
+    fn capture(&self, _variables: &mut HashMap<Name, Variable>) {}
+
+    /// Schedule a call for when the innermost function returns, or panics
+    fn defer(&self, _function: Value, _arguments: Arguments) -> RuntimeResult<()> {
+        Err(RuntimeError::DeferOutsideFunction)
+    }
+
+    /// `recover()`: stop the panic that the innermost function was deferred during, and give its
+    /// value.  Anywhere else, including in a function called by a deferred function, it's nil.
+    fn recover(&self) -> Value {
+        Value::Nil
+    }
+}
+
+impl<T: ExecutionContext + ?Sized> ExecutionContext for &T {
+    fn value(&self, name: Name) -> RuntimeResult<Value> {
+        (**self).value(name)
+    }
+
+    fn module(&self, name: Name) -> RuntimeResult<&ModuleDescriptor> {
+        (**self).module(name)
+    }
+
+    fn constant(&self, name: Name) -> RuntimeResult<Constant> {
+        (**self).constant(name)
+    }
+
+    fn r#type(&self, name: Name) -> RuntimeResult<Type> {
+        (**self).r#type(name)
+    }
+
+    fn variable(&self, name: Name) -> RuntimeResult<Variable> {
+        (**self).variable(name)
+    }
+
+    fn local(&self, name: Name) -> Option<Variable> {
+        (**self).local(name)
+    }
+
+    fn declare_variable(&self, name: Name, variable: Variable) -> RuntimeResult<()> {
+        (**self).declare_variable(name, variable)
+    }
+
+    fn package(&self) -> Option<PackageContext> {
+        (**self).package()
+    }
+
+    fn capture(&self, variables: &mut HashMap<Name, Variable>) {
+        (**self).capture(variables)
+    }
+
+    fn defer(&self, function: Value, arguments: Arguments) -> RuntimeResult<()> {
+        (**self).defer(function, arguments)
+    }
+
+    fn recover(&self) -> Value {
+        (**self).recover()
+    }
 }
 
 #[derive(Debug)]
 pub struct GlobalExecutionContext {
     globals: HashMap<Name, Value>,
+    types: HashMap<Name, Type>,
 }
 
 impl ExecutionContext for GlobalExecutionContext {
-    fn value(&self, name: Name) -> RuntimeResult<&Value> {
-        self.globals.get(&name).ok_or(RuntimeError::NameError(name))
+    fn value(&self, name: Name) -> RuntimeResult<Value> {
+        self.globals
+            .get(&name)
+            .cloned()
+            .ok_or(RuntimeError::NameError(name))
+    }
+
+    fn constant(&self, name: Name) -> RuntimeResult<Constant> {
+        match self.globals.get(&name) {
+            Some(Value::Boolean(b)) => Ok(Constant::boolean(*b)),
+            _ => Err(RuntimeError::StaticEvaluationFailure(name.to_string())),
+        }
+    }
+
+    fn r#type(&self, name: Name) -> RuntimeResult<Type> {
+        self.types
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| RuntimeError::UnknownType(name.to_string()))
     }
 }
 
@@ -317,65 +915,367 @@ lazy_static! {
     pub(crate) static ref GLOBAL_CONTEXT: GlobalExecutionContext = {
         let mut m = HashMap::new();
         m.insert("print".into(), Value::Intrinsic(Intrinsic::Print));
-        GlobalExecutionContext { globals: m }
+        m.insert("len".into(), Value::Intrinsic(Intrinsic::Len));
+        m.insert("cap".into(), Value::Intrinsic(Intrinsic::Cap));
+        m.insert("append".into(), Value::Intrinsic(Intrinsic::Append));
+        m.insert("copy".into(), Value::Intrinsic(Intrinsic::Copy));
+        m.insert("make".into(), Value::Intrinsic(Intrinsic::Make));
+        m.insert("new".into(), Value::Intrinsic(Intrinsic::New));
+        m.insert("delete".into(), Value::Intrinsic(Intrinsic::Delete));
+        m.insert("clear".into(), Value::Intrinsic(Intrinsic::Clear));
+        m.insert("close".into(), Value::Intrinsic(Intrinsic::Close));
+        m.insert("panic".into(), Value::Intrinsic(Intrinsic::Panic));
+        m.insert("recover".into(), Value::Intrinsic(Intrinsic::Recover));
+        m.insert("complex".into(), Value::Intrinsic(Intrinsic::Complex));
+        m.insert("real".into(), Value::Intrinsic(Intrinsic::Real));
+        m.insert("imag".into(), Value::Intrinsic(Intrinsic::Imag));
+        m.insert("true".into(), Value::Boolean(true));
+        m.insert("false".into(), Value::Boolean(false));
+        m.insert("nil".into(), Value::Nil);
+        let mut types = HashMap::new();
+        types.insert("int".into(), Type::Int);
+        for numeric in Numeric::PREDECLARED {
+            types.insert(numeric.name().into(), Type::Numeric(numeric));
+        }
+        types.insert("byte".into(), Type::Numeric(Numeric::Uint8));
+        types.insert("rune".into(), Type::Numeric(Numeric::Int32));
+        types.insert("bool".into(), Type::Boolean);
+        types.insert("string".into(), Type::String);
+        types.insert(
+            "any".into(),
+            Type::Interface(InterfaceType::new(None, vec![])),
+        );
+        types.insert(
+            "error".into(),
+            Type::Interface(InterfaceType::new(
+                Some("error".into()),
+                vec!["Error".into()],
+            )),
+        );
+        GlobalExecutionContext { globals: m, types }
     };
 }
 
-#[derive(Debug, Default)]
-struct ContextLadder {
-    contexts: Vec<Box<dyn ExecutionContext>>,
+/// The packages of a running program, along with their package-level variables
+#[derive(Debug)]
+struct Linked {
+    linker: Linker,
+    /// Keyed by package, then by name, and declared as each package is initialized
+    variables: Mutex<HashMap<(Name, Name), Variable>>,
+}
+
+/// The package-level scope that functions are declared in.
+///
+/// Names that aren't declared in the package fall back to the predeclared globals.  The context
+/// keeps the running program alive, and it's freed once nothing refers to it.
+#[derive(Debug, Clone)]
+pub struct PackageContext {
+    linked: Arc<Linked>,
+    package: Name,
 }
 
-impl ContextLadder {
-    fn add(&mut self, context: Box<dyn ExecutionContext>) {
-        self.contexts.insert(0, context)
+impl PackageContext {
+    fn new(linked: Arc<Linked>, package: Name) -> RuntimeResult<Self> {
+        linked.linker.lookup(package)?;
+        Ok(PackageContext { linked, package })
+    }
+
+    fn source(&self) -> &SourceModule<'_> {
+        match self.linked.linker.lookup(self.package) {
+            Ok(descriptor) => descriptor.module(),
+            Err(_) => unreachable!("the package was found when the context was made"),
+        }
+    }
+
+    /// The package imported with this name, if there is one
+    pub(crate) fn import(&self, name: Name) -> Option<PackageContext> {
+        if !self.source().imports.contains(&name) {
+            return None;
+        }
+        PackageContext::new(self.linked.clone(), name).ok()
+    }
+
+    /// `pkg.Name`, which is only visible outside the package if it starts with a capital letter
+    pub(crate) fn exported(&self, name: Name) -> EvalResult {
+        if !name.starts_with(char::is_uppercase) {
+            return Err(RuntimeError::Unexported(format!(
+                "{}.{}",
+                self.source().package,
+                name
+            )));
+        }
+        self.value(name)
+    }
+
+    fn package_variable(&self, name: Name) -> Option<Variable> {
+        let key = (self.source().package, name);
+        self.linked.variables.lock().unwrap().get(&key).cloned()
     }
 
-    fn lookup<'i, T, F>(&'i self, name: Name, mut f: F) -> RuntimeResult<&'i T>
-    where
-        F: FnMut(&'i dyn ExecutionContext, Name) -> RuntimeResult<&'i T>,
-    {
-        for context in &self.contexts {
-            if let Ok(item) = f(context.as_ref(), name) {
-                return Ok(item);
+    /// Initialize the package, once everything it imports has been: its variables in the order
+    /// their dependencies need, then its `init` functions
+    async fn initialize(&self) -> RuntimeResult<()> {
+        // Only structs and numbers keep their named type, which their methods are found through
+        for receiver in self.source().method_receivers() {
+            if !matches!(self.r#type(receiver)?, Type::Named(_)) {
+                return Err(RuntimeError::UnsupportedFeature(
+                    LanguageFeature::NamedTypeMethods,
+                ));
             }
         }
-        Err(RuntimeError::NameError(name))
+        for spec in self.source().variables() {
+            spec.execute(self).await?;
+        }
+        for index in 0..self.source().init_functions().count() {
+            Function::init(self.clone(), index)
+                .call(Arguments::default())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Contexts are the same if they're the same package of the same running program
+impl PartialEq for PackageContext {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.linked, &other.linked) && self.package == other.package
+    }
+}
+
+impl Eq for PackageContext {}
+
+impl ExecutionContext for PackageContext {
+    fn value(&self, name: Name) -> RuntimeResult<Value> {
+        if self.source().function(name).is_some() {
+            return Ok(Value::Function(Function::new(self.clone(), name)));
+        }
+        if let Some(variable) = self.package_variable(name) {
+            return Ok(variable.get());
+        }
+        match self.source().constant(name) {
+            Some(_) => self.constant(name)?.value(),
+            None => GLOBAL_CONTEXT.value(name),
+        }
+    }
+
+    fn module(&self, name: Name) -> RuntimeResult<&ModuleDescriptor> {
+        self.linked.linker.module(name)
+    }
+
+    fn variable(&self, name: Name) -> RuntimeResult<Variable> {
+        self.package_variable(name)
+            .ok_or(RuntimeError::NameError(name))
+    }
+
+    /// Declare a package-level variable, as the package is initialized
+    fn declare_variable(&self, name: Name, variable: Variable) -> RuntimeResult<()> {
+        if name.is_blank() {
+            return Ok(());
+        }
+        let key = (self.source().package, name);
+        self.linked.variables.lock().unwrap().insert(key, variable);
+        Ok(())
+    }
+
+    fn constant(&self, name: Name) -> RuntimeResult<Constant> {
+        match self.source().constant(name) {
+            Some((spec, index)) => constant::declared(self.clone(), name, spec, index),
+            None => GLOBAL_CONTEXT.constant(name),
+        }
+    }
+
+    fn package(&self) -> Option<PackageContext> {
+        Some(self.clone())
+    }
+
+    /// Only struct, interface and numeric declarations create new types for now; other
+    /// declarations behave as aliases for the type they're declared as.
+    fn r#type(&self, name: Name) -> RuntimeResult<Type> {
+        match self.source().type_spec(name) {
+            Some(spec) if !spec.alias && matches!(spec.r#type.inner, InnerType::Struct(_)) => {
+                Ok(Type::Named(NamedType::new(self.clone(), spec.name)))
+            }
+            Some(spec) => match spec.r#type.resolve(self)? {
+                Type::Interface(interface) if !spec.alias => {
+                    Ok(Type::Interface(interface.named(spec.name)))
+                }
+                r#type if r#type.numeric().is_some() && !spec.alias => {
+                    Ok(Type::Named(NamedType::new(self.clone(), spec.name)))
+                }
+                r#type => Ok(r#type),
+            },
+            None => GLOBAL_CONTEXT.r#type(name),
+        }
+    }
+}
+
+/// A block scope holding local variables, such as a function's parameters or the declarations in
+/// the body of an `if`.
+///
+/// Anything not declared in the block is looked up in the enclosing context.
+#[derive(Debug)]
+pub(crate) struct LocalContext<'c> {
+    parent: &'c dyn ExecutionContext,
+    variables: Mutex<HashMap<Name, Variable>>,
+}
+
+impl<'c> LocalContext<'c> {
+    pub(crate) fn new(parent: &'c dyn ExecutionContext) -> Self {
+        LocalContext {
+            parent,
+            variables: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<'c> LocalContext<'c> {
+    /// A new scope with the same parent, holding new variables with the same values as this one
+    pub(crate) fn next_iteration(&self) -> Self {
+        let variables = self
+            .variables
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, variable)| (*name, variable.copy()))
+            .collect();
+        LocalContext {
+            parent: self.parent,
+            variables: Mutex::new(variables),
+        }
     }
 }
 
-impl ExecutionContext for ContextLadder {
-    fn value(&self, name: Name) -> RuntimeResult<&Value> {
-        self.lookup(name, ExecutionContext::value)
+impl ExecutionContext for LocalContext<'_> {
+    fn value(&self, name: Name) -> RuntimeResult<Value> {
+        match self.local(name) {
+            Some(variable) => Ok(variable.get()),
+            None => self.parent.value(name),
+        }
     }
 
     fn module(&self, name: Name) -> RuntimeResult<&ModuleDescriptor> {
-        self.lookup(name, ExecutionContext::module)
+        self.parent.module(name)
+    }
+
+    fn constant(&self, name: Name) -> RuntimeResult<Constant> {
+        match self.local(name) {
+            Some(_) => Err(RuntimeError::StaticEvaluationFailure(name.to_string())),
+            None => self.parent.constant(name),
+        }
+    }
+
+    fn r#type(&self, name: Name) -> RuntimeResult<Type> {
+        self.parent.r#type(name)
+    }
+
+    fn variable(&self, name: Name) -> RuntimeResult<Variable> {
+        match self.local(name) {
+            Some(variable) => Ok(variable),
+            None => self.parent.variable(name),
+        }
+    }
+
+    fn local(&self, name: Name) -> Option<Variable> {
+        self.variables.lock().unwrap().get(&name).cloned()
+    }
+
+    fn declare_variable(&self, name: Name, variable: Variable) -> RuntimeResult<()> {
+        if name.is_blank() {
+            return Ok(());
+        }
+        self.variables.lock().unwrap().insert(name, variable);
+        Ok(())
+    }
+
+    fn package(&self) -> Option<PackageContext> {
+        self.parent.package()
+    }
+
+    fn capture(&self, variables: &mut HashMap<Name, Variable>) {
+        for (name, variable) in self.variables.lock().unwrap().iter() {
+            variables.entry(*name).or_insert_with(|| variable.clone());
+        }
+        self.parent.capture(variables)
+    }
+
+    fn defer(&self, function: Value, arguments: Arguments) -> RuntimeResult<()> {
+        self.parent.defer(function, arguments)
+    }
+
+    fn recover(&self) -> Value {
+        self.parent.recover()
     }
 }
 
+/// Run `fun` from `module`.
+///
+/// The linked program lives as long as any value that refers back into its source, such as a
+/// function or a value of a declared type.
 pub async fn execute_in_default_context<T: Into<Name>>(
     linker: Linker,
     module: T,
     fun: T,
 ) -> EvalResult {
-    execute_in_context(linker, ContextLadder::default(), module.into(), fun.into()).await
+    run_in_default_context(linker, module, fun)
+        .await
+        .map_err(|failure| failure.error)
 }
 
-async fn execute_in_context(
+/// Run `fun` from `module` as a program, like [execute_in_default_context], but report which
+/// goroutine failed and the calls that a panic unwound out of.
+///
+/// Every package is initialized first, in the order given by the linker.
+pub async fn run_in_default_context<T: Into<Name>>(
     linker: Linker,
-    mut context: ContextLadder,
-    module: Name,
-    fun: Name,
-) -> EvalResult {
-    context.add(Box::new(linker));
-    context
-        .module(module)?
-        .module()
-        .function(fun)
-        .ok_or(RuntimeError::NameError(fun))?
-        .evaluate(&context)
-        .await
+    module: T,
+    fun: T,
+) -> Result<Value, Failure> {
+    let linked = Arc::new(Linked {
+        linker,
+        variables: Mutex::new(HashMap::new()),
+    });
+    let package = PackageContext::new(linked.clone(), module.into())?;
+    let fun = fun.into();
+    goroutine::run_main(async move {
+        for name in linked.linker.initialization_order() {
+            PackageContext::new(linked.clone(), *name)?
+                .initialize()
+                .await?;
+        }
+        package.value(fun)?.call(Arguments::default()).await
+    })
+    .await
 }
 
+mod arguments;
+mod array;
+mod builtins;
+mod channel;
+mod constant;
 mod extensions;
+mod function;
+mod goroutine;
+mod map;
+mod numeric;
+mod panic;
+mod place;
+mod range;
+mod slice;
+mod structs;
+mod types;
+mod variable;
+
+pub use crate::arguments::Arguments;
+pub use crate::array::Array;
+pub use crate::channel::Channel;
+pub use crate::constant::Constant;
+pub use crate::function::Function;
+pub use crate::goroutine::MAX_DEPTH;
+pub use crate::map::Map;
+pub use crate::numeric::{Number, Numeric};
+pub use crate::panic::{Failure, Frame};
+pub use crate::place::{Place, Pointer};
+pub use crate::slice::Slice;
+pub use crate::structs::Struct;
+pub use crate::types::{InterfaceType, NamedType};
+pub use crate::variable::Variable;
@@ -0,0 +1,228 @@
+use crate::{EvalResult, RuntimeError, RuntimeResult, Type, Value};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+type Entries = BTreeMap<Key, (Value, Value)>;
+
+/// The value of a map: a reference to a table of entries, shared with every copy of the map.
+///
+/// Entries are kept in key order, which is the order Go prints them in.  Ranging over a map
+/// follows the same order, which Go leaves unspecified.
+#[derive(Clone)]
+pub struct Map {
+    key: Box<Type>,
+    value: Box<Type>,
+    /// A nil map has no table, so it may be read but not written to
+    entries: Option<Arc<Mutex<Entries>>>,
+}
+
+impl Map {
+    pub(crate) fn nil(key: Type, value: Type) -> Self {
+        Map {
+            key: Box::new(key),
+            value: Box::new(value),
+            entries: None,
+        }
+    }
+
+    pub(crate) fn new(key: Type, value: Type) -> Self {
+        Map {
+            key: Box::new(key),
+            value: Box::new(value),
+            entries: Some(Default::default()),
+        }
+    }
+
+    pub fn key(&self) -> &Type {
+        &self.key
+    }
+
+    pub fn value(&self) -> &Type {
+        &self.value
+    }
+
+    pub fn len(&self) -> usize {
+        self.with(|entries| entries.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.entries.is_none()
+    }
+
+    /// The value stored under `key`, if there is one
+    pub(crate) fn get(&self, key: &Value) -> RuntimeResult<Option<Value>> {
+        let key = Key::of(key)?;
+        Ok(self.with(|entries| entries.get(&key).map(|(_, value)| value.clone())))
+    }
+
+    /// `m[key]`, which is the zero value if there's no such entry
+    pub(crate) fn index(&self, key: &Value) -> EvalResult {
+        match self.get(key)? {
+            Some(value) => Ok(value),
+            None => self.value.zero_value(),
+        }
+    }
+
+    /// `m[key] = value`, which panics for a nil map
+    pub(crate) fn insert(&self, key: Value, value: Value) -> RuntimeResult<()> {
+        let entries = self.entries()?;
+        let hashed = Key::of(&key)?;
+        entries.lock().unwrap().insert(hashed, (key, value));
+        Ok(())
+    }
+
+    /// Run `f` with exclusive access to the value stored under `key`.
+    ///
+    /// A missing entry isn't created: `f` sees a zero value that's thrown away afterwards.
+    pub(crate) fn with_entry<R>(
+        &self,
+        key: &Value,
+        f: impl FnOnce(&mut Value) -> RuntimeResult<R>,
+    ) -> RuntimeResult<R> {
+        let hashed = Key::of(key)?;
+        if let Some(entries) = &self.entries {
+            if let Some((_, value)) = entries.lock().unwrap().get_mut(&hashed) {
+                return f(value);
+            }
+        }
+        f(&mut self.value.zero_value()?)
+    }
+
+    /// `delete(m, key)`, which does nothing if there's no such entry
+    pub(crate) fn delete(&self, key: &Value) -> RuntimeResult<()> {
+        let key = Key::of(key)?;
+        if let Some(entries) = &self.entries {
+            entries.lock().unwrap().remove(&key);
+        }
+        Ok(())
+    }
+
+    /// `clear(m)`, which removes every entry
+    pub(crate) fn clear(&self) {
+        if let Some(entries) = &self.entries {
+            entries.lock().unwrap().clear();
+        }
+    }
+
+    /// The keys of every entry, in order
+    pub(crate) fn keys(&self) -> Vec<Value> {
+        self.with(|entries| entries.values().map(|(key, _)| key.clone()).collect())
+    }
+
+    fn entries(&self) -> RuntimeResult<&Arc<Mutex<Entries>>> {
+        self.entries
+            .as_ref()
+            .ok_or_else(|| RuntimeError::panic("assignment to entry in nil map"))
+    }
+
+    /// Run `f` on the entries, where a nil map has none
+    fn with<R>(&self, f: impl FnOnce(&Entries) -> R) -> R {
+        match &self.entries {
+            Some(entries) => f(&entries.lock().unwrap()),
+            None => f(&Entries::new()),
+        }
+    }
+}
+
+/// A map key, reduced to something that may be ordered.
+///
+/// Structs and numbers other than `int` remember which type they are, since two types may have the
+/// same fields or values, and pointers are identified by the address they point to.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Nil,
+    Boolean(bool),
+    Int(i64),
+    Integer((usize, usize), i128),
+    Float((usize, usize), [i64; 2]),
+    String(String),
+    Error(String),
+    Struct(usize, Vec<Key>),
+    Array(Vec<Key>),
+    Pointer(usize, Vec<String>),
+    Channel(usize),
+}
+
+impl Key {
+    /// The key for a value, which panics if the value can't be compared.
+    ///
+    /// Only a key stored as an interface can hold such a value, since a map's key type has to be
+    /// comparable.
+    fn of(value: &Value) -> RuntimeResult<Key> {
+        Ok(match value {
+            Value::Nil => Key::Nil,
+            Value::Boolean(b) => Key::Boolean(*b),
+            Value::Int(n) => Key::Int(*n),
+            Value::Number(number) => match (number.as_integer(), number.float_key()) {
+                (Some(n), _) => Key::Integer(number.type_id(), n),
+                (_, Some(f)) => Key::Float(number.type_id(), f),
+                _ => unreachable!("a number is an integer or a float"),
+            },
+            Value::String(s) => Key::String(s.clone()),
+            Value::Error(message) => Key::Error(message.clone()),
+            Value::Struct(s) => Key::Struct(
+                s.r#type().id(),
+                s.values().map(Key::of).collect::<RuntimeResult<_>>()?,
+            ),
+            Value::Array(array) => Key::Array(
+                array
+                    .values()
+                    .iter()
+                    .map(Key::of)
+                    .collect::<RuntimeResult<_>>()?,
+            ),
+            // Every nil pointer is at address zero
+            Value::Pointer(pointer) => match pointer.place() {
+                Ok(place) => {
+                    let (address, fields) = place.address();
+                    Key::Pointer(address, fields)
+                }
+                Err(_) => Key::Pointer(0, vec![]),
+            },
+            Value::Channel(channel) => Key::Channel(channel.address()),
+            value => {
+                return Err(RuntimeError::panic(format!(
+                    "runtime error: hash of unhashable type {}",
+                    value.as_type()
+                )))
+            }
+        })
+    }
+}
+
+/// Maps are the same if they share the same table
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        let same_entries = match (&self.entries, &other.entries) {
+            (Some(left), Some(right)) => Arc::ptr_eq(left, right),
+            (None, None) => true,
+            _ => false,
+        };
+        same_entries && self.key == other.key && self.value == other.value
+    }
+}
+
+impl Debug for Map {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Map({})", self)
+    }
+}
+
+impl Display for Map {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let entries = self.with(|entries| entries.values().cloned().collect::<Vec<_>>());
+        write!(f, "map[")?;
+        for (index, (key, value)) in entries.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}:{}", key, value)?;
+        }
+        write!(f, "]")
+    }
+}
@@ -0,0 +1,764 @@
+use crate::types::NamedType;
+use crate::{EvalResult, RuntimeError, RuntimeResult, Type, Value};
+use gor_ast::binary_op::BinOp;
+use std::fmt::{Debug, Display, Formatter};
+
+/// One of Go's predeclared numeric types.
+///
+/// A plain `int` is a [Value::Int] of type [Type::Int], so a number only has the kind `Int` when
+/// it has a type declared as `type T int`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Numeric {
+    Int,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Uint,
+    Uint8,
+    Uint16,
+    Uint32,
+    Uint64,
+    Uintptr,
+    Float32,
+    Float64,
+    Complex64,
+    Complex128,
+}
+
+impl Numeric {
+    /// Every kind except `int`, which is predeclared along with the other basic types
+    pub(crate) const PREDECLARED: [Numeric; 14] = [
+        Numeric::Int8,
+        Numeric::Int16,
+        Numeric::Int32,
+        Numeric::Int64,
+        Numeric::Uint,
+        Numeric::Uint8,
+        Numeric::Uint16,
+        Numeric::Uint32,
+        Numeric::Uint64,
+        Numeric::Uintptr,
+        Numeric::Float32,
+        Numeric::Float64,
+        Numeric::Complex64,
+        Numeric::Complex128,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Numeric::Int => "int",
+            Numeric::Int8 => "int8",
+            Numeric::Int16 => "int16",
+            Numeric::Int32 => "int32",
+            Numeric::Int64 => "int64",
+            Numeric::Uint => "uint",
+            Numeric::Uint8 => "uint8",
+            Numeric::Uint16 => "uint16",
+            Numeric::Uint32 => "uint32",
+            Numeric::Uint64 => "uint64",
+            Numeric::Uintptr => "uintptr",
+            Numeric::Float32 => "float32",
+            Numeric::Float64 => "float64",
+            Numeric::Complex64 => "complex64",
+            Numeric::Complex128 => "complex128",
+        }
+    }
+
+    /// The predeclared type of this kind
+    pub fn r#type(self) -> Type {
+        match self {
+            Numeric::Int => Type::Int,
+            numeric => Type::Numeric(numeric),
+        }
+    }
+
+    pub fn is_integer(self) -> bool {
+        !matches!(
+            self,
+            Numeric::Float32 | Numeric::Float64 | Numeric::Complex64 | Numeric::Complex128
+        )
+    }
+
+    pub fn is_complex(self) -> bool {
+        matches!(self, Numeric::Complex64 | Numeric::Complex128)
+    }
+
+    /// The kind of float that each part of a complex number of this kind is
+    pub(crate) fn part(self) -> Option<Numeric> {
+        match self {
+            Numeric::Complex64 => Some(Numeric::Float32),
+            Numeric::Complex128 => Some(Numeric::Float64),
+            _ => None,
+        }
+    }
+
+    /// The kind of complex number made of two floats of this kind
+    pub(crate) fn complex(self) -> Option<Numeric> {
+        match self {
+            Numeric::Float32 => Some(Numeric::Complex64),
+            Numeric::Float64 => Some(Numeric::Complex128),
+            _ => None,
+        }
+    }
+
+    fn is_signed(self) -> bool {
+        matches!(
+            self,
+            Numeric::Int | Numeric::Int8 | Numeric::Int16 | Numeric::Int32 | Numeric::Int64
+        )
+    }
+
+    /// How many bytes a number of this kind takes
+    pub(crate) fn size(self) -> usize {
+        self.bits() as usize / 8
+    }
+
+    fn bits(self) -> u32 {
+        match self {
+            Numeric::Int8 | Numeric::Uint8 => 8,
+            Numeric::Int16 | Numeric::Uint16 => 16,
+            Numeric::Int32 | Numeric::Uint32 | Numeric::Float32 => 32,
+            Numeric::Complex128 => 128,
+            _ => 64,
+        }
+    }
+
+    /// The smallest integer of this kind
+    pub(crate) fn min(self) -> i128 {
+        if self.is_signed() {
+            -(1 << (self.bits() - 1))
+        } else {
+            0
+        }
+    }
+
+    /// The largest integer of this kind
+    pub(crate) fn max(self) -> i128 {
+        if self.is_signed() {
+            (1 << (self.bits() - 1)) - 1
+        } else {
+            (1 << self.bits()) - 1
+        }
+    }
+
+    /// Wrap an integer around into the range of this kind, as two's complement arithmetic does
+    fn wrap(self, n: i128) -> i128 {
+        let unused = 128 - self.bits();
+        if self.is_signed() {
+            (n << unused) >> unused
+        } else {
+            ((n << unused) as u128 >> unused) as i128
+        }
+    }
+
+    /// Round a float to the precision of this kind, or of each part of a complex kind
+    pub(crate) fn round(self, f: f64) -> f64 {
+        match self {
+            Numeric::Float32 | Numeric::Complex64 => f as f32 as f64,
+            _ => f,
+        }
+    }
+
+    /// Truncate a float towards zero, as Go does on amd64: a value out of the range of `int64`
+    /// gives its smallest value, except that `uint64` has its own range above it
+    fn truncate(self, f: f64) -> i128 {
+        const TWO_63: f64 = 9_223_372_036_854_775_808.0;
+        let to_i64 = |f: f64| {
+            if f.is_nan() || !(-TWO_63..TWO_63).contains(&f) {
+                i64::MIN
+            } else {
+                f as i64
+            }
+        };
+        match self {
+            Numeric::Uint | Numeric::Uint64 | Numeric::Uintptr if f >= TWO_63 => {
+                (to_i64(f - TWO_63) as u64 ^ (1 << 63)) as i128
+            }
+            _ => to_i64(f) as i128,
+        }
+    }
+}
+
+/// A value of any of Go's numeric types other than a plain `int`, which is a [Value::Int].
+///
+/// Arithmetic wraps around for integers and follows IEEE 754 for floats and each part of a complex
+/// number, with `float32` and `complex64` results rounded to single precision.
+#[derive(Clone)]
+pub struct Number {
+    kind: Numeric,
+    /// The declared type, for a number of a type like `type Celsius float64`
+    named: Option<NamedType>,
+    /// An integer in two's complement, or a float's IEEE 754 bits, which keeps values small
+    bits: u64,
+    /// The IEEE 754 bits of the imaginary part of a complex number
+    imaginary: u64,
+}
+
+/// The value of a number, where integers of every kind fit in an `i128`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Repr {
+    Integer(i128),
+    Float(f64),
+    Complex(f64, f64),
+}
+
+impl Number {
+    /// A number of `kind` holding `repr`, which has to be within the range of its kind
+    fn new(kind: Numeric, named: Option<NamedType>, repr: Repr) -> Self {
+        let (bits, imaginary) = match repr {
+            Repr::Integer(n) => (n as u64, 0),
+            Repr::Float(f) => (f.to_bits(), 0),
+            Repr::Complex(real, imaginary) => (real.to_bits(), imaginary.to_bits()),
+        };
+        Number {
+            kind,
+            named,
+            bits,
+            imaginary,
+        }
+    }
+
+    fn repr(&self) -> Repr {
+        match self.kind {
+            kind if kind.is_complex() => {
+                Repr::Complex(f64::from_bits(self.bits), f64::from_bits(self.imaginary))
+            }
+            kind if !kind.is_integer() => Repr::Float(f64::from_bits(self.bits)),
+            kind if kind.is_signed() => Repr::Integer(self.bits as i64 as i128),
+            _ => Repr::Integer(self.bits as i128),
+        }
+    }
+
+    /// An integer as a number of a numeric type, wrapping around to fit
+    pub(crate) fn integer(r#type: &Type, n: i128) -> RuntimeResult<Self> {
+        let (kind, named) = target(r#type)?;
+        let repr = if kind.is_integer() {
+            Repr::Integer(kind.wrap(n))
+        } else {
+            rounded(kind, n as f64, 0.0)
+        };
+        Ok(Number::new(kind, named, repr))
+    }
+
+    /// A float as a number of a numeric type, rounding it or truncating it to fit
+    pub(crate) fn float(r#type: &Type, f: f64) -> RuntimeResult<Self> {
+        let (kind, named) = target(r#type)?;
+        let repr = if kind.is_integer() {
+            Repr::Integer(kind.wrap(kind.truncate(f)))
+        } else {
+            rounded(kind, f, 0.0)
+        };
+        Ok(Number::new(kind, named, repr))
+    }
+
+    /// A complex number of a complex type, with each part rounded to fit
+    pub(crate) fn complex(r#type: &Type, real: f64, imaginary: f64) -> RuntimeResult<Self> {
+        let (kind, named) = target(r#type)?;
+        if !kind.is_complex() {
+            return Err(RuntimeError::TypeError {
+                expected: Type::Numeric(Numeric::Complex128),
+                found: r#type.clone(),
+            });
+        }
+        Ok(Number::new(kind, named, rounded(kind, real, imaginary)))
+    }
+
+    pub fn r#type(&self) -> Type {
+        match &self.named {
+            Some(named) => Type::Named(named.clone()),
+            None => self.kind.r#type(),
+        }
+    }
+
+    /// The declared type of the number, if it has one
+    pub(crate) fn named(&self) -> Option<NamedType> {
+        self.named.clone()
+    }
+
+    /// The number as an integer, if it is one
+    pub(crate) fn as_integer(&self) -> Option<i128> {
+        match self.repr() {
+            Repr::Integer(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// `complex(real, imaginary)`, for two floats of the same type
+    pub(crate) fn from_parts(real: Number, imaginary: Number) -> Option<Self> {
+        let kind = real.kind.complex()?;
+        match (real.repr(), imaginary.repr()) {
+            (Repr::Float(real_part), Repr::Float(imaginary_part))
+                if real.r#type() == imaginary.r#type() =>
+            {
+                Some(Number::new(
+                    kind,
+                    None,
+                    Repr::Complex(real_part, imaginary_part),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// The real and imaginary parts of a complex number, as `real(x)` and `imag(x)` give them
+    pub(crate) fn parts(self) -> Option<(Number, Number)> {
+        let kind = self.kind.part()?;
+        let (real, imaginary) = self.components();
+        Some((
+            Number::new(kind, None, Repr::Float(real)),
+            Number::new(kind, None, Repr::Float(imaginary)),
+        ))
+    }
+
+    /// The number as a value, where a plain `int` is always a [Value::Int]
+    pub(crate) fn value(self) -> Value {
+        match (self.kind, &self.named, self.repr()) {
+            (Numeric::Int, None, Repr::Integer(n)) => Value::Int(n as i64),
+            _ => Value::Number(self),
+        }
+    }
+
+    /// `T(x)`, which wraps integers around and truncates floats to fit an integer type.  Complex
+    /// numbers only convert to other complex types.
+    pub(crate) fn convert(self, r#type: &Type) -> RuntimeResult<Self> {
+        match self.repr() {
+            Repr::Integer(n) => Number::integer(r#type, n),
+            Repr::Float(f) => Number::float(r#type, f),
+            Repr::Complex(real, imaginary) => {
+                Number::complex(r#type, real, imaginary).map_err(|_| RuntimeError::TypeError {
+                    expected: r#type.clone(),
+                    found: self.r#type(),
+                })
+            }
+        }
+    }
+
+    /// The same value as a number of another numeric type, which has to be able to hold it
+    /// exactly, except that floats may be rounded
+    fn represent(self, r#type: &Type) -> RuntimeResult<Self> {
+        let (kind, _) = target(r#type)?;
+        let overflow = || RuntimeError::ConstantOverflow {
+            value: self.to_string(),
+            r#type: r#type.clone(),
+        };
+        let (real, imaginary) = match self.repr() {
+            Repr::Integer(n) if kind.is_integer() && !(kind.min()..=kind.max()).contains(&n) => {
+                return Err(overflow())
+            }
+            Repr::Integer(n) => return Number::integer(r#type, n),
+            Repr::Float(f) => (f, 0.0),
+            Repr::Complex(real, imaginary) => (real, imaginary),
+        };
+        if !kind.is_complex() && (imaginary != 0.0 || kind.is_integer() && real.fract() != 0.0) {
+            return Err(RuntimeError::ConstantTruncated {
+                value: self.to_string(),
+                r#type: r#type.clone(),
+            });
+        }
+        if kind.is_integer() {
+            let n = real as i128;
+            if !(kind.min()..=kind.max()).contains(&n) {
+                return Err(overflow());
+            }
+            return Number::integer(r#type, n);
+        }
+        let number = match kind.is_complex() {
+            true => Number::complex(r#type, real, imaginary)?,
+            false => Number::float(r#type, real)?,
+        };
+        let (rounded, rounded_imaginary) = number.components();
+        if rounded.is_infinite() && real.is_finite()
+            || rounded_imaginary.is_infinite() && imaginary.is_finite()
+        {
+            return Err(overflow());
+        }
+        Ok(number)
+    }
+
+    /// The real and imaginary parts of a float or complex number, where a float's imaginary part
+    /// is zero
+    fn components(&self) -> (f64, f64) {
+        match self.repr() {
+            Repr::Integer(n) => (n as f64, 0.0),
+            Repr::Float(f) => (f, 0.0),
+            Repr::Complex(real, imaginary) => (real, imaginary),
+        }
+    }
+
+    /// `-x`, which wraps around for the smallest integer of a signed kind
+    pub(crate) fn negate(self) -> Self {
+        let repr = match self.repr() {
+            Repr::Integer(n) => Repr::Integer(self.kind.wrap(-n)),
+            Repr::Float(f) => Repr::Float(-f),
+            Repr::Complex(real, imaginary) => Repr::Complex(-real, -imaginary),
+        };
+        Number::new(self.kind, self.named, repr)
+    }
+
+    /// Apply `op` to two numbers of the same type, where complex numbers can only be compared for
+    /// equality
+    pub(crate) fn bin_op(self, op: BinOp, right: Number) -> EvalResult {
+        let repr = match (self.repr(), right.repr()) {
+            (Repr::Integer(left), Repr::Integer(right)) => {
+                if let Some(result) = compare(op, &left, &right) {
+                    return Ok(Value::Boolean(result));
+                }
+                if right == 0 && matches!(op, BinOp::Div | BinOp::Modulo) {
+                    return Err(RuntimeError::panic("runtime error: integer divide by zero"));
+                }
+                Repr::Integer(self.kind.wrap(match op {
+                    BinOp::Add => left + right,
+                    BinOp::Sub => left - right,
+                    BinOp::Mul => left.wrapping_mul(right),
+                    BinOp::Div => left / right,
+                    BinOp::Modulo => left % right,
+                    BinOp::BitOr => left | right,
+                    BinOp::BitXor => left ^ right,
+                    BinOp::BitAnd => left & right,
+                    BinOp::BitClear => left & !right,
+                    _ => return Err(self.unsupported(op)),
+                }))
+            }
+            (Repr::Float(left), Repr::Float(right)) => {
+                if let Some(result) = compare(op, &left, &right) {
+                    return Ok(Value::Boolean(result));
+                }
+                Repr::Float(self.kind.round(match op {
+                    BinOp::Add => left + right,
+                    BinOp::Sub => left - right,
+                    BinOp::Mul => left * right,
+                    BinOp::Div => left / right,
+                    _ => return Err(self.unsupported(op)),
+                }))
+            }
+            (Repr::Complex(a, b), Repr::Complex(c, d)) => {
+                let round = |f: f64| self.kind.round(f);
+                let (real, imaginary) = match op {
+                    BinOp::Eq => return Ok(Value::Boolean(a == c && b == d)),
+                    BinOp::Neq => return Ok(Value::Boolean(a != c || b != d)),
+                    BinOp::Add => (a + c, b + d),
+                    BinOp::Sub => (a - c, b - d),
+                    BinOp::Mul => (round(a * c) - round(b * d), round(a * d) + round(b * c)),
+                    BinOp::Div => divide((a, b), (c, d)),
+                    _ => return Err(self.unsupported(op)),
+                };
+                Repr::Complex(round(real), round(imaginary))
+            }
+            _ => {
+                return Err(RuntimeError::TypeMismatch {
+                    left: self.r#type(),
+                    op,
+                    right: right.r#type(),
+                })
+            }
+        };
+        Ok(Number::new(self.kind, self.named, repr).value())
+    }
+
+    /// `x << n` or `x >> n`, where the count may be any type of integer but mustn't be negative
+    pub(crate) fn shift(self, op: BinOp, count: Number) -> EvalResult {
+        let Repr::Integer(n) = self.repr() else {
+            return Err(self.unsupported(op));
+        };
+        let Repr::Integer(count) = count.repr() else {
+            return Err(RuntimeError::TypeError {
+                expected: Type::Int,
+                found: count.r#type(),
+            });
+        };
+        if count < 0 {
+            return Err(RuntimeError::panic("runtime error: negative shift amount"));
+        }
+        // Shifting out every bit leaves nothing, or just the sign
+        let shifted = match op {
+            BinOp::Shl if count >= 64 => 0,
+            BinOp::Shl => n << count,
+            _ if count >= 64 => n.signum().min(0),
+            _ => n >> count,
+        };
+        let repr = Repr::Integer(self.kind.wrap(shifted));
+        Ok(Number::new(self.kind, self.named, repr).value())
+    }
+
+    fn unsupported(&self, op: BinOp) -> RuntimeError {
+        RuntimeError::TypeOpMismatch {
+            op,
+            r#type: self.r#type(),
+        }
+    }
+
+    /// Identifies the type of the number, so that numbers of different types are never the same
+    /// map key
+    pub(crate) fn type_id(&self) -> (usize, usize) {
+        (
+            self.named.as_ref().map_or(0, NamedType::id),
+            self.kind as usize,
+        )
+    }
+
+    /// The value of a float or both parts of a complex number, ordered so that every value has a
+    /// place but `-0` is the same as `0`
+    pub(crate) fn float_key(&self) -> Option<[i64; 2]> {
+        if self.kind.is_integer() {
+            return None;
+        }
+        let key = |f: f64| {
+            let bits = (f + 0.0).to_bits() as i64;
+            bits ^ (((bits >> 63) as u64) >> 1) as i64
+        };
+        let (real, imaginary) = self.components();
+        Some([key(real), key(imaginary)])
+    }
+
+    /// The number as Go's `print` shows it, with floats in exponent form
+    pub(crate) fn printed(&self) -> String {
+        match self.repr() {
+            Repr::Integer(n) => n.to_string(),
+            Repr::Float(f) => print_float(f),
+            Repr::Complex(real, imaginary) => {
+                format!("({}{}i)", print_float(real), print_float(imaginary))
+            }
+        }
+    }
+}
+
+/// A float as a value of a float or complex kind, rounded to its precision
+fn rounded(kind: Numeric, real: f64, imaginary: f64) -> Repr {
+    if kind.is_complex() {
+        Repr::Complex(kind.round(real), kind.round(imaginary))
+    } else {
+        Repr::Float(kind.round(real))
+    }
+}
+
+/// `n / m` for complex numbers, as Go's runtime divides them: by Smith's algorithm, with the
+/// infinities and zeros of C99 where that gives `NaN`
+fn divide((a, b): (f64, f64), (c, d): (f64, f64)) -> (f64, f64) {
+    let (mut e, mut f) = if c.abs() >= d.abs() {
+        let ratio = d / c;
+        let denominator = c + ratio * d;
+        ((a + b * ratio) / denominator, (b - a * ratio) / denominator)
+    } else {
+        let ratio = c / d;
+        let denominator = d + ratio * c;
+        ((a * ratio + b) / denominator, (b * ratio - a) / denominator)
+    };
+    if e.is_nan() && f.is_nan() {
+        let infinite = |x: f64| f64::from(u8::from(x.is_infinite())).copysign(x);
+        if c == 0.0 && d == 0.0 && (!a.is_nan() || !b.is_nan()) {
+            e = f64::INFINITY.copysign(c) * a;
+            f = f64::INFINITY.copysign(c) * b;
+        } else if (a.is_infinite() || b.is_infinite()) && c.is_finite() && d.is_finite() {
+            let (a, b) = (infinite(a), infinite(b));
+            e = f64::INFINITY * (a * c + b * d);
+            f = f64::INFINITY * (b * c - a * d);
+        } else if (c.is_infinite() || d.is_infinite()) && a.is_finite() && b.is_finite() {
+            let (c, d) = (infinite(c), infinite(d));
+            e = 0.0 * (a * c + b * d);
+            f = 0.0 * (b * c - a * d);
+        }
+    }
+    (e, f)
+}
+
+/// The kind and declared type of numbers of a numeric type
+fn target(r#type: &Type) -> RuntimeResult<(Numeric, Option<NamedType>)> {
+    let named = match r#type {
+        Type::Named(named) => Some(named.clone()),
+        _ => None,
+    };
+    match r#type.numeric() {
+        Some(kind) => Ok((kind, named)),
+        None => Err(RuntimeError::TypeError {
+            expected: Type::Int,
+            found: r#type.clone(),
+        }),
+    }
+}
+
+fn compare<T: PartialOrd>(op: BinOp, left: &T, right: &T) -> Option<bool> {
+    Some(match op {
+        BinOp::Eq => left == right,
+        BinOp::Neq => left != right,
+        BinOp::Lt => left < right,
+        BinOp::Leq => left <= right,
+        BinOp::Gt => left > right,
+        BinOp::Geq => left >= right,
+        _ => return None,
+    })
+}
+
+/// Store a value as a numeric type: an `int`, `float64` or `complex128`, which may be what an
+/// untyped constant became, converts to any other numeric type that can hold its value.
+///
+/// Anything else is left alone, to be checked against the type by the caller.
+pub(crate) fn implicit(r#type: &Type, value: Value) -> EvalResult {
+    match untyped(&value) {
+        Some(number) if r#type.numeric().is_some() && number.r#type() != *r#type => {
+            Ok(number.represent(r#type)?.value())
+        }
+        _ => Ok(value),
+    }
+}
+
+/// Assign a value in place of `current`, converting it as [implicit] does when it can.
+///
+/// Variables don't remember their types, so it takes the type of the number it replaces.
+pub(crate) fn assigned(current: &Value, value: Value) -> Value {
+    match (current.as_number(), untyped(&value)) {
+        (Some(current), Some(number)) if current.r#type() != number.r#type() => number
+            .represent(&current.r#type())
+            .map(Number::value)
+            .unwrap_or(value),
+        _ => value,
+    }
+}
+
+/// A number of one of the types that untyped constants default to
+fn untyped(value: &Value) -> Option<Number> {
+    match value {
+        Value::Int(_) => value.as_number(),
+        Value::Number(number)
+            if matches!(number.kind, Numeric::Float64 | Numeric::Complex128)
+                && number.named.is_none() =>
+        {
+            Some(number.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Numbers are equal if they have the same type and value, so `NaN` isn't equal to itself
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.named == other.named && self.repr() == other.repr()
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.repr() {
+            Repr::Integer(n) => Display::fmt(&n, f),
+            Repr::Float(float) => write!(f, "{}", format_float(float, self.kind)),
+            Repr::Complex(real, imaginary) => {
+                let imaginary = format_float(imaginary, self.kind);
+                let sign = if imaginary.starts_with(['+', '-']) {
+                    ""
+                } else {
+                    "+"
+                };
+                write!(
+                    f,
+                    "({}{}{}i)",
+                    format_float(real, self.kind),
+                    sign,
+                    imaginary
+                )
+            }
+        }
+    }
+}
+
+impl Debug for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.r#type(), self)
+    }
+}
+
+/// A float as Go's `%v` formats it: the fewest digits that read back as the same value, with an
+/// exponent if it's less than -4 or at least 6
+pub(crate) fn format_float(f: f64, kind: Numeric) -> String {
+    if f.is_nan() {
+        return "NaN".to_string();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "+Inf" } else { "-Inf" }.to_string();
+    }
+    let scientific = match kind {
+        Numeric::Float32 | Numeric::Complex64 => format!("{:e}", f as f32),
+        _ => format!("{:e}", f),
+    };
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+    let formatted = if !(-4..6).contains(&exponent) {
+        let (first, rest) = digits.split_at(1);
+        let point = if rest.is_empty() { "" } else { "." };
+        let exponent_sign = if exponent < 0 { '-' } else { '+' };
+        format!(
+            "{}{}{}e{}{:02}",
+            first,
+            point,
+            rest,
+            exponent_sign,
+            exponent.abs()
+        )
+    } else if exponent < 0 {
+        format!("0.{}{}", "0".repeat(-exponent as usize - 1), digits)
+    } else {
+        let point = exponent as usize + 1;
+        if digits.len() <= point {
+            format!("{}{}", digits, "0".repeat(point - digits.len()))
+        } else {
+            format!("{}.{}", &digits[..point], &digits[point..])
+        }
+    };
+    format!("{}{}", sign, formatted)
+}
+
+/// A float as Go's `print` shows it, always with seven significant digits and an exponent
+fn print_float(mut f: f64) -> String {
+    if f.is_nan() {
+        return "NaN".to_string();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "+Inf" } else { "-Inf" }.to_string();
+    }
+    const DIGITS: i32 = 7;
+    let mut sign = '+';
+    let mut exponent: i32 = 0;
+    if f == 0.0 {
+        if f.is_sign_negative() {
+            sign = '-';
+        }
+    } else {
+        if f < 0.0 {
+            f = -f;
+            sign = '-';
+        }
+        while f >= 10.0 {
+            exponent += 1;
+            f /= 10.0;
+        }
+        while f < 1.0 {
+            exponent -= 1;
+            f *= 10.0;
+        }
+        f += 5.0 / 10f64.powi(DIGITS);
+        if f >= 10.0 {
+            exponent += 1;
+            f /= 10.0;
+        }
+    }
+    let mut digits = String::new();
+    for _ in 0..DIGITS {
+        let digit = f as u8;
+        digits.push((b'0' + digit) as char);
+        f -= digit as f64;
+        f *= 10.0;
+    }
+    let exponent_sign = if exponent < 0 { '-' } else { '+' };
+    format!(
+        "{}{}.{}e{}{:03}",
+        sign,
+        &digits[..1],
+        &digits[1..],
+        exponent_sign,
+        exponent.abs()
+    )
+}
@@ -0,0 +1,96 @@
+use crate::{Arguments, Function, RuntimeError, Value};
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+/// Why a program ended before `main` returned: an error, or a panic that no deferred call
+/// recovered from.
+#[derive(Error, Debug)]
+#[error("{error}")]
+pub struct Failure {
+    pub error: RuntimeError,
+    /// The goroutine that failed, where `main` is goroutine 1
+    pub goroutine: u64,
+    /// The calls that a panic unwound out of, innermost first
+    pub trace: Vec<Frame>,
+    /// What Go prints after `panic: `, if the failure was a panic
+    pub panic: Option<String>,
+}
+
+impl From<RuntimeError> for Failure {
+    fn from(error: RuntimeError) -> Self {
+        Failure {
+            error,
+            goroutine: 1,
+            trace: vec![],
+            panic: None,
+        }
+    }
+}
+
+/// A call that a panic unwound out of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// The function's name, qualified by its package and any receiver, as in `main.(*T).Close`
+    pub function: String,
+    /// Whether the call had any arguments, which Go elides as `(...)`
+    pub arguments: bool,
+    /// The line of the statement that was running when the panic left the call
+    pub line: Option<usize>,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let arguments = if self.arguments { "..." } else { "" };
+        write!(f, "{}({})", self.function, arguments)
+    }
+}
+
+/// The value of a panic as Go prints it: an `error` by its `Error` method, then anything with a
+/// `String` method by calling it, and any other value that isn't a string, number or boolean
+/// along with its type.  Floats are in exponent form, and a number of a declared type is shown
+/// as a conversion to it, like `main.Celsius(+1.000000e+002)`.
+pub(crate) async fn describe(value: &Value) -> String {
+    match value {
+        Value::String(s) | Value::Error(s) => return s.clone(),
+        Value::Int(_) | Value::Boolean(_) => return value.to_string(),
+        Value::Number(number) if number.named().is_none() => return number.printed(),
+        _ => {}
+    }
+    for name in ["Error", "String"] {
+        if let Some(method) = method(value, name) {
+            if let Ok(Value::String(s)) = method.call(Arguments::default()).await {
+                return s;
+            }
+        }
+    }
+    match value {
+        Value::Number(number) => format!("{}({})", value.as_type(), number.printed()),
+        _ => format!("({}) {}", value.as_type(), value),
+    }
+}
+
+/// The method with this name in the method set of `value`, bound to it
+fn method(value: &Value, name: &str) -> Option<Function> {
+    let (target, pointer) = match value {
+        Value::Struct(_) | Value::Number(_) => (value.clone(), false),
+        Value::Pointer(pointer) => (pointer.place().ok()?.get().ok()?, true),
+        _ => return None,
+    };
+    let named = match &target {
+        Value::Struct(s) => s.r#type(),
+        Value::Number(number) => number.named()?,
+        _ => return None,
+    };
+    let receiver = match named.method(name.into())?.receiver_base() {
+        Some((_, true)) if pointer => value.clone(),
+        // A pointer method isn't in the method set of a value
+        Some((_, true)) => return None,
+        _ => target,
+    };
+    Some(Function::method(
+        named.package(),
+        named.name(),
+        name.into(),
+        receiver,
+    ))
+}
@@ -0,0 +1,250 @@
+use crate::map::Map;
+use crate::numeric;
+use crate::slice::Slice;
+use crate::variable::Variable;
+use crate::{EvalResult, RuntimeError, RuntimeResult, Type, Value, NIL_DEREFERENCE};
+use gor_ast::name::Name;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Somewhere a value may be stored: a variable, or a field nested inside one, or an entry in a map.
+///
+/// A pointer's value is the place it points to.
+#[derive(Clone)]
+pub struct Place {
+    root: Root,
+    fields: Vec<Name>,
+}
+
+#[derive(Clone)]
+enum Root {
+    Variable(Variable),
+    /// The entry for `key`, which is created when it's first stored to
+    Entry {
+        map: Map,
+        key: Box<Value>,
+    },
+}
+
+impl Place {
+    /// The entry in `map` for `key`, whose value may be read or replaced but not addressed
+    pub(crate) fn entry(map: Map, key: Value) -> Self {
+        Place {
+            root: Root::Entry {
+                map,
+                key: Box::new(key),
+            },
+            fields: vec![],
+        }
+    }
+
+    /// Whether a pointer may point here.
+    ///
+    /// Map entries move around as the map grows, so Go doesn't allow their address to be taken.
+    pub(crate) fn is_addressable(&self) -> bool {
+        matches!(self.root, Root::Variable(_))
+    }
+
+    /// The address of the variable, and the fields within it, which identify an addressable place
+    pub(crate) fn address(&self) -> (usize, Vec<String>) {
+        let address = match &self.root {
+            Root::Variable(variable) => variable.address(),
+            Root::Entry { .. } => 0,
+        };
+        (address, self.fields.iter().map(|f| f.to_string()).collect())
+    }
+
+    /// The field called `name` in the struct stored here
+    pub(crate) fn field(mut self, name: Name) -> Self {
+        self.fields.push(name);
+        self
+    }
+
+    pub(crate) fn get(&self) -> EvalResult {
+        match &self.root {
+            Root::Variable(variable) => variable.with_mut(|value| Ok(self.select(value)?.clone())),
+            Root::Entry { map, key } => self.select(&mut map.index(key)?).cloned(),
+        }
+    }
+
+    /// Store a value here.
+    ///
+    /// Assigning `nil` to a slice or a map keeps its type, just as it would in Go.
+    pub(crate) fn set(&self, value: Value) -> RuntimeResult<()> {
+        if let Root::Entry { map, key } = &self.root {
+            if self.fields.is_empty() {
+                return map.insert((**key).clone(), typed(value, &self.get()?));
+            }
+        }
+        self.with(|stored| {
+            *stored = typed(value, stored);
+            Ok(())
+        })
+    }
+
+    /// Run `f` with exclusive access to the value stored here
+    pub(crate) fn with<R>(
+        &self,
+        f: impl FnOnce(&mut Value) -> RuntimeResult<R>,
+    ) -> RuntimeResult<R> {
+        match &self.root {
+            Root::Variable(variable) => variable.with_mut(|value| f(self.select(value)?)),
+            Root::Entry { map, key } => map.with_entry(key, |value| f(self.select(value)?)),
+        }
+    }
+
+    fn select<'v>(&self, mut value: &'v mut Value) -> RuntimeResult<&'v mut Value> {
+        for field in &self.fields {
+            value = match value {
+                Value::Struct(s) => s.field_mut(*field)?,
+                _ => {
+                    return Err(RuntimeError::UnknownField {
+                        r#type: value.as_type(),
+                        field: *field,
+                    })
+                }
+            };
+        }
+        Ok(value)
+    }
+}
+
+/// The value to store over `stored`, which gives `nil` the type of a slice, map or pointer, and an
+/// `int` or `float64` the type of the number it replaces if it can hold it.
+///
+/// A pointer stored over a value of another type, or over a pointer held by an interface, is being
+/// stored in an interface, so it's held by one too.
+fn typed(value: Value, stored: &Value) -> Value {
+    match (value, stored) {
+        (Value::Nil, Value::Slice(slice)) => Value::Slice(Slice::nil(slice.element().clone())),
+        (Value::Nil, Value::Map(map)) => {
+            Value::Map(Map::nil(map.key().clone(), map.value().clone()))
+        }
+        (Value::Nil, Value::Pointer(pointer)) if !pointer.is_boxed() => {
+            Value::Pointer(Pointer::nil(pointer.pointee().clone()))
+        }
+        (Value::Pointer(pointer), Value::Pointer(stored)) => {
+            Value::Pointer(pointer.boxed(stored.is_boxed()))
+        }
+        (Value::Pointer(pointer), _) => Value::Pointer(pointer.boxed(true)),
+        (value, stored) => numeric::assigned(stored, value),
+    }
+}
+
+impl From<Variable> for Place {
+    fn from(variable: Variable) -> Self {
+        Place {
+            root: Root::Variable(variable),
+            fields: vec![],
+        }
+    }
+}
+
+/// Places are the same if they're the same field of the same variable, or the same map entry
+impl PartialEq for Place {
+    fn eq(&self, other: &Self) -> bool {
+        let same_root = match (&self.root, &other.root) {
+            (Root::Variable(left), Root::Variable(right)) => left.ptr_eq(right),
+            (
+                Root::Entry { map, key },
+                Root::Entry {
+                    map: other,
+                    key: other_key,
+                },
+            ) => map == other && key == other_key,
+            _ => false,
+        };
+        same_root && self.fields == other.fields
+    }
+}
+
+/// Values may refer to themselves through pointers, so only the address is shown
+impl Debug for Place {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Place({})", self)
+    }
+}
+
+impl Display for Place {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.root {
+            Root::Variable(variable) => write!(f, "{:p}", variable)?,
+            Root::Entry { key, .. } => write!(f, "map[{}]", key)?,
+        }
+        for field in &self.fields {
+            write!(f, ".{}", field)?;
+        }
+        Ok(())
+    }
+}
+
+/// The address of a place, along with the type of the value stored there.
+///
+/// A nil pointer has no place, but keeps its type, so that it can still have methods called on it
+/// and be asserted out of an interface.
+#[derive(Debug, Clone)]
+pub struct Pointer {
+    place: Option<Place>,
+    pointee: Box<Type>,
+    /// Whether the pointer is held by an interface, which isn't nil even if the pointer is
+    boxed: bool,
+}
+
+impl Pointer {
+    pub(crate) fn new(place: Place, pointee: Type) -> Self {
+        Pointer {
+            place: Some(place),
+            pointee: Box::new(pointee),
+            boxed: false,
+        }
+    }
+
+    /// The zero value of `*pointee`
+    pub(crate) fn nil(pointee: Type) -> Self {
+        Pointer {
+            place: None,
+            pointee: Box::new(pointee),
+            boxed: false,
+        }
+    }
+
+    /// The place pointed to, which panics if the pointer is nil
+    pub(crate) fn place(&self) -> RuntimeResult<&Place> {
+        self.place
+            .as_ref()
+            .ok_or_else(|| RuntimeError::panic(NIL_DEREFERENCE))
+    }
+
+    pub fn pointee(&self) -> &Type {
+        &self.pointee
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.place.is_none()
+    }
+
+    /// Whether the pointer is held by an interface
+    pub(crate) fn is_boxed(&self) -> bool {
+        self.boxed
+    }
+
+    /// The same pointer held by an interface, or not
+    pub(crate) fn boxed(self, boxed: bool) -> Self {
+        Pointer { boxed, ..self }
+    }
+}
+
+/// Pointers are equal if they point to the same place, whether or not they're held by interfaces
+impl PartialEq for Pointer {
+    fn eq(&self, other: &Self) -> bool {
+        self.place == other.place && self.pointee == other.pointee
+    }
+}
+
+impl Display for Pointer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.place {
+            Some(place) => Display::fmt(place, f),
+            None => write!(f, "0x0"),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::channel::Channel;
+use crate::map::Map;
+use crate::slice::Slice;
+use crate::{Number, RuntimeError, RuntimeResult, Type, Value};
+use std::vec::IntoIter;
+
+/// The keys and values visited by a `for range` loop, produced one iteration at a time.
+///
+/// The range expression is only evaluated once, but some of what it refers to may change while
+/// the loop runs: a slice's elements are read as they're reached, and map entries that are
+/// deleted before they're reached are skipped.
+#[derive(Debug)]
+pub(crate) enum Range {
+    /// `range n`, counting from zero in numbers of the same type as `n`
+    Count {
+        next: i128,
+        end: Number,
+    },
+    Slice {
+        slice: Slice,
+        next: usize,
+    },
+    /// The elements of an array, which was copied when the range expression was evaluated
+    Array(std::iter::Enumerate<IntoIter<Value>>),
+    /// The byte offset and value of each rune in a string
+    String(IntoIter<(usize, char)>),
+    Map {
+        map: Map,
+        keys: IntoIter<Value>,
+    },
+    /// Each value received from a channel, until it's closed
+    Channel(Channel),
+}
+
+impl Range {
+    pub(crate) fn new(value: Value) -> RuntimeResult<Self> {
+        Ok(match value {
+            Value::Int(_) | Value::Number(_) => match value.as_number() {
+                Some(end) if end.as_integer().is_some() => Range::Count { next: 0, end },
+                _ => return Err(not_rangeable(&value)),
+            },
+            Value::Slice(slice) => Range::Slice { slice, next: 0 },
+            Value::Array(array) => Range::Array(array.values().into_iter().enumerate()),
+            // Ranging over a pointer to an array doesn't copy it
+            Value::Pointer(pointer) => match pointer.place()?.get()? {
+                Value::Array(array) => Range::Slice {
+                    slice: array.slice(None, None, None)?,
+                    next: 0,
+                },
+                value => return Err(not_rangeable(&value)),
+            },
+            Value::String(s) => Range::String(s.char_indices().collect::<Vec<_>>().into_iter()),
+            Value::Map(map) => Range::Map {
+                keys: map.keys().into_iter(),
+                map,
+            },
+            Value::Channel(channel) => Range::Channel(channel),
+            Value::Nil => Range::Count {
+                next: 0,
+                end: Number::integer(&Type::Int, 0)?,
+            },
+            value => return Err(not_rangeable(&value)),
+        })
+    }
+
+    /// Whether each iteration has a value as well as a key
+    pub(crate) fn has_values(&self) -> bool {
+        !matches!(self, Range::Count { .. } | Range::Channel(_))
+    }
+
+    /// The key and value for the next iteration, or `None` once the loop is over.
+    ///
+    /// A channel waits for each value to be sent.
+    pub(crate) async fn next(&mut self) -> RuntimeResult<Option<(Value, Value)>> {
+        Ok(match self {
+            Range::Count { next, end } => {
+                if *next >= end.as_integer().unwrap_or_default() {
+                    return Ok(None);
+                }
+                *next += 1;
+                let key = Number::integer(&end.r#type(), *next - 1)?.value();
+                Some((key, Value::Void))
+            }
+            Range::Slice { slice, next } => {
+                if *next >= slice.len() {
+                    return Ok(None);
+                }
+                *next += 1;
+                let index = *next as i64 - 1;
+                Some((Value::Int(index), slice.get(index)?))
+            }
+            Range::Array(values) => values
+                .next()
+                .map(|(index, value)| (Value::Int(index as i64), value)),
+            Range::String(runes) => runes
+                .next()
+                .map(|(index, rune)| (Value::Int(index as i64), Value::Int(rune as i64))),
+            Range::Map { map, keys } => {
+                for key in keys.by_ref() {
+                    if let Some(value) = map.get(&key)? {
+                        return Ok(Some((key, value)));
+                    }
+                }
+                None
+            }
+            Range::Channel(channel) => match channel.receive().await? {
+                (value, true) => Some((value, Value::Void)),
+                (_, false) => None,
+            },
+        })
+    }
+}
+
+fn not_rangeable(value: &Value) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        function: "range",
+        r#type: value.as_type(),
+    }
+}
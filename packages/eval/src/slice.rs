@@ -0,0 +1,351 @@
+use crate::place::Place;
+use crate::variable::Variable;
+use crate::{EvalResult, Numeric, RuntimeError, RuntimeResult, Type, Value};
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+/// A window onto a backing array, which is shared with every slice taken from it.
+///
+/// Each element of the array is stored in its own variable, so that it may be assigned to or have
+/// its address taken.
+#[derive(Clone)]
+pub struct Slice {
+    element: Type,
+    /// A nil slice has no backing array at all
+    array: Option<Arc<[Variable]>>,
+    offset: usize,
+    len: usize,
+    cap: usize,
+}
+
+impl Slice {
+    pub(crate) fn nil(element: Type) -> Self {
+        Slice {
+            element,
+            array: None,
+            offset: 0,
+            len: 0,
+            cap: 0,
+        }
+    }
+
+    /// A slice of a new array holding exactly these values
+    pub(crate) fn new(element: Type, values: Vec<Value>) -> Self {
+        let len = values.len();
+        Slice {
+            element,
+            array: Some(values.into_iter().map(Variable::new).collect()),
+            offset: 0,
+            len,
+            cap: len,
+        }
+    }
+
+    /// A slice of the whole of an existing array, sharing its elements
+    pub(crate) fn of(element: Type, array: Arc<[Variable]>) -> Self {
+        let len = array.len();
+        Slice {
+            element,
+            array: Some(array),
+            offset: 0,
+            len,
+            cap: len,
+        }
+    }
+
+    /// `make([]T, len, cap)`, with every element of the new array set to its zero value
+    pub(crate) fn make(element: Type, len: i64, cap: i64) -> RuntimeResult<Self> {
+        if len < 0 {
+            return Err(RuntimeError::panic(
+                "runtime error: makeslice: len out of range",
+            ));
+        }
+        if cap < len {
+            return Err(RuntimeError::panic(
+                "runtime error: makeslice: cap out of range",
+            ));
+        }
+        let zero = element.zero_value()?;
+        let mut slice = Slice::new(element, vec![zero; cap as usize]);
+        slice.len = len as usize;
+        Ok(slice)
+    }
+
+    pub fn element(&self) -> &Type {
+        &self.element
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.array.is_none()
+    }
+
+    /// The variables holding the elements of the slice, followed by any spare capacity
+    fn variables(&self) -> &[Variable] {
+        match &self.array {
+            Some(array) => &array[self.offset..self.offset + self.cap],
+            None => &[],
+        }
+    }
+
+    /// A copy of each element of the slice
+    pub(crate) fn values(&self) -> Vec<Value> {
+        self.variables()[..self.len]
+            .iter()
+            .map(Variable::get)
+            .collect()
+    }
+
+    /// The element at `index`, which panics if it's out of range just as Go does
+    pub(crate) fn place(&self, index: i64) -> RuntimeResult<Place> {
+        let index = check_index(index, self.len)?;
+        Ok(self.variables()[index].clone().into())
+    }
+
+    pub(crate) fn get(&self, index: i64) -> EvalResult {
+        self.place(index)?.get()
+    }
+
+    /// `s[low:high]` or `s[low:high:max]`, sharing this slice's backing array
+    pub(crate) fn slice(
+        &self,
+        low: Option<i64>,
+        high: Option<i64>,
+        max: Option<i64>,
+    ) -> RuntimeResult<Self> {
+        let (low, high, max) = check_bounds(low, high, max, self.len, self.cap, "capacity")?;
+        Ok(Slice {
+            element: self.element.clone(),
+            array: self.array.clone(),
+            offset: self.offset + low,
+            len: high - low,
+            cap: max - low,
+        })
+    }
+
+    /// Add the values to the end of the slice.
+    ///
+    /// If there's enough capacity then the values are stored in the existing backing array, where
+    /// any other slices sharing it will see them.  Otherwise the elements are copied into a new
+    /// array, which grows in the same way as Go's.
+    pub(crate) fn append(&self, values: Vec<Value>) -> RuntimeResult<Self> {
+        let len = self.len + values.len();
+        if len <= self.cap {
+            for (variable, value) in self.variables()[self.len..len].iter().zip(values) {
+                variable.set(value);
+            }
+            return Ok(Slice {
+                len,
+                ..self.clone()
+            });
+        }
+        let cap = grow(self.cap, len, size_of(&self.element));
+        let mut contents = self.values();
+        contents.extend(values);
+        contents.resize(cap, self.element.zero_value()?);
+        Ok(Slice {
+            len,
+            ..Slice::new(self.element.clone(), contents)
+        })
+    }
+
+    /// Set every element of the slice to its zero value
+    pub(crate) fn clear(&self) -> RuntimeResult<()> {
+        let zero = self.element.zero_value()?;
+        for variable in &self.variables()[..self.len] {
+            variable.set(zero.clone());
+        }
+        Ok(())
+    }
+
+    /// Copy as many values as will fit into the start of this slice, returning how many there were
+    pub(crate) fn copy_from(&self, values: Vec<Value>) -> usize {
+        let count = self.len.min(values.len());
+        for (variable, value) in self.variables().iter().zip(values).take(count) {
+            variable.set(value);
+        }
+        count
+    }
+}
+
+/// Check an index against the length of the thing being indexed
+pub(crate) fn check_index(index: i64, len: usize) -> RuntimeResult<usize> {
+    if index < 0 {
+        Err(RuntimeError::panic(format!(
+            "runtime error: index out of range [{}]",
+            index
+        )))
+    } else if index as usize >= len {
+        Err(RuntimeError::panic(format!(
+            "runtime error: index out of range [{}] with length {}",
+            index, len
+        )))
+    } else {
+        Ok(index as usize)
+    }
+}
+
+/// Check the bounds of a slice expression in the same order as Go, so that a panic describes the
+/// same bound.  A missing `high` defaults to the length, and a missing `max` to the capacity.
+///
+/// Strings are sliced up to their length rather than their capacity, which `limit` names.
+pub(crate) fn check_bounds(
+    low: Option<i64>,
+    high: Option<i64>,
+    max: Option<i64>,
+    len: usize,
+    cap: usize,
+    limit: &str,
+) -> RuntimeResult<(usize, usize, usize)> {
+    let out_of_range = |bounds: String| {
+        RuntimeError::panic(format!(
+            "runtime error: slice bounds out of range {}",
+            bounds
+        ))
+    };
+    let low = low.unwrap_or(0);
+    let high = high.unwrap_or(len as i64);
+    match max {
+        Some(max) => {
+            if max < 0 || max as usize > cap {
+                return Err(out_of_range(format!("[::{}] with {} {}", max, limit, cap)));
+            }
+            if high < 0 || high > max {
+                return Err(out_of_range(format!("[:{}:{}]", high, max)));
+            }
+            if low < 0 || low > high {
+                return Err(out_of_range(format!("[{}:{}:]", low, high)));
+            }
+            Ok((low as usize, high as usize, max as usize))
+        }
+        None => {
+            if high < 0 || high as usize > cap {
+                return Err(out_of_range(format!("[:{}] with {} {}", high, limit, cap)));
+            }
+            if low < 0 || low > high {
+                return Err(out_of_range(format!("[{}:{}]", low, high)));
+            }
+            Ok((low as usize, high as usize, cap))
+        }
+    }
+}
+
+/// The sizes of the blocks Go's allocator hands out for small objects
+const SIZE_CLASSES: [usize; 67] = [
+    8, 16, 24, 32, 48, 64, 80, 96, 112, 128, 144, 160, 176, 192, 208, 224, 240, 256, 288, 320, 352,
+    384, 416, 448, 480, 512, 576, 640, 704, 768, 896, 1024, 1152, 1280, 1408, 1536, 1792, 2048,
+    2304, 2688, 3072, 3200, 3456, 4096, 4864, 5376, 6144, 6528, 6784, 6912, 8192, 9472, 9728,
+    10240, 10880, 12288, 13568, 14336, 16384, 18432, 19072, 20480, 21760, 24576, 27264, 28672,
+    32768,
+];
+
+const PAGE_SIZE: usize = 8192;
+
+/// The capacity of the array `append` allocates when a slice runs out of room, following Go's
+/// `growslice`: double small slices, grow large ones by about a quarter, then round up to fill
+/// the block the allocator would return.
+fn grow(old_cap: usize, needed: usize, element_size: usize) -> usize {
+    let double = old_cap * 2;
+    let cap = if needed > double {
+        needed
+    } else if old_cap < 256 {
+        double
+    } else {
+        let mut cap = old_cap;
+        while cap < needed {
+            cap += (cap + 3 * 256) / 4;
+        }
+        cap
+    };
+    if element_size == 0 {
+        return needed;
+    }
+    let bytes = cap * element_size;
+    let rounded = match SIZE_CLASSES.iter().find(|&&class| class >= bytes) {
+        Some(class) => *class,
+        None => bytes.next_multiple_of(PAGE_SIZE),
+    };
+    rounded / element_size
+}
+
+/// How many bytes Go would use to store a value of this type
+fn size_of(r#type: &Type) -> usize {
+    layout(r#type).0
+}
+
+/// The size and alignment of a type on a 64-bit platform
+fn layout(r#type: &Type) -> (usize, usize) {
+    if let Some(numeric) = r#type.numeric() {
+        // A complex number is aligned as each of its parts is
+        let size = numeric.size();
+        return (size, numeric.part().map_or(size, Numeric::size));
+    }
+    match r#type {
+        Type::Boolean => (1, 1),
+        Type::String | Type::Interface(_) => (16, 8),
+        Type::Slice(_) => (24, 8),
+        Type::Array(len, element) => {
+            let (size, align) = layout(element);
+            (size * len, align)
+        }
+        Type::Named(named) => {
+            let Ok(fields) = named.fields() else {
+                return (8, 8);
+            };
+            let (mut size, mut align) = (0usize, 1);
+            for (_, field) in fields {
+                let (field_size, field_align) = layout(&field);
+                size = size.next_multiple_of(field_align) + field_size;
+                align = align.max(field_align);
+            }
+            (size.next_multiple_of(align), align)
+        }
+        _ => (8, 8),
+    }
+}
+
+/// Slices are the same if they're the same window onto the same array
+impl PartialEq for Slice {
+    fn eq(&self, other: &Self) -> bool {
+        let same_array = match (&self.array, &other.array) {
+            (Some(left), Some(right)) => Arc::ptr_eq(left, right),
+            (None, None) => true,
+            _ => false,
+        };
+        same_array
+            && self.element == other.element
+            && self.offset == other.offset
+            && self.len == other.len
+            && self.cap == other.cap
+    }
+}
+
+impl Debug for Slice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Slice({})", self)
+    }
+}
+
+impl Display for Slice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (index, value) in self.values().iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            Display::fmt(value, f)?;
+        }
+        write!(f, "]")
+    }
+}
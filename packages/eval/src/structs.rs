@@ -0,0 +1,76 @@
+use crate::types::NamedType;
+use crate::{RuntimeError, RuntimeResult, Type, Value};
+use gor_ast::name::Name;
+use std::fmt::{Display, Formatter};
+
+/// The value of a struct: a copy of each of its fields, in declaration order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Struct {
+    r#type: NamedType,
+    fields: Vec<(Name, Value)>,
+}
+
+impl Struct {
+    pub(crate) fn new(r#type: NamedType, fields: Vec<(Name, Value)>) -> Self {
+        Struct { r#type, fields }
+    }
+
+    pub fn r#type(&self) -> NamedType {
+        self.r#type.clone()
+    }
+
+    pub fn field(&self, name: Name) -> RuntimeResult<&Value> {
+        self.fields
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| self.unknown(name))
+    }
+
+    /// The value of each field, in declaration order
+    pub(crate) fn values(&self) -> impl Iterator<Item = &Value> {
+        self.fields.iter().map(|(_, value)| value)
+    }
+
+    pub(crate) fn field_mut(&mut self, name: Name) -> RuntimeResult<&mut Value> {
+        let error = self.unknown(name);
+        self.fields
+            .iter_mut()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+            .ok_or(error)
+    }
+
+    /// Structs are equal if all their fields are equal.
+    ///
+    /// Every field is compared, so a field that isn't comparable is always an error.
+    pub(crate) fn equals(&self, other: &Struct) -> RuntimeResult<bool> {
+        let mut equal = true;
+        let fields = self.fields.iter().zip(&other.fields);
+        for (((_, left), (_, right)), (_, r#type)) in fields.zip(self.r#type.fields()?) {
+            let interface = matches!(r#type, Type::Interface(_));
+            equal &= left.clone().equals(right.clone(), interface)?;
+        }
+        Ok(equal)
+    }
+
+    fn unknown(&self, field: Name) -> RuntimeError {
+        RuntimeError::UnknownField {
+            r#type: Type::Named(self.r#type.clone()),
+            field,
+        }
+    }
+}
+
+impl Display for Struct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{")?;
+        for (index, (_, value)) in self.fields.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            Display::fmt(value, f)?;
+        }
+        write!(f, "}}")
+    }
+}
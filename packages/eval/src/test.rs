@@ -5,7 +5,12 @@ use pest::iterators::Pairs;
 use pretty_assertions::assert_eq;
 
 use crate::extensions::Evaluable;
-use crate::{try_static_eval, Value, GLOBAL_CONTEXT};
+use crate::{
+    execute_in_default_context, run_in_default_context, try_static_eval, EvalResult, Frame, Value,
+    GLOBAL_CONTEXT,
+};
+use gor_linker::Linker;
+use gor_loader::memory_loader::MemoryLoader;
 use gor_parse::parse;
 use gor_parse::Rule;
 
@@ -19,7 +24,6 @@ pub fn assert_static_expression(expected: Value, expression: &Expression) {
     );
 }
 
-#[track_caller]
 pub async fn assert_expression(expected: Value, expression: &Expression<'_>) {
     let r = expression.evaluate(&*GLOBAL_CONTEXT).await;
     assert_eq!(
@@ -32,7 +36,7 @@ pub async fn assert_expression(expected: Value, expression: &Expression<'_>) {
 }
 
 #[track_caller]
-pub fn parse_expression(input: &str) -> Result<Pairs<Rule>> {
+pub fn parse_expression(input: &str) -> Result<Pairs<'_, Rule>> {
     let p = parse(Rule::expression, input)?;
     let first = p.peek().context("Expected a parse")?;
     assert_eq!(first.as_span().start(), 0);
@@ -40,6 +44,17 @@ pub fn parse_expression(input: &str) -> Result<Pairs<Rule>> {
     Ok(p)
 }
 
+/// Link a `main` package from source, then call its `test` function.
+pub async fn run_module(source: &str) -> EvalResult {
+    run_modules([("main", source)]).await
+}
+
+/// Link packages from source, including `main`, then call the `test` function in `main`.
+pub async fn run_modules<const N: usize>(sources: [(&str, &str); N]) -> EvalResult {
+    let linker = Linker::bootstrap(MemoryLoader::new(sources)).await?;
+    execute_in_default_context(linker, "main", "test").await
+}
+
 macro_rules! test_static_eval {
     ($func_name:ident, $input:expr, $result:expr) => {
         #[test]
@@ -74,6 +89,16 @@ macro_rules! test_eval {
     };
 }
 
+macro_rules! test_module {
+    ($name:ident, $source:expr, $result:expr) => {
+        #[tokio::test]
+        async fn $name() -> Result<()> {
+            assert_eq!($result, run_module($source).await?);
+            Ok(())
+        }
+    };
+}
+
 macro_rules! test_eval_int {
     ($name:ident, $input:expr) => {
         test_eval!($name, stringify!($input), Value::Int($input));
@@ -90,9 +115,4301 @@ test_eval_int!(shl, 13 << 20);
 test_eval_int!(shr, 100000 >> 10);
 test_eval_int!(xor, 6 ^ 10);
 
+test_eval_int!(negate_binds_tightly, -1 + 2);
+
 test_eval!(bit_nand, "6 &^ 10", Value::Int(4));
 
 test_eval!(bit_xor, "6 ^ 10", Value::Int(12));
 
 test_eval!(log_and, "1 && 1", Value::Boolean(true));
 test_eval!(log_or, "0 || 1", Value::Boolean(true));
+test_eval!(not, "!(1 > 2)", Value::Boolean(true));
+test_eval!(
+    not_binds_tightly,
+    "!(1 > 2) && 1 > 2",
+    Value::Boolean(false)
+);
+test_eval!(string_concat, r#""a" + "b" == "ab""#, Value::Boolean(true));
+test_eval!(exact_constant_shift, "1 << 100 >> 98", Value::Int(4));
+test_eval!(
+    exact_constant_arithmetic,
+    "(1 << 70) * (1 << 70) / (1 << 138) - 5",
+    Value::Int(-1)
+);
+
+test_module!(
+    return_value,
+    r#"package main
+    func test() int {
+        return 1 + 2
+    }"#,
+    Value::Int(3)
+);
+
+test_module!(
+    call_with_arguments,
+    r#"package main
+    func add(a, b int) int {
+        return a + b
+    }
+    func test() int {
+        return add(2, 3) * 2
+    }"#,
+    Value::Int(10)
+);
+
+test_module!(
+    nested_calls,
+    r#"package main
+    func square(x int) int {
+        return x * x
+    }
+    func sum_of_squares(a int, b int) int {
+        return square(a) + square(b)
+    }
+    func test() int {
+        return sum_of_squares(3, 4)
+    }"#,
+    Value::Int(25)
+);
+
+test_module!(
+    bare_return,
+    r#"package main
+    func test() {
+        return
+        print("unreachable")
+    }"#,
+    Value::Void
+);
+
+#[tokio::test]
+async fn wrong_argument_count() {
+    let result = run_module(
+        r#"package main
+        func one(a int) int {
+            return a
+        }
+        func test() int {
+            return one(1, 2)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ArgumentCount { expected: 1, found: 2 })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    var_declarations,
+    r#"package main
+    func test() int {
+        var a int = 1
+        var b = 2
+        var c int
+        var (
+            d, e = 3, 4
+            f string
+        )
+        return a + b + c + d + e
+    }"#,
+    Value::Int(10)
+);
+
+test_module!(
+    zero_values,
+    r#"package main
+    func test() string {
+        var s string
+        var b bool
+        print(b)
+        return s
+    }"#,
+    Value::String(String::new())
+);
+
+test_module!(
+    short_declaration,
+    r#"package main
+    func test() int {
+        x := 1
+        x, y := 2, 3
+        return x * y
+    }"#,
+    Value::Int(6)
+);
+
+test_module!(
+    swap,
+    r#"package main
+    func test() int {
+        a, b := 1, 2
+        a, b = b, a
+        return a * 10 + b
+    }"#,
+    Value::Int(21)
+);
+
+test_module!(
+    compound_assignment,
+    r#"package main
+    func test() int {
+        x := 6
+        x += 4
+        x *= 3
+        x <<= 2
+        x &^= 8
+        x -= 1
+        x++
+        x++
+        x--
+        return x
+    }"#,
+    Value::Int(112)
+);
+
+test_module!(
+    parameters_are_variables,
+    r#"package main
+    func double(n int) int {
+        n *= 2
+        return n
+    }
+    func test() int {
+        n := 4
+        return double(n) + n
+    }"#,
+    Value::Int(12)
+);
+
+#[tokio::test]
+async fn no_new_variables() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            x := 1
+            x := 2
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(NoNewVariables)", format!("{:?}", result));
+}
+
+#[tokio::test]
+async fn declared_type_mismatch() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            var x int = "one"
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeError { expected: Int, found: String })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn argument_type_mismatch() {
+    let result = run_module(
+        r#"package main
+        func double(n int) int {
+            return n * 2
+        }
+        func test() int {
+            return double("s")
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeError { expected: Int, found: String })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn result_type_mismatch() {
+    let result = run_module(
+        r#"package main
+        func name() string {
+            return 1.5
+        }
+        func test() string {
+            return name()
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeError { expected: String, found: Numeric(Float64) })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn assign_to_undeclared() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            x = 2
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(NameError(\"x\"))", format!("{:?}", result));
+}
+
+test_module!(
+    recursion,
+    r#"package main
+    func fib(n int) int {
+        if n < 2 {
+            return n
+        }
+        return fib(n - 1) + fib(n - 2)
+    }
+    func test() int {
+        return fib(15)
+    }"#,
+    Value::Int(610)
+);
+
+test_module!(
+    deep_recursion,
+    r#"package main
+    func down(n int) int {
+        if n == 0 {
+            return 0
+        }
+        return down(n-1) + 1
+    }
+    func test() int {
+        return down(20000)
+    }"#,
+    Value::Int(20000)
+);
+
+test_module!(
+    deep_recursion_in_goroutine,
+    r#"package main
+    func down(n int) int {
+        if n == 0 {
+            return 0
+        }
+        return down(n-1) + 1
+    }
+    func test() int {
+        ch := make(chan int)
+        go func() {
+            ch <- down(20000)
+        }()
+        return <-ch
+    }"#,
+    Value::Int(20000)
+);
+
+#[tokio::test]
+async fn stack_overflow() {
+    let result = run_module(
+        r#"package main
+        func forever(n int) int {
+            return forever(n+1) + 1
+        }
+        func test() {
+            defer func() {
+                recover()
+            }()
+            forever(0)
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(StackOverflow)", format!("{:?}", result));
+}
+
+test_module!(
+    else_if_chain,
+    r#"package main
+    func sign(n int) string {
+        if n < 0 {
+            return "negative"
+        } else if n == 0 {
+            return "zero"
+        } else {
+            return "positive"
+        }
+    }
+    func test() string {
+        return sign(-3) + sign(0) + sign(7)
+    }"#,
+    Value::String("negativezeropositive".to_string())
+);
+
+test_module!(
+    if_init_scope,
+    r#"package main
+    func test() int {
+        x := 1
+        if x := 10; x > 5 {
+            x++
+        } else {
+            x--
+        }
+        return x
+    }"#,
+    Value::Int(1)
+);
+
+test_module!(
+    if_init_visible_in_else,
+    r#"package main
+    func test() int {
+        if y := 3; y > 5 {
+            return 0
+        } else if z := y * 2; z > 5 {
+            return y + z
+        }
+        return -1
+    }"#,
+    Value::Int(9)
+);
+
+#[tokio::test]
+async fn non_boolean_condition() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            if 1 {
+                print("truthy")
+            }
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeError { expected: Boolean, found: Int })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    for_clause,
+    r#"package main
+    func test() int {
+        sum := 0
+        for i := 1; i <= 10; i++ {
+            sum += i
+        }
+        return sum
+    }"#,
+    Value::Int(55)
+);
+
+test_module!(
+    for_condition,
+    r#"package main
+    func test() int {
+        n := 1
+        for n < 100 {
+            n *= 3
+        }
+        return n
+    }"#,
+    Value::Int(243)
+);
+
+test_module!(
+    for_forever_with_break,
+    r#"package main
+    func test() int {
+        n := 0
+        for {
+            n++
+            if n == 7 {
+                break
+            }
+        }
+        return n
+    }"#,
+    Value::Int(7)
+);
+
+test_module!(
+    for_continue_runs_post,
+    r#"package main
+    func test() int {
+        odd := 0
+        for i := 0; i < 10; i++ {
+            if i % 2 == 0 {
+                continue
+            }
+            odd += i
+        }
+        return odd
+    }"#,
+    Value::Int(25)
+);
+
+test_module!(
+    loop_variable_carries_between_iterations,
+    r#"package main
+    func test() int {
+        count := 0
+        for i := 0; i < 10; i++ {
+            i++
+            count++
+        }
+        return count
+    }"#,
+    Value::Int(5)
+);
+
+test_module!(
+    labeled_branches,
+    r#"package main
+    func test() int {
+        found := 0
+    outer:
+        for i := 0; i < 5; i++ {
+            for j := 0; j < 5; j++ {
+                if j > i {
+                    continue outer
+                }
+                if i * j == 6 {
+                    found = i * 10 + j
+                    break outer
+                }
+            }
+        }
+        return found
+    }"#,
+    Value::Int(32)
+);
+
+test_module!(
+    return_from_loop,
+    r#"package main
+    func first_square_over(n int) int {
+        for i := 0; ; i++ {
+            if i * i > n {
+                return i
+            }
+        }
+    }
+    func test() int {
+        return first_square_over(50)
+    }"#,
+    Value::Int(8)
+);
+
+#[tokio::test]
+async fn break_outside_loop() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            if true {
+                break
+            }
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(BranchOutsideLoop(\"break\"))", format!("{:?}", result));
+}
+
+test_module!(
+    switch_tag,
+    r#"package main
+    func name(n int) string {
+        switch n {
+        case 1:
+            return "one"
+        case 2, 3:
+            return "few"
+        default:
+            return "many"
+        }
+    }
+    func test() string {
+        return name(1) + name(3) + name(9)
+    }"#,
+    Value::String("onefewmany".to_string())
+);
+
+test_module!(
+    switch_tagless_with_init,
+    r#"package main
+    func test() int {
+        switch x := 5; {
+        case x < 0:
+            return -1
+        case x > 0:
+            return 1
+        }
+        return 0
+    }"#,
+    Value::Int(1)
+);
+
+test_module!(
+    switch_default_first,
+    r#"package main
+    func test() int {
+        switch 2 {
+        default:
+            return 0
+        case 2:
+            return 2
+        }
+    }"#,
+    Value::Int(2)
+);
+
+test_module!(
+    switch_no_match,
+    r#"package main
+    func test() int {
+        result := 1
+        switch "a" {
+        case "b":
+            result = 2
+        }
+        return result
+    }"#,
+    Value::Int(1)
+);
+
+test_module!(
+    switch_fallthrough,
+    r#"package main
+    func test() int {
+        total := 0
+        switch 1 {
+        case 1:
+            total += 1
+            fallthrough
+        case 2:
+            total += 10
+            fallthrough
+        default:
+            total += 100
+        case 3:
+            total += 1000
+        }
+        return total
+    }"#,
+    Value::Int(111)
+);
+
+test_module!(
+    switch_cases_evaluated_lazily,
+    r#"package main
+    func test() int {
+        // Once a case matches, later values aren't evaluated at all.
+        switch 2 {
+        case 1, 2, missing():
+            return 2
+        }
+        return 0
+    }"#,
+    Value::Int(2)
+);
+
+test_module!(
+    switch_break,
+    r#"package main
+    func test() int {
+        n := 0
+        for i := 0; i < 5; i++ {
+            switch i {
+            case 2:
+                break
+            default:
+                n++
+            }
+        }
+        return n
+    }"#,
+    Value::Int(4)
+);
+
+test_module!(
+    switch_labeled_break,
+    r#"package main
+    func test() int {
+        n := 0
+    outer:
+        for i := 0; i < 5; i++ {
+            switch {
+            case i == 2:
+                break outer
+            }
+            n++
+        }
+        return n
+    }"#,
+    Value::Int(2)
+);
+
+#[tokio::test]
+async fn fallthrough_final_case() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            switch 1 {
+            case 1:
+                fallthrough
+            }
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(MisplacedFallthrough)", format!("{:?}", result));
+}
+
+#[tokio::test]
+async fn fallthrough_nested() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            switch 1 {
+            case 1:
+                if true {
+                    fallthrough
+                }
+            case 2:
+            }
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(MisplacedFallthrough)", format!("{:?}", result));
+}
+
+test_module!(
+    struct_keyed_literal,
+    r#"package main
+    type Point struct {
+        x, y int
+    }
+    func test() int {
+        p := Point{y: 2, x: 1}
+        return p.x * 10 + p.y
+    }"#,
+    Value::Int(12)
+);
+
+test_module!(
+    struct_positional_literal,
+    r#"package main
+    type Person struct {
+        name string
+        age  int
+    }
+    func test() string {
+        return Person{"Ann", 42}.name
+    }"#,
+    Value::String("Ann".to_string())
+);
+
+test_module!(
+    struct_zero_values,
+    r#"package main
+    type Inner struct {
+        label string
+        set   bool
+    }
+    type Outer struct {
+        count int
+        inner Inner
+    }
+    func test() bool {
+        var o Outer
+        partial := Outer{count: 1}
+        return o.count == 0 && o.inner.label == "" && !o.inner.set && partial.inner == o.inner
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    struct_field_assignment,
+    r#"package main
+    type Inner struct {
+        n int
+    }
+    type Outer struct {
+        inner Inner
+        total int
+    }
+    func test() int {
+        o := Outer{}
+        o.total = 5
+        o.inner.n += 3
+        o.inner.n++
+        return o.total * 10 + o.inner.n
+    }"#,
+    Value::Int(54)
+);
+
+test_module!(
+    struct_copied_on_assignment,
+    r#"package main
+    type Point struct {
+        x, y int
+    }
+    func move(p Point) int {
+        p.x = 100
+        return p.x
+    }
+    func test() int {
+        a := Point{1, 2}
+        b := a
+        b.x = 10
+        return a.x + move(a) + a.x
+    }"#,
+    Value::Int(102)
+);
+
+test_module!(
+    struct_equality,
+    r#"package main
+    type Point struct {
+        x, y int
+    }
+    func test() bool {
+        return Point{1, 2} == Point{x: 1, y: 2} && Point{1, 2} != Point{2, 1}
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    composite_literal_in_header,
+    r#"package main
+    type Point struct {
+        x, y int
+    }
+    func test() int {
+        p := Point{1, 2}
+        ok := true
+        if ok { p.x = 3 }
+        if p == (Point{3, 2}) {
+            return p.x
+        }
+        return 0
+    }"#,
+    Value::Int(3)
+);
+
+#[tokio::test]
+async fn struct_unknown_field() {
+    let result = run_module(
+        r#"package main
+        type Point struct {
+            x, y int
+        }
+        func test() int {
+            return Point{1, 2}.z
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(UnknownField { type: Named(Point), field: \"z\" })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn struct_too_few_values() {
+    let result = run_module(
+        r#"package main
+        type Point struct {
+            x, y int
+        }
+        func test() Point {
+            return Point{1}
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(InvalidCompositeLiteral(\"Expected 2 values for Named(Point), found 1\"))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn struct_field_type_checked() {
+    let result = run_module(
+        r#"package main
+        type Point struct {
+            x, y int
+        }
+        func test() Point {
+            return Point{x: "one"}
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeError { expected: Int, found: String })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    value_receiver,
+    r#"package main
+    type Rect struct {
+        w, h int
+    }
+    func (r Rect) Area() int {
+        return r.w * r.h
+    }
+    func (r Rect) Scaled(n int) Rect {
+        r.w *= n
+        r.h *= n
+        return r
+    }
+    func test() int {
+        r := Rect{2, 3}
+        big := r.Scaled(2)
+        return r.Area() * 100 + big.Area()
+    }"#,
+    Value::Int(624)
+);
+
+test_module!(
+    pointer_receiver_takes_address,
+    r#"package main
+    type Counter struct {
+        n int
+    }
+    func (c *Counter) Add(by int) {
+        c.n += by
+    }
+    func (c *Counter) Inc() {
+        c.Add(1)
+    }
+    func (c Counter) Value() int {
+        return c.n
+    }
+    func test() int {
+        var c Counter
+        c.Inc()
+        c.Inc()
+        c.Add(10)
+        return c.Value()
+    }"#,
+    Value::Int(12)
+);
+
+test_module!(
+    pointer_receiver_on_field,
+    r#"package main
+    type Counter struct {
+        n int
+    }
+    func (c *Counter) Inc() {
+        c.n++
+    }
+    type Pair struct {
+        left, right Counter
+    }
+    func test() int {
+        p := Pair{}
+        p.right.Inc()
+        p.right.Inc()
+        p.left.Inc()
+        return p.left.n * 10 + p.right.n
+    }"#,
+    Value::Int(12)
+);
+
+test_module!(
+    method_value,
+    r#"package main
+    type Greeter struct {
+        greeting string
+    }
+    func (g Greeter) Greet(name string) string {
+        return g.greeting + ", " + name
+    }
+    func test() string {
+        g := Greeter{"Hello"}
+        greet := g.Greet
+        g.greeting = "Bye"
+        return greet("gor")
+    }"#,
+    Value::String("Hello, gor".to_string())
+);
+
+test_module!(
+    method_and_function_share_name,
+    r#"package main
+    type T struct {
+        n int
+    }
+    func (t T) Get() int {
+        return t.n
+    }
+    func Get() int {
+        return 7
+    }
+    func test() int {
+        return Get() + T{1}.Get()
+    }"#,
+    Value::Int(8)
+);
+
+#[tokio::test]
+async fn pointer_method_needs_address() {
+    let result = run_module(
+        r#"package main
+        type Counter struct {
+            n int
+        }
+        func (c *Counter) Inc() {
+            c.n++
+        }
+        func test() {
+            Counter{}.Inc()
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(NotAddressable(\"Counter{}\"))",
+        format!("{:?}", result)
+    );
+}
+
+const SHAPES: &str = r#"package main
+    type Shape interface {
+        Area() int
+    }
+    type Rect struct {
+        w, h int
+    }
+    func (r Rect) Area() int {
+        return r.w * r.h
+    }
+    type Square struct {
+        side int
+    }
+    func (s Square) Area() int {
+        return s.side * s.side
+    }
+    func total(a, b Shape) int {
+        return a.Area() + b.Area()
+    }
+"#;
+
+#[tokio::test]
+async fn interface_dispatch() -> Result<()> {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() int {
+            var s Shape = Rect{2, 3}
+            first := s.Area()
+            s = Square{4}
+            return first * 100 + s.Area() + total(Rect{1, 1}, Square{1}) * 1000
+        }"#
+    ))
+    .await?;
+    assert_eq!(Value::Int(2616), result);
+    Ok(())
+}
+
+#[tokio::test]
+async fn interface_zero_value_is_nil() -> Result<()> {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() bool {
+            var s Shape
+            return s == nil
+        }"#
+    ))
+    .await?;
+    assert_eq!(Value::Boolean(true), result);
+    Ok(())
+}
+
+#[tokio::test]
+async fn interface_holding_value_is_not_nil() -> Result<()> {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() bool {
+            var s Shape = Square{}
+            return s != nil
+        }"#
+    ))
+    .await?;
+    assert_eq!(Value::Boolean(true), result);
+    Ok(())
+}
+
+test_module!(
+    interface_holding_nil_pointer_is_not_nil,
+    r#"package main
+    type E struct{}
+    func (e *E) Error() string {
+        return "E"
+    }
+    func find() error {
+        var p *E
+        return p
+    }
+    func test() bool {
+        var p *E
+        var e error = p
+        var assigned error
+        assigned = p
+        return p == nil && e != nil && assigned != nil && find() != nil
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    method_on_nil_pointer_in_interface,
+    r#"package main
+    type E struct{}
+    func (e *E) Error() string {
+        if e == nil {
+            return "nil E"
+        }
+        return "E"
+    }
+    func test() string {
+        var p *E
+        var e error = p
+        return e.Error()
+    }"#,
+    Value::String("nil E".into())
+);
+
+test_module!(
+    type_assertion_of_nil_pointer,
+    r#"package main
+    type E struct{}
+    func (e *E) Error() string {
+        return "E"
+    }
+    func test() bool {
+        var p *E
+        var e error = p
+        q, ok := e.(*E)
+        return ok && q == nil
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    interfaces_with_different_dynamic_types,
+    r#"package main
+    func test() bool {
+        var a, b interface{} = 1, "x"
+        return a != b && !(a == b)
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    interfaces_of_different_types_from_anywhere,
+    r#"package main
+    type NotFound struct{}
+    func (NotFound) Error() string {
+        return "not found"
+    }
+    type Timeout struct{}
+    func (Timeout) Error() string {
+        return "timeout"
+    }
+    type Result struct {
+        Err error
+    }
+    func find() error {
+        return Timeout{}
+    }
+    func test() int {
+        count := 0
+        err := find()
+        if err != (NotFound{}) && find() == (Timeout{}) {
+            count++
+        }
+        if r := (Result{NotFound{}}); r.Err != (Timeout{}) {
+            count++
+        }
+        values := []interface{}{1, "x", 2.5}
+        for _, v := range values {
+            if v == "x" {
+                count++
+            }
+        }
+        if values[0] != "x" {
+            count++
+        }
+        m := map[string]interface{}{"a": 1}
+        switch m["a"] {
+        case "a":
+        case 1:
+            count++
+        }
+        if ([2]interface{}{1, "x"}) != ([2]interface{}{"x", 1}) {
+            count++
+        }
+        return count
+    }"#,
+    Value::Int(6)
+);
+
+#[tokio::test]
+async fn comparing_different_types_fails() {
+    for (declarations, comparison, left, right) in [
+        ("", "int(1) == int64(1)", "Int", "Numeric(Int64)"),
+        (
+            "type Celsius float64\ntype Fahrenheit float64",
+            "Celsius(1) == Fahrenheit(1)",
+            "Named(Celsius)",
+            "Named(Fahrenheit)",
+        ),
+        (
+            "type A struct{ X int }\ntype B struct{ X int }",
+            "A{1} != B{1}",
+            "Named(A)",
+            "Named(B)",
+        ),
+    ] {
+        let result = run_module(&format!(
+            "package main\n{}\nfunc test() bool {{\nreturn {}\n}}",
+            declarations, comparison
+        ))
+        .await;
+        let op = if comparison.contains("==") {
+            "Eq"
+        } else {
+            "Neq"
+        };
+        assert_eq!(
+            format!(
+                "Err(TypeMismatch {{ left: {}, op: {}, right: {} }})",
+                left, op, right
+            ),
+            format!("{:?}", result)
+        );
+    }
+}
+
+#[tokio::test]
+async fn comparing_uncomparable_interfaces_panics() {
+    let result = run_module(
+        r#"package main
+        func test() bool {
+            var a, b interface{} = []int{1}, []int{1}
+            return a == b
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: comparing uncomparable type []int\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn method_on_nil_interface_panics() {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() int {
+            var s Shape
+            return s.Area()
+        }"#
+    ))
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: invalid memory address or nil pointer dereference\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn interface_not_implemented() {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() {
+            var s Shape = 3
+        }"#
+    ))
+    .await;
+    assert_eq!(
+        "Err(TypeError { expected: Interface(Shape), found: Int })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    pointer_method_set,
+    r#"package main
+    type Incer interface {
+        Inc()
+    }
+    type Counter struct {
+        n int
+    }
+    func (c *Counter) Inc() {
+        c.n++
+    }
+    func (c *Counter) Twice() {
+        var i Incer = c
+        i.Inc()
+        i.Inc()
+    }
+    func test() int {
+        var c Counter
+        c.Twice()
+        c.Twice()
+        return c.n
+    }"#,
+    Value::Int(4)
+);
+
+#[tokio::test]
+async fn value_lacks_pointer_methods() {
+    let result = run_module(
+        r#"package main
+        type Incer interface {
+            Inc()
+        }
+        type Counter struct {
+            n int
+        }
+        func (c *Counter) Inc() {
+            c.n++
+        }
+        func test() {
+            var i Incer = Counter{}
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeError { expected: Interface(Incer), found: Named(Counter) })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn type_assertion() -> Result<()> {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() int {
+            var s Shape = Rect{2, 3}
+            r := s.(Rect)
+            return r.w
+        }"#
+    ))
+    .await?;
+    assert_eq!(Value::Int(2), result);
+    Ok(())
+}
+
+#[tokio::test]
+async fn type_assertion_panics() {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() int {
+            var s Shape = Rect{2, 3}
+            return s.(Square).side
+        }"#
+    ))
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"interface conversion: interface is Rect, not Square\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn type_assertion_on_nil_panics() {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() Rect {
+            var s Shape
+            return s.(Rect)
+        }"#
+    ))
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"interface conversion: interface is nil, not Rect\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn type_assertion_comma_ok() -> Result<()> {
+    let result = run_module(&format!(
+        "{}{}",
+        SHAPES,
+        r#"func test() int {
+            var s Shape = Rect{2, 3}
+            sq, ok := s.(Square)
+            if ok {
+                return -1
+            }
+            r, ok := s.(Rect)
+            if !ok {
+                return -2
+            }
+            var n, found = s.(Square)
+            if found {
+                return -3
+            }
+            return r.w * 10 + sq.side + n.side
+        }"#
+    ))
+    .await?;
+    assert_eq!(Value::Int(20), result);
+    Ok(())
+}
+
+test_module!(
+    type_assertion_to_interface,
+    r#"package main
+    type Named interface {
+        Name() string
+    }
+    type Aged interface {
+        Age() int
+    }
+    type Person struct {
+        name string
+        age int
+    }
+    func (p Person) Name() string {
+        return p.name
+    }
+    func (p Person) Age() int {
+        return p.age
+    }
+    func test() int {
+        var x any = Person{"Ada", 36}
+        n := x.(Named)
+        a, ok := n.(Aged)
+        if !ok || n.Name() != "Ada" {
+            return -1
+        }
+        return a.Age()
+    }"#,
+    Value::Int(36)
+);
+
+#[tokio::test]
+async fn type_assertion_missing_method() {
+    let result = run_module(
+        r#"package main
+        type Stringer interface {
+            String() string
+        }
+        func test() {
+            var x any = 1
+            x.(Stringer)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"interface conversion: int is not Stringer: missing method String\")))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    embedded_interface,
+    r#"package main
+    type Reader interface {
+        Read() string
+    }
+    type ReadCloser interface {
+        Reader
+        Close() bool
+    }
+    type File struct {
+        contents string
+    }
+    func (f File) Read() string {
+        return f.contents
+    }
+    func (f File) Close() bool {
+        return true
+    }
+    func test() string {
+        var rc ReadCloser = File{"data"}
+        var r Reader = rc
+        return r.Read()
+    }"#,
+    Value::String("data".to_string())
+);
+
+test_module!(
+    error_interface,
+    r#"package main
+    type NotFound struct {
+        name string
+    }
+    func (e NotFound) Error() string {
+        return e.name + " not found"
+    }
+    func find(name string) error {
+        if name == "gor" {
+            return nil
+        }
+        return NotFound{name}
+    }
+    func test() string {
+        if err := find("gor"); err != nil {
+            return "unexpected"
+        }
+        err := find("go")
+        return err.Error()
+    }"#,
+    Value::String("go not found".to_string())
+);
+
+test_module!(
+    type_switch,
+    r#"package main
+    type Stringer interface {
+        String() string
+    }
+    type Celsius struct {
+        degrees int
+    }
+    func (c Celsius) String() string {
+        return "hot"
+    }
+    type Point struct {
+        x, y int
+    }
+    func describe(x any) string {
+        switch v := x.(type) {
+        case nil:
+            return "nil"
+        case int:
+            return "int"
+        case string:
+            return "string " + v
+        case Stringer:
+            return "stringer " + v.String()
+        case Point:
+            if v.x == v.y {
+                break
+            }
+            return "point"
+        default:
+            return "other"
+        }
+        return "diagonal"
+    }
+    func test() string {
+        return describe(nil) + ", " + describe(1) + ", " + describe("a") + ", " +
+            describe(Celsius{40}) + ", " + describe(Point{1, 2}) + ", " + describe(Point{}) +
+            ", " + describe(true)
+    }"#,
+    Value::String("nil, int, string a, stringer hot, point, diagonal, other".to_string())
+);
+
+test_module!(
+    type_switch_multiple_types,
+    r#"package main
+    func kind(x any) int {
+        switch y := 1; x.(type) {
+        case int, bool:
+            return y
+        case string:
+            return y + 1
+        }
+        return 0
+    }
+    func test() int {
+        return kind(1) * 100 + kind(false) * 10 + kind("s") + kind(nil) * 1000
+    }"#,
+    Value::Int(112)
+);
+
+test_module!(
+    type_switch_first_match,
+    r#"package main
+    type Sizer interface {
+        Size() int
+    }
+    type File struct {
+        size int
+    }
+    func (f File) Size() int {
+        return f.size
+    }
+    func test() int {
+        var x any = File{3}
+        switch v := x.(type) {
+        case Sizer:
+            return v.Size()
+        case File:
+            return -1
+        }
+        return 0
+    }"#,
+    Value::Int(3)
+);
+
+test_module!(
+    type_switch_nil_pointer,
+    r#"package main
+    func test() string {
+        var p *int
+        var x any
+        x = p
+        switch v := x.(type) {
+        case nil:
+            return "nil"
+        case *int:
+            if v == nil {
+                return "nil *int"
+            }
+            return "*int"
+        }
+        return "other"
+    }"#,
+    Value::String("nil *int".to_string())
+);
+
+#[tokio::test]
+async fn type_switch_unknown_type() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            var x any = 1
+            switch x.(type) {
+            case Missing:
+            }
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(UnknownType(\"Missing\"))", format!("{:?}", result));
+}
+
+test_module!(
+    slice_index,
+    r#"package main
+    func test() int {
+        s := []int{1, 2, 3}
+        s[1] = 5
+        s[2] += 1
+        return s[0] + s[1] * 10 + s[2] * 100 + len(s) * 1000
+    }"#,
+    Value::Int(3451)
+);
+
+test_module!(
+    slice_shares_array,
+    r#"package main
+    func test() int {
+        s := []int{1, 2, 3, 4}
+        t := s[1:3]
+        t[0] = 9
+        return s[1] * 100 + len(t) * 10 + cap(t)
+    }"#,
+    Value::Int(923)
+);
+
+test_module!(
+    slice_omitted_bounds,
+    r#"package main
+    func test() int {
+        s := []int{1, 2, 3, 4}
+        return len(s[:]) * 100 + len(s[2:]) * 10 + len(s[:1])
+    }"#,
+    Value::Int(421)
+);
+
+test_module!(
+    append_within_capacity,
+    r#"package main
+    func test() int {
+        s := make([]int, 2, 4)
+        t := append(s, 7)
+        s = append(s, 8)
+        return t[2]
+    }"#,
+    Value::Int(8)
+);
+
+test_module!(
+    append_growth,
+    r#"package main
+    func test() int {
+        var s []int
+        caps := 0
+        for i := 0; i < 5; i++ {
+            s = append(s, i)
+            caps = caps * 10 + cap(s)
+        }
+        return caps
+    }"#,
+    Value::Int(12448)
+);
+
+test_module!(
+    append_rounds_to_size_class,
+    r#"package main
+    func test() int {
+        ints := append([]int{}, 1, 2, 3, 4, 5)
+        strings := append([]string{}, "a", "b", "c")
+        return cap(ints) * 10 + cap(strings)
+    }"#,
+    Value::Int(63)
+);
+
+test_module!(
+    append_rounds_by_element_size,
+    r#"package main
+    func test() int {
+        bytes := append([]byte{}, 1)
+        shorts := append([]int16{}, 1)
+        floats := append([]float32{}, 1)
+        complexes := append([]complex128{}, 1)
+        return cap(bytes)*1000 + cap(shorts)*100 + cap(floats)*10 + cap(complexes)
+    }"#,
+    Value::Int(8421)
+);
+
+test_module!(
+    append_growth_large,
+    r#"package main
+    func test() int {
+        var s []int
+        for i := 0; i < 600; i++ {
+            s = append(s, i)
+        }
+        return cap(s)
+    }"#,
+    Value::Int(848)
+);
+
+test_module!(
+    append_beyond_capacity_copies,
+    r#"package main
+    func test() int {
+        s := make([]int, 5, 10)
+        t := s[1:3:4]
+        if len(t) != 2 || cap(t) != 3 {
+            return -1
+        }
+        t = append(t, 1, 2)
+        t[0] = 7
+        return s[1]
+    }"#,
+    Value::Int(0)
+);
+
+test_module!(
+    make_zero_values,
+    r#"package main
+    func test() bool {
+        s := make([]string, 2, 5)
+        return len(s) == 2 && cap(s) == 5 && s[1] == ""
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    copy_slices,
+    r#"package main
+    func test() int {
+        dst := make([]int, 2)
+        n := copy(dst, []int{1, 2, 3})
+        s := []int{1, 2, 3, 4}
+        copy(s[1:], s)
+        return n * 10000 + dst[0] * 1000 + dst[1] * 100 + s[1] * 10 + s[3]
+    }"#,
+    Value::Int(21213)
+);
+
+test_module!(
+    copy_string_to_bytes,
+    r#"package main
+    func test() int {
+        b := make([]byte, 3)
+        n := copy(b, "hello")
+        return n * 1000 + int(b[0]) - int(b[2])
+    }"#,
+    Value::Int(2996)
+);
+
+test_module!(
+    nil_slice,
+    r#"package main
+    func test() int {
+        var s []int
+        if s != nil || len(s) != 0 {
+            return -1
+        }
+        s = append(s, 1)
+        if s == nil {
+            return -2
+        }
+        s = nil
+        if s != nil || len(append(s, 1, 2)) != 2 {
+            return -3
+        }
+        return len([]int{})
+    }"#,
+    Value::Int(0)
+);
+
+test_module!(
+    slice_literal_indexes,
+    r#"package main
+    func test() int {
+        s := []int{2: 5, 1}
+        return len(s) * 1000 + s[0] * 100 + s[2] * 10 + s[3]
+    }"#,
+    Value::Int(4051)
+);
+
+test_module!(
+    slice_of_structs,
+    r#"package main
+    type Point struct {
+        x, y int
+    }
+    func test() int {
+        ps := []Point{Point{1, 2}, Point{y: 3}}
+        ps[0].x = 5
+        p := ps[1]
+        p.y = 9
+        return ps[0].x * 10 + ps[1].y
+    }"#,
+    Value::Int(53)
+);
+
+test_module!(
+    slice_of_slices,
+    r#"package main
+    func test() int {
+        grid := [][]int{[]int{1}, []int{2, 3}}
+        grid[1][0] = 4
+        return grid[1][0] * 10 + len(grid[1])
+    }"#,
+    Value::Int(42)
+);
+
+test_module!(
+    string_index_and_slice,
+    r#"package main
+    func test() string {
+        s := "hello"
+        if s[1] != 101 || len(s) != 5 {
+            return "wrong"
+        }
+        return s[1:3] + s[3:]
+    }"#,
+    Value::String("ello".to_string())
+);
+
+#[tokio::test]
+async fn index_out_of_range() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            s := []int{1}
+            return s[3]
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: index out of range [3] with length 1\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn slice_out_of_range() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            s := make([]int, 2, 3)
+            return len(s[1:5])
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: slice bounds out of range [:5] with capacity 3\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn slice_bounds_inverted() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            s := []int{1, 2, 3}
+            i := 2
+            return len(s[i:1])
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: slice bounds out of range [2:1]\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn make_negative_len() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            n := -1
+            make([]int, n)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: makeslice: len out of range\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn slices_are_not_comparable() {
+    let result = run_module(
+        r#"package main
+        func test() bool {
+            return []int{} == []int{}
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: comparing uncomparable type []int\")))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    array_value_semantics,
+    r#"package main
+    func zero(a [3]int) [3]int {
+        a[0] = 0
+        return a
+    }
+    func test() int {
+        a := [3]int{1, 2, 3}
+        b := a
+        b[0] = 9
+        c := zero(a)
+        return a[0] * 100 + b[0] * 10 + c[0] + len(a) * 1000
+    }"#,
+    Value::Int(3190)
+);
+
+test_module!(
+    array_zero_value,
+    r#"package main
+    func test() bool {
+        var a [4]string
+        var grid [2][3]int
+        grid[1][2] = 5
+        return len(a) == 4 && a[3] == "" && grid[1][2] == 5 && grid[0][2] == 0
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    array_length_from_constant,
+    r#"package main
+    const N = 4
+    const Rows = 2
+    func test() int {
+        var a [N]int
+        var grid [Rows * N]bool
+        return len(a) * 10 + len(grid)
+    }"#,
+    Value::Int(48)
+);
+
+test_module!(
+    array_ellipsis_literal,
+    r#"package main
+    func test() int {
+        a := [...]int{1, 2, 3, 4}
+        b := [...]string{5: "x"}
+        return len(a) * 10 + len(b)
+    }"#,
+    Value::Int(46)
+);
+
+test_module!(
+    array_comparison,
+    r#"package main
+    type Point struct {
+        x, y int
+    }
+    func test() bool {
+        a := [2]Point{Point{1, 2}}
+        b := a
+        same := a == b
+        b[1].y = 1
+        return same && a != b
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    array_slice_aliases,
+    r#"package main
+    func test() int {
+        var a [5]int
+        s := a[1:3]
+        s[0] = 7
+        s = append(s, 8)
+        return a[1] * 100 + a[3] * 10 + cap(s)
+    }"#,
+    Value::Int(784)
+);
+
+test_module!(
+    array_in_struct,
+    r#"package main
+    type Matrix struct {
+        rows [2][2]int
+    }
+    func (m Matrix) Trace() int {
+        return m.rows[0][0] + m.rows[1][1]
+    }
+    func (m *Matrix) Scale(n int) {
+        for i := 0; i < 2; i++ {
+            for j := 0; j < 2; j++ {
+                m.rows[i][j] *= n
+            }
+        }
+    }
+    func test() int {
+        m := Matrix{[2][2]int{[2]int{1, 2}, [2]int{3, 4}}}
+        copy := m
+        m.Scale(10)
+        return m.Trace() * 100 + copy.Trace()
+    }"#,
+    Value::Int(5005)
+);
+
+#[tokio::test]
+async fn array_index_out_of_range() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            var a [3]int
+            i := 3
+            return a[i]
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: index out of range [3] with length 3\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn array_literal_too_long() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            a := [2]int{1, 2, 3}
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(InvalidCompositeLiteral(\"Index 2 out of bounds [0:2]\"))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn array_type_mismatch() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            var a [2]int = [3]int{}
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeError { expected: Array(2, Int), found: Array(3, Int) })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    map_literal_and_index,
+    r#"package main
+    func test() int {
+        m := map[string]int{"one": 1, "two": 2}
+        m["three"] = 3
+        m["one"] += 10
+        return m["one"] * 100 + m["three"] * 10 + m["missing"] + len(m) * 1000
+    }"#,
+    Value::Int(4130)
+);
+
+test_module!(
+    map_is_shared,
+    r#"package main
+    func add(m map[int]bool, key int) {
+        m[key] = true
+    }
+    func test() int {
+        m := make(map[int]bool, 10)
+        alias := m
+        add(alias, 4)
+        add(m, 7)
+        return len(m)
+    }"#,
+    Value::Int(2)
+);
+
+test_module!(
+    map_comma_ok,
+    r#"package main
+    func test() int {
+        m := map[string]int{"zero": 0}
+        total := 0
+        if v, ok := m["zero"]; ok {
+            total += 10 + v
+        }
+        v, ok := m["none"]
+        if !ok {
+            total += 100 + v
+        }
+        return total
+    }"#,
+    Value::Int(110)
+);
+
+test_module!(
+    map_delete_and_clear,
+    r#"package main
+    func test() int {
+        m := map[int]int{1: 1, 2: 4, 3: 9}
+        delete(m, 2)
+        delete(m, 5)
+        before := len(m)
+        clear(m)
+        return before * 10 + len(m)
+    }"#,
+    Value::Int(20)
+);
+
+test_module!(
+    map_struct_and_array_keys,
+    r#"package main
+    type Point struct {
+        x, y int
+    }
+    func test() string {
+        names := map[Point]string{Point{1, 2}: "a"}
+        names[Point{1, 2}] += "b"
+        grid := map[[2]int]string{}
+        grid[[2]int{3, 4}] = "c"
+        var keys map[any]string = map[any]string{1: "d", "1": "e"}
+        return names[Point{1, 2}] + grid[[2]int{3, 4}] + keys[1] + keys["1"]
+    }"#,
+    Value::String("abcde".to_string())
+);
+
+test_module!(
+    nil_map_reads,
+    r#"package main
+    func test() bool {
+        var m map[string]int
+        delete(m, "x")
+        return m == nil && len(m) == 0 && m["x"] == 0
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    map_of_slices,
+    r#"package main
+    func test() int {
+        m := map[string][]int{}
+        m["a"] = append(m["a"], 1)
+        m["a"] = append(m["a"], 2)
+        m["a"][0] = 5
+        return m["a"][0] * 10 + len(m["a"])
+    }"#,
+    Value::Int(52)
+);
+
+#[tokio::test]
+async fn map_prints_in_key_order() -> Result<()> {
+    let value = run_module(
+        r#"package main
+        func test() map[string]int {
+            return map[string]int{"b": 2, "c": 3, "a": 1}
+        }"#,
+    )
+    .await?;
+    assert_eq!("map[a:1 b:2 c:3]", value.to_string());
+    Ok(())
+}
+
+#[tokio::test]
+async fn nil_map_assignment_panics() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            var m map[string]int
+            m["x"] = 1
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"assignment to entry in nil map\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn unhashable_map_key_panics() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            m := map[any]int{}
+            m[[]int{1}] = 1
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: hash of unhashable type []int\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn invalid_map_key_type() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            var m map[[]int]int
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(InvalidMapKey(Slice(Int)))", format!("{:?}", result));
+}
+
+#[tokio::test]
+async fn duplicate_map_literal_key() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            m := map[string]int{"a": 1, "a": 2}
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(InvalidCompositeLiteral(\"Duplicate key \\\"a\\\" in map literal\"))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn duplicate_constant_map_literal_key() {
+    let result = run_module(
+        r#"package main
+        const a = "x"
+        const b = "x"
+        func test() {
+            m := map[string]int{a: 1, b: 2}
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(InvalidCompositeLiteral(\"Duplicate key b in map literal\"))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn map_entry_field_not_assignable() {
+    let result = run_module(
+        r#"package main
+        type Point struct {
+            x, y int
+        }
+        func test() {
+            m := map[string]Point{}
+            m["a"].x = 1
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(NotAssignable(\"m[\\\"a\\\"].x\"))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    range_over_slice,
+    r#"package main
+    func test() int {
+        s := []int{1, 2, 3}
+        total := 0
+        for i, v := range s {
+            total += i * v
+            if i == 0 {
+                s[2] = 10
+            }
+        }
+        for i := range s {
+            total += i
+        }
+        return total
+    }"#,
+    Value::Int(25)
+);
+
+test_module!(
+    range_over_array_copies,
+    r#"package main
+    func test() int {
+        a := [3]int{1, 2, 3}
+        total := 0
+        for i, v := range a {
+            a[2] = 10
+            total += v
+            if i == 1 {
+                continue
+            }
+        }
+        return total * 100 + a[2]
+    }"#,
+    Value::Int(610)
+);
+
+test_module!(
+    range_over_string,
+    r#"package main
+    func test() int {
+        total := 0
+        for i, r := range "aé!" {
+            total = total * 1000 + i * 100 + r
+        }
+        return total
+    }"#,
+    Value::Int(97_333_333)
+);
+
+test_module!(
+    range_over_map,
+    r#"package main
+    func test() int {
+        m := map[int]int{1: 10, 2: 20, 3: 30}
+        total := 0
+        for k, v := range m {
+            if k == 1 {
+                delete(m, 3)
+            }
+            total += k + v
+        }
+        return total
+    }"#,
+    Value::Int(33)
+);
+
+test_module!(
+    range_over_int,
+    r#"package main
+    func test() int {
+        total := 0
+        for i := range 5 {
+            total += i
+        }
+        for range 3 {
+            total++
+        }
+        return total
+    }"#,
+    Value::Int(13)
+);
+
+test_module!(
+    range_assigns_existing_variables,
+    r#"package main
+    func test() int {
+        var k, v int
+        outer:
+        for k, v = range []int{4, 5, 6} {
+            for range 10 {
+                if v == 5 {
+                    break outer
+                }
+            }
+        }
+        return k * 10 + v
+    }"#,
+    Value::Int(15)
+);
+
+#[tokio::test]
+async fn range_over_int_has_no_values() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            for i, v := range 3 {
+            }
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(AssignmentMismatch { expected: 2, found: 1 })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    address_and_deref,
+    r#"package main
+    func swap(a, b *int) {
+        *a, *b = *b, *a
+    }
+    func test() int {
+        x, y := 1, 2
+        p := &x
+        *p = 5
+        swap(&x, &y)
+        return x * 10 + y
+    }"#,
+    Value::Int(25)
+);
+
+test_module!(
+    new_allocates_zero_value,
+    r#"package main
+    func test() int {
+        p := new(int)
+        *p += 3
+        q := new(*int)
+        *q = p
+        **q *= 7
+        return *p
+    }"#,
+    Value::Int(21)
+);
+
+test_module!(
+    address_of_composite_literal,
+    r#"package main
+    type Node struct {
+        value int
+        next  *Node
+    }
+    func test() int {
+        list := &Node{value: 1}
+        list = &Node{2, list}
+        list.next.value = 10
+        total := 0
+        for n := list; n != nil; n = n.next {
+            total += n.value
+        }
+        return total
+    }"#,
+    Value::Int(12)
+);
+
+test_module!(
+    composite_literals_with_elided_types,
+    r#"package main
+    type P struct {
+        X, Y int
+    }
+    func test() int {
+        grid := [3][3]int{{1, 2, 3}, {4, 5, 6}, {7, 8, 9}}
+        ragged := [][]int{{1, 2}, {3}}
+        points := []P{{X: 9}, {1, 2}}
+        pointers := []*P{{X: 4}}
+        byPoint := map[P]string{{1, 2}: "a", {X: 3}: "b"}
+        nested := map[string][]P{"a": {{Y: 5}}}
+        return grid[2][1] + len(ragged[0])*10 + points[0].X*100 + points[1].Y*1000 +
+            pointers[0].X*10000 + len(byPoint[P{3, 0}])*100000 + nested["a"][0].Y*1000000
+    }"#,
+    Value::Int(5_142_928)
+);
+
+#[tokio::test]
+async fn composite_literal_with_elided_type_mixing_elements() {
+    let result = run_module(
+        r#"package main
+        type P struct {
+            X, Y int
+        }
+        func test() int {
+            return []P{{X: 1, 2}}[0].X
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(InvalidCompositeLiteral(\"Mixture of keyed and positional elements in Named(P)\"))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    pointers_to_elements_and_fields,
+    r#"package main
+    type Point struct {
+        x, y int
+    }
+    func test() int {
+        s := []int{1, 2, 3}
+        e := &s[2]
+        *e = 30
+        var p Point
+        f := &p.y
+        *f = 4
+        pp := &p
+        pp.x = 5
+        return s[2] + p.x * 10 + p.y
+    }"#,
+    Value::Int(84)
+);
+
+test_module!(
+    pointer_equality,
+    r#"package main
+    func test() bool {
+        x, y := 1, 1
+        var nothing *int
+        return &x == &x && &x != &y && nothing == nil
+    }"#,
+    Value::Boolean(true)
+);
+
+#[tokio::test]
+async fn nil_pointer_dereference_panics() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            var p *int
+            return *p
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: invalid memory address or nil pointer dereference\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn nil_pointer_assignment_panics() {
+    let result = run_module(
+        r#"package main
+        type Point struct {
+            x, y int
+        }
+        func test() {
+            var p *Point
+            p.x = 1
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: invalid memory address or nil pointer dereference\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn map_entry_not_addressable() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            m := map[string]int{}
+            p := &m["a"]
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(NotAddressable(\"m[\\\"a\\\"]\"))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    closure_counter,
+    r#"package main
+    func counter() func() int {
+        n := 0
+        return func() int {
+            n++
+            return n
+        }
+    }
+    func test() int {
+        a, b := counter(), counter()
+        a()
+        a()
+        b()
+        return a() * 10 + b()
+    }"#,
+    Value::Int(32)
+);
+
+test_module!(
+    closure_captures_by_reference,
+    r#"package main
+    func test() int {
+        x := 1
+        add := func(n int) {
+            x += n
+        }
+        get := func() int { return x }
+        add(2)
+        x *= 10
+        add(4)
+        return get()
+    }"#,
+    Value::Int(34)
+);
+
+test_module!(
+    function_arguments,
+    r#"package main
+    func apply(f func(int) int, x int) int {
+        return f(x)
+    }
+    func double(x int) int {
+        return x * 2
+    }
+    func adder(n int) func(int) int {
+        return func(x int) int { return x + n }
+    }
+    func test() int {
+        return apply(double, apply(adder(3), 4))
+    }"#,
+    Value::Int(14)
+);
+
+test_module!(
+    recursive_closure,
+    r#"package main
+    func test() int {
+        var fib func(int) int
+        fib = func(n int) int {
+            if n < 2 {
+                return n
+            }
+            return fib(n-1) + fib(n-2)
+        }
+        return fib(10)
+    }"#,
+    Value::Int(55)
+);
+
+test_module!(
+    closures_capture_each_iteration,
+    r#"package main
+    func test() int {
+        var fs []func() int
+        for i := 0; i < 3; i++ {
+            fs = append(fs, func() int { return i })
+        }
+        return fs[0]() * 100 + fs[1]() * 10 + fs[2]()
+    }"#,
+    Value::Int(12)
+);
+
+test_module!(
+    call_function_values,
+    r#"package main
+    type Op struct {
+        apply func(int, int) int
+    }
+    func test() int {
+        fs := map[string]func(int) int{
+            "inc": func(x int) int { return x + 1 },
+        }
+        op := Op{apply: func(a, b int) int { return a * b }}
+        return func(x int) int { return x * 100 }(fs["inc"](1)) + op.apply(3, 4)
+    }"#,
+    Value::Int(212)
+);
+
+test_module!(
+    nil_function_comparison,
+    r#"package main
+    func test() bool {
+        var f func()
+        g := func() {}
+        return f == nil && g != nil
+    }"#,
+    Value::Boolean(true)
+);
+
+#[tokio::test]
+async fn nil_function_call_panics() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            var f func(int)
+            f(1)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: invalid memory address or nil pointer dereference\")))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    multiple_results,
+    r#"package main
+    func divmod(a, b int) (int, int) {
+        return a / b, a % b
+    }
+    func test() int {
+        q, r := divmod(17, 5)
+        r, q = divmod(q*10, 7)
+        return q * 10 + r
+    }"#,
+    Value::Int(24)
+);
+
+test_module!(
+    error_results,
+    r#"package main
+    type DivError struct {
+        n int
+    }
+    func (e DivError) Error() string {
+        return "division by zero"
+    }
+    func div(a, b int) (int, error) {
+        if b == 0 {
+            return 0, DivError{a}
+        }
+        return a / b, nil
+    }
+    func test() string {
+        if _, err := div(1, 0); err != nil {
+            v, err := div(6, 3)
+            if err == nil && v == 2 {
+                return describe(div(1, 0))
+            }
+        }
+        return "no error"
+    }
+    func describe(_ int, err error) string {
+        return "error: " + err.Error()
+    }"#,
+    Value::String("error: division by zero".to_string())
+);
+
+test_module!(
+    named_results,
+    r#"package main
+    func split(sum int) (x, y int) {
+        x = sum * 4 / 9
+        y = sum - x
+        return
+    }
+    func early(n int) (result int) {
+        result = 1
+        if n > 0 {
+            return n * 2
+        }
+        return
+    }
+    func test() int {
+        a, b := split(17)
+        return a * 100 + b * 10 + early(0) + early(3)
+    }"#,
+    Value::Int(807)
+);
+
+test_module!(
+    call_with_results_as_arguments,
+    r#"package main
+    func pair() (int, int) {
+        return 3, 4
+    }
+    func add(a, b int) int {
+        return a + b
+    }
+    func forward() (int, int) {
+        return pair()
+    }
+    func test() int {
+        return add(pair()) * 10 + add(forward())
+    }"#,
+    Value::Int(77)
+);
+
+test_module!(
+    blank_identifier,
+    r#"package main
+    func pair() (int, int) {
+        return 3, 4
+    }
+    func test() int {
+        _, b := pair()
+        _ = b
+        var _ = 5
+        total := 0
+        for _, v := range []int{1, 2, 3} {
+            total += v
+        }
+        _, total = pair()
+        return b * 10 + total
+    }"#,
+    Value::Int(44)
+);
+
+#[tokio::test]
+async fn results_count_mismatch() {
+    let result = run_module(
+        r#"package main
+        func pair() (int, int) {
+            return 3, 4
+        }
+        func test() {
+            x := pair()
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(AssignmentMismatch { expected: 1, found: 2 })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn multiple_results_in_single_value_context() {
+    let result = run_module(
+        r#"package main
+        func pair() (int, int) {
+            return 3, 4
+        }
+        func test() {
+            print(1, pair())
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(MultipleValues(\"pair()\"))", format!("{:?}", result));
+}
+
+#[tokio::test]
+async fn blank_identifier_is_not_a_value() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            _ = 1
+            return _
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(NameError(\"_\"))", format!("{:?}", result));
+}
+
+test_module!(
+    variadic_function,
+    r#"package main
+    func sum(prefix int, xs ...int) int {
+        total := prefix
+        for _, x := range xs {
+            total += x
+        }
+        return total
+    }
+    func test() int {
+        return sum(1000) + sum(100, 1, 2) + sum(0, []int{10, 20}...)
+    }"#,
+    Value::Int(1133)
+);
+
+test_module!(
+    variadic_without_arguments_is_nil,
+    r#"package main
+    func none(xs ...string) bool {
+        return xs == nil && len(xs) == 0
+    }
+    func test() bool {
+        return none()
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    spread_shares_slice,
+    r#"package main
+    func zero(xs ...int) {
+        xs[0] = 0
+    }
+    func test() int {
+        s := []int{1, 2, 3}
+        zero(s...)
+        zero(s[1], s[2])
+        return s[0] * 100 + s[1] * 10 + s[2]
+    }"#,
+    Value::Int(23)
+);
+
+test_module!(
+    append_spread,
+    r#"package main
+    func test() int {
+        s := []int{1, 2}
+        s = append(s, s...)
+        s = append(s, []int{5}...)
+        var empty []int
+        s = append(s, empty...)
+        return len(s) * 10 + s[3] + s[4]
+    }"#,
+    Value::Int(57)
+);
+
+test_module!(
+    variadic_closure_and_method,
+    r#"package main
+    type Log struct {
+        lines []string
+    }
+    func (l *Log) add(lines ...string) {
+        l.lines = append(l.lines, lines...)
+    }
+    func test() int {
+        var l Log
+        l.add("a", "b")
+        l.add()
+        count := func(xs ...string) int { return len(xs) }
+        return count(l.lines...) * 10 + count("x")
+    }"#,
+    Value::Int(21)
+);
+
+#[tokio::test]
+async fn spread_to_non_variadic_function() {
+    let result = run_module(
+        r#"package main
+        func one(a int) int {
+            return a
+        }
+        func test() int {
+            return one([]int{1}...)
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(NotVariadic)", format!("{:?}", result));
+}
+
+#[tokio::test]
+async fn spread_with_extra_arguments() {
+    let result = run_module(
+        r#"package main
+        func sum(xs ...int) int {
+            return 0
+        }
+        func test() int {
+            return sum(1, []int{2}...)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ArgumentCount { expected: 1, found: 2 })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    goroutine_runs_concurrently,
+    r#"package main
+    func test() int {
+        done := false
+        total := 0
+        go func(n int) {
+            for i := 1; i <= n; i++ {
+                total += i
+            }
+            done = true
+        }(10)
+        for !done {
+        }
+        return total
+    }"#,
+    Value::Int(55)
+);
+
+test_module!(
+    goroutine_arguments_evaluated_by_caller,
+    r#"package main
+    type Box struct {
+        value int
+        done bool
+    }
+    func (b *Box) set(value int) {
+        b.value = value
+        b.done = true
+    }
+    func test() int {
+        b := &Box{}
+        x := 1
+        go b.set(x * 10)
+        x = 2
+        for !b.done {
+        }
+        return b.value + x
+    }"#,
+    Value::Int(12)
+);
+
+test_module!(
+    operands_evaluated_left_to_right,
+    r#"package main
+    var order string
+    func f(s string) int {
+        for i := 0; i < 3; i++ {
+            order += s
+        }
+        return len(order)
+    }
+    func sum(a, b int) int {
+        return a + b
+    }
+    func test() string {
+        ch := make(chan string, 2)
+        ch <- "x"
+        ch <- "y"
+        received := <-ch + <-ch
+        if f("a")+f("b") > sum(f("c"), f("d")) {
+            return "wrong"
+        }
+        return order + " " + received
+    }"#,
+    Value::String("aaabbbcccddd xy".to_string())
+);
+
+test_module!(
+    main_does_not_wait_for_goroutines,
+    r#"package main
+    func spin() {
+        for {
+        }
+    }
+    func test() int {
+        go spin()
+        go spin()
+        return 1
+    }"#,
+    Value::Int(1)
+);
+
+#[tokio::test]
+async fn goroutine_failure_ends_program() {
+    let result = run_module(
+        r#"package main
+        func fail() {
+            var p *int
+            *p = 1
+        }
+        func test() {
+            go fail()
+            for {
+            }
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: invalid memory address or nil pointer dereference\")))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    unbuffered_channel_hands_over_values,
+    r#"package main
+    func squares(out chan<- int, n int) {
+        for i := 1; i <= n; i++ {
+            out <- i * i
+        }
+        close(out)
+    }
+    func test() int {
+        ch := make(chan int)
+        go squares(ch, 4)
+        total := 0
+        for v := range ch {
+            total = total*100 + v
+        }
+        return total
+    }"#,
+    Value::Int(1040916)
+);
+
+test_module!(
+    buffered_channel_len_and_cap,
+    r#"package main
+    func test() int {
+        ch := make(chan string, 3)
+        ch <- "a"
+        ch <- "b"
+        first := len(ch)*10 + cap(ch)
+        <-ch
+        return first*10 + len(ch)
+    }"#,
+    Value::Int(231)
+);
+
+test_module!(
+    receive_from_closed_channel,
+    r#"package main
+    func test() int {
+        ch := make(chan int, 1)
+        ch <- 7
+        close(ch)
+        v, ok := <-ch
+        w, more := <-ch
+        if !ok || more {
+            return -1
+        }
+        return v*10 + w
+    }"#,
+    Value::Int(70)
+);
+
+test_module!(
+    channel_synchronises_goroutines,
+    r#"package main
+    func test() int {
+        results := make(chan int)
+        for i := 1; i <= 3; i++ {
+            go func() {
+                results <- i
+            }()
+        }
+        return <-results + <-results + <-results
+    }"#,
+    Value::Int(6)
+);
+
+test_module!(
+    nil_channel_zero_value,
+    r#"package main
+    func test() bool {
+        var ch chan int
+        var r <-chan int = make(chan int)
+        return ch == nil && len(ch) == 0 && r != nil
+    }"#,
+    Value::Boolean(true)
+);
+
+#[tokio::test]
+async fn send_on_closed_channel() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            ch := make(chan int, 1)
+            close(ch)
+            ch <- 1
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"send on closed channel\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn close_of_closed_channel() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            ch := make(chan int)
+            close(ch)
+            close(ch)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"close of closed channel\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn closing_wakes_blocked_sender() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            ch := make(chan int)
+            go func() {
+                close(ch)
+            }()
+            ch <- 1
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"send on closed channel\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn receive_only_channel_cannot_send() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            var ch <-chan int = make(chan int, 1)
+            ch <- 1
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ChannelDirection { operation: \"send to\", type: Channel(Receive, Int) })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn receive_only_parameter_cannot_send() {
+    let result = run_module(
+        r#"package main
+        func bad(in <-chan int) {
+            in <- 1
+        }
+        func test() {
+            bad(make(chan int, 1))
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ChannelDirection { operation: \"send to\", type: Channel(Receive, Int) })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn receive_only_result_cannot_send() {
+    let result = run_module(
+        r#"package main
+        func source() <-chan int {
+            return make(chan int, 1)
+        }
+        func test() {
+            ch := source()
+            ch <- 1
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ChannelDirection { operation: \"send to\", type: Channel(Receive, Int) })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn all_goroutines_asleep() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            ch := make(chan int)
+            go func() {
+                ch <- 1
+            }()
+            <-ch
+            <-ch
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(Deadlock)", format!("{:?}", result));
+}
+
+test_module!(
+    select_default_when_nothing_ready,
+    r#"package main
+    func test() int {
+        ch := make(chan int)
+        select {
+        case v := <-ch:
+            return v
+        default:
+            return -1
+        }
+    }"#,
+    Value::Int(-1)
+);
+
+test_module!(
+    select_nil_channel_never_ready,
+    r#"package main
+    func test() int {
+        var never chan int
+        ready := make(chan int, 1)
+        ready <- 2
+        select {
+        case never <- 1:
+            return 1
+        case v := <-ready:
+            return v
+        }
+    }"#,
+    Value::Int(2)
+);
+
+test_module!(
+    select_chooses_randomly_among_ready_cases,
+    r#"package main
+    func test() bool {
+        a := make(chan int, 1)
+        b := make(chan int, 1)
+        fromA, fromB := 0, 0
+        for i := 0; i < 200; i++ {
+            a <- 1
+            b <- 1
+            select {
+            case <-a:
+                fromA++
+                <-b
+            case <-b:
+                fromB++
+                <-a
+            }
+        }
+        return fromA > 0 && fromB > 0
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    select_waits_for_first_ready_case,
+    r#"package main
+    func test() int {
+        results := make(chan int)
+        cancel := make(chan bool)
+        go func() {
+            close(cancel)
+        }()
+        select {
+        case v := <-results:
+            return v
+        case _, ok := <-cancel:
+            if ok {
+                return 1
+            }
+            return 0
+        }
+    }"#,
+    Value::Int(0)
+);
+
+test_module!(
+    select_sends_until_cancelled,
+    r#"package main
+    func generate(out chan<- int, quit <-chan bool, done chan<- int) {
+        n := 0
+        for {
+            select {
+            case out <- n:
+                n++
+            case <-quit:
+                done <- n
+                return
+            }
+        }
+    }
+    func test() int {
+        out := make(chan int)
+        quit := make(chan bool)
+        done := make(chan int)
+        go generate(out, quit, done)
+        total := 0
+        for i := 0; i < 4; i++ {
+            total += <-out
+        }
+        quit <- true
+        return total*10 + <-done
+    }"#,
+    Value::Int(64)
+);
+
+test_module!(
+    break_out_of_select,
+    r#"package main
+    func test() int {
+        ch := make(chan int, 1)
+        count := 0
+    loop:
+        for {
+            ch <- count
+            select {
+            case v := <-ch:
+                if v == 3 {
+                    break loop
+                }
+                count++
+                break
+            }
+        }
+        return count
+    }"#,
+    Value::Int(3)
+);
+
+#[tokio::test]
+async fn empty_select_blocks_forever() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            select {}
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(Deadlock)", format!("{:?}", result));
+}
+
+test_module!(
+    deferred_calls_run_in_reverse_order,
+    r#"package main
+    func record(log *string, s string) {
+        *log += s
+    }
+    func run(log *string) {
+        for _, s := range []string{"a", "b", "c"} {
+            defer record(log, s)
+        }
+        record(log, "-")
+    }
+    func test() string {
+        log := ""
+        run(&log)
+        return log
+    }"#,
+    Value::String("-cba".to_string())
+);
+
+test_module!(
+    deferred_arguments_are_evaluated_when_deferred,
+    r#"package main
+    func test() (n int) {
+        x := 1
+        defer func(v int) {
+            n = v*10 + x
+        }(x)
+        x = 2
+        return 0
+    }"#,
+    Value::Int(12)
+);
+
+test_module!(
+    deferred_call_changes_named_result,
+    r#"package main
+    func double() (n int) {
+        defer func() {
+            n *= 2
+        }()
+        return 21
+    }
+    func test() int {
+        return double()
+    }"#,
+    Value::Int(42)
+);
+
+test_module!(
+    recover_stops_panic,
+    r#"package main
+    type Error struct {
+        msg string
+    }
+    func (e Error) Error() string {
+        return e.msg
+    }
+    func divide(a int, b int) (q int, err error) {
+        defer func() {
+            if r := recover(); r != nil {
+                err = Error{"cannot divide"}
+            }
+        }()
+        return a / b, nil
+    }
+    func test() string {
+        q, err := divide(7, 2)
+        if err != nil || q != 3 {
+            return "wrong"
+        }
+        q, err = divide(1, 0)
+        return err.Error()
+    }"#,
+    Value::String("cannot divide".to_string())
+);
+
+test_module!(
+    recovered_function_returns_zero_values,
+    r#"package main
+    func f() (int, string) {
+        defer func() {
+            recover()
+        }()
+        panic("boom")
+    }
+    func test() int {
+        n, s := f()
+        if s != "" {
+            return -1
+        }
+        return n
+    }"#,
+    Value::Int(0)
+);
+
+test_module!(
+    recover_gives_panic_value,
+    r#"package main
+    func test() (r any) {
+        defer func() {
+            r = recover()
+        }()
+        panic(42)
+    }"#,
+    Value::Int(42)
+);
+
+test_module!(
+    recover_without_panic_is_nil,
+    r#"package main
+    func test() (r any) {
+        defer func() {
+            r = recover()
+        }()
+        r = 1
+        return
+    }"#,
+    Value::Nil
+);
+
+test_module!(
+    recovered_runtime_panic_is_an_error,
+    r#"package main
+    func test() (message string) {
+        defer func() {
+            r := recover()
+            if _, ok := r.(string); ok {
+                message = "a string"
+                return
+            }
+            message = r.(error).Error()
+        }()
+        s := []int{1}
+        i := 3
+        s[i] = 2
+        return
+    }"#,
+    Value::String("runtime error: index out of range [3] with length 1".into())
+);
+
+test_module!(
+    deferred_calls_run_while_unwinding,
+    r#"package main
+    func inner(n *int) {
+        defer func() {
+            *n += 1
+        }()
+        panic("inner")
+    }
+    func middle(n *int) {
+        defer func() {
+            *n += 10
+        }()
+        inner(n)
+        *n += 1000
+    }
+    func test() (n int) {
+        defer func() {
+            recover()
+        }()
+        middle(&n)
+        return
+    }"#,
+    Value::Int(11)
+);
+
+#[tokio::test]
+async fn recover_only_works_directly_in_deferred_function() {
+    let result = run_module(
+        r#"package main
+        func helper() any {
+            return recover()
+        }
+        func test() {
+            defer func() {
+                helper()
+            }()
+            defer recover()
+            panic("unrecovered")
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(String(\"unrecovered\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn panic_in_deferred_call_replaces_panic() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            defer func() {
+                panic("second")
+            }()
+            panic("first")
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(Panic(String(\"second\")))", format!("{:?}", result));
+}
+
+#[tokio::test]
+async fn panic_with_nil() {
+    let result = run_module(
+        r#"package main
+        func test() {
+            panic(nil)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"panic called with nil argument\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn integer_divide_by_zero() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            x := 0
+            return 1 % x
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: integer divide by zero\")))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn uncaught_panic_traces_calls() -> Result<()> {
+    let source = r#"package main
+        type Error struct {
+            msg string
+        }
+        func (e *Error) Error() string {
+            return "failed: " + e.msg
+        }
+        func fail(n int) {
+            if n == 0 {
+                panic(&Error{"deep"})
+            }
+            fail(n - 1)
+        }
+        func test() {
+            defer func() {}()
+            fail(1)
+        }"#;
+    let linker = Linker::bootstrap(MemoryLoader::new([("main", source)])).await?;
+    let failure = match run_in_default_context(linker, "main", "test").await {
+        Ok(value) => panic!("Expected a panic, got {:?}", value),
+        Err(failure) => failure,
+    };
+    assert_eq!(Some("failed: deep"), failure.panic.as_deref());
+    assert_eq!(1, failure.goroutine);
+    let frame = |function: &str, arguments, line| Frame {
+        function: function.to_string(),
+        arguments,
+        line: Some(line),
+    };
+    assert_eq!(
+        vec![
+            frame("main.fail", true, 10),
+            frame("main.fail", true, 12),
+            frame("main.test", false, 16),
+        ],
+        failure.trace
+    );
+    Ok(())
+}
+
+test_module!(
+    const_iota,
+    r#"package main
+    const (
+        A = iota * 10
+        B
+        C
+    )
+    func test() int {
+        return A + B + C
+    }"#,
+    Value::Int(30)
+);
+
+test_module!(
+    const_iota_repeats_type_and_expression,
+    r#"package main
+    type Size int
+    const (
+        _ = iota
+        KB Size = 1 << (10 * iota)
+        MB
+        GB
+    )
+    func test() int {
+        return int(GB / MB)
+    }"#,
+    Value::Int(1024)
+);
+
+test_module!(
+    const_iota_counts_specs_not_names,
+    r#"package main
+    const (
+        a, b = iota, iota + 10
+        c, d
+    )
+    func test() int {
+        return a*1000 + b*100 + c*10 + d
+    }"#,
+    Value::Int(1021)
+);
+
+test_module!(
+    untyped_constants_are_exact,
+    r#"package main
+    const Big = 1 << 100
+    const Small = Big >> 98
+    func test() int {
+        return Small + Big>>97
+    }"#,
+    Value::Int(12)
+);
+
+test_module!(
+    constant_shadowed_by_variable,
+    r#"package main
+    const n = 2
+    func test() int {
+        total := 0
+        for i := 1; i <= 3; i++ {
+            total += n
+            n := i
+            total += n * 10
+        }
+        return total
+    }"#,
+    Value::Int(66)
+);
+
+test_module!(
+    typed_string_and_bool_constants,
+    r#"package main
+    const greeting string = "hello"
+    const (
+        loud = true
+        quiet = !loud
+    )
+    func test() string {
+        if quiet {
+            return greeting
+        }
+        return greeting + "!"
+    }"#,
+    Value::String("hello!".to_string())
+);
+
+test_module!(
+    local_variable_shadows_constant,
+    r#"package main
+    const x = 1 << 100
+    func test() int {
+        x := 5
+        return x + 1
+    }"#,
+    Value::Int(6)
+);
+
+#[tokio::test]
+async fn untyped_constant_overflows_int() {
+    let result = run_module(
+        r#"package main
+        const Big = 1 << 100
+        func test() int {
+            return Big
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ConstantOverflow { value: \"1267650600228229401496703205376\", type: Int })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn typed_constant_overflows_int() {
+    let result = run_module(
+        r#"package main
+        const Max int = 1<<63 - 1
+        const TooBig int = Max + 1
+        func test() int {
+            return TooBig
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ConstantOverflow { value: \"9223372036854775808\", type: Int })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn constant_division_by_zero() {
+    let result = run_module(
+        r#"package main
+        const zero = 0
+        func test() int {
+            return 1 / zero
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(DivisionByZero)", format!("{:?}", result));
+}
+
+#[tokio::test]
+async fn recursive_constant() {
+    let result = run_module(
+        r#"package main
+        const a = b + 1
+        const b = a
+        func test() int {
+            return a
+        }"#,
+    )
+    .await;
+    assert_eq!("Err(InitializationCycle(\"a\"))", format!("{:?}", result));
+}
+
+test_module!(
+    package_variables,
+    r#"package main
+    var count = 3
+    var (
+        total int
+        names = []string{"a", "b"}
+    )
+    func test() int {
+        total += count
+        return total + len(names)
+    }"#,
+    Value::Int(5)
+);
+
+test_module!(
+    package_variables_initialized_in_dependency_order,
+    r#"package main
+    var a = b * 2
+    var b = f()
+    func f() int {
+        return c + 1
+    }
+    var c = 10
+    func test() int {
+        return a
+    }"#,
+    Value::Int(22)
+);
+
+test_module!(
+    init_functions_run_before_main,
+    r#"package main
+    var x int
+    func init() {
+        x = 1
+    }
+    func init() {
+        x = x*10 + 2
+    }
+    func test() int {
+        return x
+    }"#,
+    Value::Int(12)
+);
+
+#[tokio::test]
+async fn imported_packages_initialized_first() -> Result<()> {
+    let result = run_modules([
+        (
+            "main",
+            r#"package main
+            import "config"
+            var fromConfig = config.Value + 1
+            func test() int {
+                return fromConfig + config.Double()
+            }"#,
+        ),
+        (
+            "config",
+            r#"package config
+            var Value int
+            func init() {
+                Value = 42
+            }
+            func Double() int {
+                return Value * 2
+            }"#,
+        ),
+    ])
+    .await?;
+    assert_eq!(Value::Int(127), result);
+    Ok(())
+}
+
+#[tokio::test]
+async fn packages_initialized_in_import_path_order() -> Result<()> {
+    let record = |name: &str| {
+        format!(
+            r#"package {name}
+            import "log"
+            func init() {{
+                log.Record("{name}")
+            }}"#
+        )
+    };
+    let (a, b) = (record("a"), record("b"));
+    let result = run_modules([
+        (
+            "main",
+            r#"package main
+            import "b"
+            import "a"
+            import "log"
+            func init() {
+                log.Record("main")
+            }
+            func test() string {
+                return log.Recorded()
+            }"#,
+        ),
+        ("a", &a),
+        ("b", &b),
+        (
+            "log",
+            r#"package log
+            var recorded string
+            func Record(name string) {
+                recorded = recorded + name + " "
+            }
+            func Recorded() string {
+                return recorded
+            }"#,
+        ),
+    ])
+    .await?;
+    assert_eq!(Value::String("a b main ".to_string()), result);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unexported_names_are_not_visible() {
+    let result = run_modules([
+        (
+            "main",
+            r#"package main
+            import "config"
+            func test() int {
+                return config.secret
+            }"#,
+        ),
+        ("config", "package config\nvar secret = 1"),
+    ])
+    .await;
+    assert_eq!(
+        "Err(Unexported(\"config.secret\"))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn import_cycle() {
+    let result = run_modules([
+        ("main", "package main\nimport \"a\"\nfunc test() {}"),
+        ("a", "package a\nimport \"b\""),
+        ("b", "package b\nimport \"a\""),
+    ])
+    .await;
+    match result {
+        Err(error) => assert_eq!(
+            "Import cycle not allowed: a imports b imports a",
+            error.to_string()
+        ),
+        Ok(value) => panic!("Expected an import cycle: {}", value),
+    }
+}
+
+test_module!(
+    sized_integers_wrap_around,
+    r#"package main
+    func test() int {
+        var small int8 = 127
+        small++
+        var b uint8 = 200
+        b += 100
+        var big uint64 = 1<<64 - 1
+        big++
+        return int(small)*1000 + int(b) + int(big)
+    }"#,
+    Value::Int(-127956)
+);
+
+test_module!(
+    integer_literals,
+    r#"package main
+    func test() int {
+        return 0xFF + 0o17 + 017 + 0b101 + 1_000
+    }"#,
+    Value::Int(1290)
+);
+
+test_module!(
+    untyped_constant_takes_operand_type,
+    r#"package main
+    func test() bool {
+        var x uint64 = 1 << 63
+        var f float32 = 0.5
+        return x+1 > 1<<63 && f*3 == 1.5
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    float_constants_are_exact,
+    r#"package main
+    func test() bool {
+        x, y := 0.1, 0.2
+        return 0.1+0.2 == 0.3 && x+y != 0.3
+    }"#,
+    Value::Boolean(true)
+);
+
+#[tokio::test]
+async fn float32_rounds_to_single_precision() {
+    let result = run_module(
+        r#"package main
+        func test() float64 {
+            var f float32 = 0.1
+            return float64(f)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Ok(Number(float64(0.10000000149011612)))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    conversions_truncate_and_wrap,
+    r#"package main
+    func test() int {
+        f := -3.9
+        n := 300
+        var u uint32 = 1 << 31
+        return int(f)*10000 + int(uint8(n))*10 + int(int32(u)>>31)
+    }"#,
+    Value::Int(-29561)
+);
+
+#[tokio::test]
+async fn constant_conversion_overflows() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            return int(uint8(256))
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ConstantOverflow { value: \"256\", type: Numeric(Uint8) })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn constant_conversion_truncated() {
+    let result = run_module(
+        r#"package main
+        func test() int {
+            return int(2.5)
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ConstantTruncated { value: \"2.5\", type: Int })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn distinct_named_numeric_types() {
+    let result = run_module(
+        r#"package main
+        type Celsius float64
+        type Fahrenheit float64
+        func test() Celsius {
+            c := Celsius(100)
+            f := Fahrenheit(212)
+            return c + f
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeMismatch { left: Named(Celsius), op: Add, right: Named(Fahrenheit) })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn mismatched_sized_integers() {
+    let result = run_module(
+        r#"package main
+        func test() int64 {
+            var a int32 = 1
+            var b int64 = 2
+            return a + b
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeMismatch { left: Numeric(Int32), op: Add, right: Numeric(Int64) })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    methods_on_named_numbers,
+    r#"package main
+    type Celsius float64
+    func (c Celsius) Fahrenheit() float64 {
+        return float64(c*9/5 + 32)
+    }
+    func test() bool {
+        var boiling Celsius = 100
+        return boiling.Fahrenheit() == 212
+    }"#,
+    Value::Boolean(true)
+);
+
+#[tokio::test]
+async fn methods_on_other_named_types_are_unsupported() {
+    for declaration in [
+        "type List []int\nfunc (l List) Sum() int {\nreturn 0\n}",
+        "type Name string\nfunc (n Name) Greet() string {\nreturn \"hi\"\n}",
+        "type Set map[string]bool\nfunc (s Set) Has(k string) bool {\nreturn s[k]\n}",
+    ] {
+        let result = run_module(&format!(
+            "package main\n{}\nfunc test() int {{\nreturn 1\n}}",
+            declaration
+        ))
+        .await;
+        assert_eq!(
+            "Err(UnsupportedFeature(NamedTypeMethods))",
+            format!("{:?}", result)
+        );
+    }
+}
+
+#[tokio::test]
+async fn integer_division_by_zero_panics() {
+    let result = run_module(
+        r#"package main
+        func test() uint8 {
+            var a, b uint8 = 1, 0
+            return a / b
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(Panic(Error(\"runtime error: integer divide by zero\")))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    float_division_by_zero_is_infinite,
+    r#"package main
+    func test() bool {
+        x, zero := 1.0, 0.0
+        inf := x / zero
+        nan := zero / zero
+        return inf > 1e308 && -inf < -1e308 && nan != nan
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    numbers_of_different_types_are_different_keys,
+    r#"package main
+    func test() int {
+        m := map[any]int{}
+        m[1] = 1
+        m[int8(1)] = 2
+        m[1.0] = 3
+        m[float32(1)] = 4
+        m[int8(1)] += 10
+        return len(m)*100 + m[int8(1)]
+    }"#,
+    Value::Int(412)
+);
+
+test_module!(
+    range_over_sized_integer,
+    r#"package main
+    func test() bool {
+        var n uint8 = 3
+        var last uint8
+        for i := range n {
+            last = i
+        }
+        return last == 2
+    }"#,
+    Value::Boolean(true)
+);
+
+#[tokio::test]
+async fn complex_arithmetic() {
+    let result = run_module(
+        r#"package main
+        func test() complex128 {
+            x := 1 + 2i
+            y := complex(3, -1)
+            return x*y + x/(1-1i) - 1.5e3i
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Ok(Number(complex128((4.5-1493.5i))))",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    complex_parts,
+    r#"package main
+    func test() bool {
+        var c complex64 = 3 + 4i
+        r := real(c)
+        var f float32 = r
+        return f == 3 && imag(c) == 4 && complex(r, imag(c)) == c && real(2i) == 0
+    }"#,
+    Value::Boolean(true)
+);
+
+test_module!(
+    complex_constants,
+    r#"package main
+    const c = complex(1, 2) * 1i
+    const r = real(c) / 2
+    func test() bool {
+        var z complex128 = 2
+        return c == -2+1i && r == -1 && z == 2+0i
+    }"#,
+    Value::Boolean(true)
+);
+
+#[tokio::test]
+async fn complex64_prints_single_precision() {
+    let result = run_module(
+        r#"package main
+        func test() complex64 {
+            return 0.1 - 2e-7i
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Ok(Number(complex64((0.1-2e-07i))))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn complex_numbers_are_unordered() {
+    let result = run_module(
+        r#"package main
+        func test() bool {
+            x := 1i
+            return x < 2i
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(TypeOpMismatch { op: Lt, type: Numeric(Complex128) })",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn complex_division_by_zero() {
+    let result = run_module(
+        r#"package main
+        func test() complex128 {
+            var zero complex128
+            return (1 + 2i) / zero
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Ok(Number(complex128((+Inf+Infi))))",
+        format!("{:?}", result)
+    );
+}
+
+#[tokio::test]
+async fn imaginary_constant_truncated() {
+    let result = run_module(
+        r#"package main
+        func test() float64 {
+            var f float64 = 1 + 1i
+            return f
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        "Err(ConstantTruncated { value: \"(1+1i)\", type: Numeric(Float64) })",
+        format!("{:?}", result)
+    );
+}
+
+test_module!(
+    complex_map_keys,
+    r#"package main
+    func test() int {
+        m := map[complex128]int{1 + 2i: 1}
+        m[complex(1, 2)] += 10
+        m[2i] = 100
+        return len(m)*1000 + m[1+2i]
+    }"#,
+    Value::Int(2011)
+);
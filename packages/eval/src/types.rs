@@ -0,0 +1,186 @@
+use crate::extensions::TypeExt;
+use crate::structs::Struct;
+use crate::{EvalResult, Number, Numeric, PackageContext, RuntimeResult, Type, Value};
+use gor_ast::declaration::TypeSpec;
+use gor_ast::func::SourceFunction;
+use gor_ast::name::Name;
+use gor_ast::types::InnerType;
+use std::fmt::{Debug, Display, Formatter};
+
+/// A struct type declared in Go source with `type T struct { ... }`, or a numeric type declared as
+/// `type T float64`, along with the package it was declared in.
+#[derive(Clone)]
+pub struct NamedType {
+    package: PackageContext,
+    name: Name,
+}
+
+impl NamedType {
+    /// The type declared in `package` with this name
+    pub(crate) fn new(package: PackageContext, name: Name) -> Self {
+        NamedType { package, name }
+    }
+
+    pub fn name(&self) -> Name {
+        self.name
+    }
+
+    fn spec(&self) -> &TypeSpec<'_> {
+        match self.package.source().type_spec(self.name) {
+            Some(spec) => spec,
+            None => unreachable!("named types are only made from their declarations"),
+        }
+    }
+
+    /// Identifies the declaration, so that values of different types are never the same
+    pub(crate) fn id(&self) -> usize {
+        self.spec() as *const TypeSpec as usize
+    }
+
+    pub(crate) fn package(&self) -> PackageContext {
+        self.package.clone()
+    }
+
+    /// The kind of number that values of the type are, unless it's a struct type
+    pub(crate) fn numeric(&self) -> Option<Numeric> {
+        let spec = self.spec();
+        match &spec.r#type.inner {
+            InnerType::Struct(_) => None,
+            _ => spec.r#type.resolve(&self.package).ok()?.numeric(),
+        }
+    }
+
+    /// The method with this name declared on the type, with either a value or a pointer receiver
+    pub(crate) fn method(&self, name: Name) -> Option<&SourceFunction<'_>> {
+        self.package.source().method(self.name, name)
+    }
+
+    /// The name and type of each field, in declaration order
+    pub(crate) fn fields(&self) -> RuntimeResult<Vec<(Name, Type)>> {
+        match &self.spec().r#type.inner {
+            InnerType::Struct(fields) => fields
+                .iter()
+                .map(|field| Ok((field.name, field.r#type.resolve(&self.package)?)))
+                .collect(),
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// A struct with every field set to its own zero value, or zero for a numeric type
+    pub(crate) fn zero_value(&self) -> EvalResult {
+        if self.numeric().is_some() {
+            return Ok(Number::integer(&Type::Named(self.clone()), 0)?.value());
+        }
+        let fields = self
+            .fields()?
+            .into_iter()
+            .map(|(name, r#type)| Ok((name, r#type.zero_value()?)))
+            .collect::<RuntimeResult<_>>()?;
+        Ok(Value::Struct(Struct::new(self.clone(), fields)))
+    }
+}
+
+impl Debug for NamedType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl PartialEq for NamedType {
+    fn eq(&self, other: &Self) -> bool {
+        self.package == other.package && self.name == other.name
+    }
+}
+
+impl Eq for NamedType {}
+
+/// The methods a value must have to be stored in a variable of an interface type.
+///
+/// Methods are matched by name alone.
+#[derive(Clone, PartialEq, Eq)]
+pub struct InterfaceType {
+    name: Option<Name>,
+    methods: Vec<Name>,
+}
+
+impl InterfaceType {
+    pub(crate) fn new(name: Option<Name>, mut methods: Vec<Name>) -> Self {
+        methods.sort_by_key(|method| method.to_string());
+        methods.dedup();
+        InterfaceType { name, methods }
+    }
+
+    /// The same methods, declared as a new type
+    pub(crate) fn named(self, name: Name) -> Self {
+        InterfaceType {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    pub(crate) fn methods(&self) -> &[Name] {
+        &self.methods
+    }
+
+    /// Whether the value has every method in the interface.
+    ///
+    /// Any interface may hold `nil`.
+    pub(crate) fn is_implemented_by(&self, value: &Value) -> bool {
+        *value == Value::Nil || self.missing_method(value).is_none()
+    }
+
+    /// The first method the value doesn't have, if any
+    pub(crate) fn missing_method(&self, value: &Value) -> Option<Name> {
+        self.methods
+            .iter()
+            .find(|method| !has_method(value, **method))
+            .copied()
+    }
+}
+
+/// A struct has the methods declared with a value receiver, while a pointer to a struct has every
+/// method declared on the struct type.
+fn has_method(value: &Value, name: Name) -> bool {
+    let (named, pointer) = match value {
+        Value::Struct(s) => (s.r#type(), false),
+        Value::Number(number) => match number.named() {
+            Some(named) => (named, false),
+            None => return false,
+        },
+        Value::Pointer(pointer) => match pointer.pointee() {
+            Type::Named(named) => (named.clone(), true),
+            _ => return false,
+        },
+        Value::Error(_) => return name == "Error".into(),
+        _ => return false,
+    };
+    match named.method(name).and_then(|method| method.receiver_base()) {
+        Some((_, pointer_receiver)) => pointer || !pointer_receiver,
+        None => false,
+    }
+}
+
+impl Debug for InterfaceType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for InterfaceType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{}", name),
+            None if self.methods.is_empty() => write!(f, "interface {{}}"),
+            None => {
+                write!(f, "interface {{ ")?;
+                for (index, method) in self.methods.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}()", method)?;
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
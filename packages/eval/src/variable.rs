@@ -0,0 +1,66 @@
+use crate::Value;
+use std::fmt::{Formatter, Pointer};
+use std::sync::{Arc, Mutex};
+
+/// Storage for a single Go variable.
+///
+/// Clones share the same storage, so every scope that can see a variable sees every update to it.
+#[derive(Debug, Clone)]
+pub struct Variable {
+    value: Arc<Mutex<Value>>,
+    /// Whether the variable was declared with an interface type, so that it may hold values of
+    /// different types
+    interface: bool,
+}
+
+impl Variable {
+    pub(crate) fn new(value: Value) -> Self {
+        Variable {
+            value: Arc::new(Mutex::new(value)),
+            interface: false,
+        }
+    }
+
+    /// The same variable, declared with an interface type or not
+    pub(crate) fn interface(self, interface: bool) -> Self {
+        Variable { interface, ..self }
+    }
+
+    pub(crate) fn is_interface(&self) -> bool {
+        self.interface
+    }
+
+    /// A new variable of the same type, holding the same value
+    pub(crate) fn copy(&self) -> Self {
+        Variable::new(self.get()).interface(self.interface)
+    }
+
+    pub(crate) fn get(&self) -> Value {
+        self.value.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set(&self, value: Value) {
+        *self.value.lock().unwrap() = value;
+    }
+
+    /// Whether both variables share the same storage
+    pub(crate) fn ptr_eq(&self, other: &Variable) -> bool {
+        Arc::ptr_eq(&self.value, &other.value)
+    }
+
+    /// Where the value is stored, which is unique to this variable while it exists
+    pub(crate) fn address(&self) -> usize {
+        Arc::as_ptr(&self.value) as usize
+    }
+
+    /// Run `f` with exclusive access to the stored value
+    pub(crate) fn with_mut<R>(&self, f: impl FnOnce(&mut Value) -> R) -> R {
+        f(&mut self.value.lock().unwrap())
+    }
+}
+
+impl Pointer for Variable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Pointer::fmt(&Arc::as_ptr(&self.value), f)
+    }
+}
@@ -1,6 +1,5 @@
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     missing_debug_implementations,
@@ -9,7 +8,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unreachable_pub,
     unused,
@@ -23,7 +21,6 @@
 #![doc = include_str!("../README.md")]
 
 use gor_ast::name::Name;
-use gor_loader::file_loader::FileLoader;
 use gor_loader::{Loader, LoaderError, LoaderResult, ModuleDescriptor};
 use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
@@ -39,6 +36,8 @@ pub enum LinkerError {
     Tokio(#[from] JoinError),
     #[error("Module not found: {0}")]
     NotFound(Name),
+    #[error("Import cycle not allowed: {0}")]
+    ImportCycle(String),
 }
 
 pub type LinkerResult<T> = Result<T, LinkerError>;
@@ -46,10 +45,12 @@ pub type LinkerResult<T> = Result<T, LinkerError>;
 #[derive(Debug)]
 pub struct Linker {
     modules: HashMap<Name, Box<ModuleDescriptor>>,
+    /// Every package, in the order they're initialized
+    order: Vec<Name>,
 }
 
-async fn do_load(
-    loader: FileLoader,
+async fn do_load<L: Loader>(
+    loader: L,
     name: Name,
     sender: UnboundedSender<LoaderResult<ModuleDescriptor>>,
 ) {
@@ -58,7 +59,9 @@ async fn do_load(
 }
 
 impl Linker {
-    pub async fn bootstrap(loader: FileLoader) -> LinkerResult<Linker> {
+    pub async fn bootstrap<L: Loader + Clone + Send + Sync + 'static>(
+        loader: L,
+    ) -> LinkerResult<Linker> {
         let (sender, mut receiver) = unbounded_channel::<LoaderResult<ModuleDescriptor>>();
         let mut modules: HashMap<Name, Box<ModuleDescriptor>> = HashMap::new();
         let main: Name = "main".into();
@@ -86,7 +89,13 @@ impl Linker {
             }
         }
 
-        Ok(Linker { modules })
+        let order = initialization_order(&modules)?;
+        Ok(Linker { modules, order })
+    }
+
+    /// Every linked package, each after all of the packages it imports
+    pub fn initialization_order(&self) -> &[Name] {
+        &self.order
     }
 
     pub fn lookup(&self, name: Name) -> LinkerResult<&ModuleDescriptor> {
@@ -96,3 +105,45 @@ impl Linker {
             .map(|boxed| boxed.deref())
     }
 }
+
+/// The order in which Go initializes packages: sorted by import path, repeatedly the first that
+/// hasn't been initialized but whose imports all have
+fn initialization_order(modules: &HashMap<Name, Box<ModuleDescriptor>>) -> LinkerResult<Vec<Name>> {
+    let imports = |name: &Name| -> &[Name] {
+        modules
+            .get(name)
+            .map(|module| module.module().imports.as_slice())
+            .unwrap_or_default()
+    };
+    let mut remaining: Vec<Name> = modules.keys().copied().collect();
+    remaining.sort_by_key(ToString::to_string);
+    let mut order: Vec<Name> = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let ready = remaining
+            .iter()
+            .position(|name| imports(name).iter().all(|import| order.contains(import)));
+        let Some(index) = ready else {
+            // Each remaining package imports another that remains, which eventually leads back
+            let mut path = vec![remaining[0]];
+            loop {
+                let current = path[path.len() - 1];
+                let Some(next) = imports(&current)
+                    .iter()
+                    .find(|import| !order.contains(import))
+                else {
+                    break;
+                };
+                if let Some(start) = path.iter().position(|name| name == next) {
+                    path.drain(..start);
+                    path.push(*next);
+                    break;
+                }
+                path.push(*next);
+            }
+            let path: Vec<String> = path.iter().map(ToString::to_string).collect();
+            return Err(LinkerError::ImportCycle(path.join(" imports ")));
+        };
+        order.push(remaining.remove(index));
+    }
+    Ok(order)
+}
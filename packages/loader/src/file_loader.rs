@@ -1,9 +1,6 @@
-use crate::{InnerModuleDescriptor, Loader, LoaderError, LoaderResult, ModuleDescriptor};
+use crate::{Loader, LoaderError, LoaderResult, ModuleDescriptor};
 use async_trait::async_trait;
-use gor_ast::module::SourceModule;
 use gor_ast::name::Name;
-use gor_ast::Parseable;
-use gor_parse::{parse, Rule};
 use std::path::PathBuf;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
@@ -17,19 +14,6 @@ impl FileLoader {
     pub fn new<T: Into<PathBuf>>(file: T) -> Self {
         Self { file: file.into() }
     }
-    fn load_from_string(input: String, module: Name) -> LoaderResult<ModuleDescriptor> {
-        let descriptor = InnerModuleDescriptor::try_new(input, |input| {
-            parse(Rule::module, input).map_or_else(
-                |e| Err(LoaderError::ParseError(e)),
-                |p| SourceModule::parse(p).map_err(Into::into),
-            )
-        })?;
-        if descriptor.borrow_dependent().package == module {
-            Ok(ModuleDescriptor(descriptor))
-        } else {
-            Err(LoaderError::ModuleNotFound(module))
-        }
-    }
 }
 
 #[async_trait]
@@ -41,8 +25,9 @@ impl Loader for FileLoader {
         if module == "main".into() {
             path = Some(self.file.to_path_buf());
         }
-        if let Some(parent) = self.file.parent() {
-            let mod_file = parent.join(module).with_extension(".go");
+        // `main` is always the file we were given, even if there's a `main.go` beside it
+        if let Some(parent) = self.file.parent().filter(|_| path.is_none()) {
+            let mod_file = parent.join(module).with_extension("go");
             if mod_file.exists() {
                 path = Some(mod_file)
             } else {
@@ -56,6 +41,6 @@ impl Loader for FileLoader {
 
         let mut file = File::open(path).await?;
         file.read_to_string(&mut input).await?;
-        FileLoader::load_from_string(input, module)
+        ModuleDescriptor::from_source(input, module)
     }
 }
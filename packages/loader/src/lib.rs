@@ -1,6 +1,5 @@
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     missing_debug_implementations,
@@ -9,7 +8,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unreachable_pub,
     unused,
@@ -26,13 +24,15 @@ use async_trait::async_trait;
 use gor_ast::module::SourceModule;
 use gor_ast::name::Name;
 use gor_ast::AstError;
+use gor_ast::Parseable;
 use gor_core::parse_error::{parse_enum, InternalError};
-use gor_parse::ParseError;
+use gor_parse::{parse, ParseError, Rule};
 use self_cell::self_cell;
 use std::io;
 use thiserror::Error;
 
 pub mod file_loader;
+pub mod memory_loader;
 
 #[derive(Error, Debug)]
 pub enum LoaderError {
@@ -87,7 +87,21 @@ self_cell!(
 pub struct ModuleDescriptor(InnerModuleDescriptor);
 
 impl ModuleDescriptor {
-    pub fn module(&self) -> &SourceModule {
+    pub fn module(&self) -> &SourceModule<'_> {
         self.0.borrow_dependent()
     }
+
+    fn from_source(input: String, module: Name) -> LoaderResult<ModuleDescriptor> {
+        let descriptor = InnerModuleDescriptor::try_new(input, |input| {
+            parse(Rule::module, input).map_or_else(
+                |e| Err(LoaderError::ParseError(e)),
+                |p| SourceModule::parse(p).map_err(Into::into),
+            )
+        })?;
+        if descriptor.borrow_dependent().package == module {
+            Ok(ModuleDescriptor(descriptor))
+        } else {
+            Err(LoaderError::ModuleNotFound(module))
+        }
+    }
 }
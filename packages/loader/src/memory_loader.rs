@@ -0,0 +1,37 @@
+use crate::{Loader, LoaderError, LoaderResult, ModuleDescriptor};
+use async_trait::async_trait;
+use gor_ast::name::Name;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Loads modules from source held in memory, keyed by package name.
+///
+/// Useful for tests, where writing each module out to a file would be a chore.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLoader {
+    sources: Arc<HashMap<Name, String>>,
+}
+
+impl MemoryLoader {
+    pub fn new<'s, T: IntoIterator<Item = (&'s str, &'s str)>>(sources: T) -> Self {
+        Self {
+            sources: Arc::new(
+                sources
+                    .into_iter()
+                    .map(|(name, source)| (Name::from(name), source.to_string()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl Loader for MemoryLoader {
+    async fn load_module(&self, module: Name) -> LoaderResult<ModuleDescriptor> {
+        let source = self
+            .sources
+            .get(&module)
+            .ok_or(LoaderError::ModuleNotFound(module))?;
+        ModuleDescriptor::from_source(source.clone(), module)
+    }
+}
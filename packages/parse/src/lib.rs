@@ -1,6 +1,5 @@
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     missing_debug_implementations,
@@ -9,7 +8,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unreachable_pub,
     unused,
@@ -24,7 +22,7 @@
 
 use lazy_static::lazy_static;
 use pest::iterators::Pairs;
-use pest::prec_climber::{Assoc, Operator, PrecClimber};
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest::Parser;
 use thiserror::Error;
 
@@ -43,16 +41,16 @@ pub use implementation::Rule;
 #[non_exhaustive]
 pub enum ParseError {
     #[error(transparent)]
-    PestError(#[from] pest::error::Error<Rule>),
+    PestError(#[from] Box<pest::error::Error<Rule>>),
 }
 
-pub fn parse(rule: Rule, input: &str) -> Result<Pairs<Rule>, ParseError> {
-    Ok(ModuleParser::parse(rule, input)?)
+pub fn parse(rule: Rule, input: &str) -> Result<Pairs<'_, Rule>, ParseError> {
+    Ok(ModuleParser::parse(rule, input).map_err(Box::new)?)
 }
 
 macro_rules! l {
     ($rule:ident) => {
-        Operator::new(Rule::$rule, Assoc::Left)
+        Op::infix(Rule::$rule, Assoc::Left)
     };
 }
 
@@ -69,13 +67,24 @@ lazy_static! {
     /// |    2       |    &&                     |
     /// |    1       |    ||                     |
     ///
+    /// Unary operators bind more tightly than any binary operator, and postfix operators like type
+    /// assertions, indexes, slices and calls bind more tightly still.
+    ///
     /// Gór adds `.` as the highest precedence binary operator for AST parsing
-    pub static ref PRECEDENCE: PrecClimber<Rule> = PrecClimber::new(vec![
-        l!(dot),
-        l!(bool_or),
-        l!(bool_and),
-        l!(eq) | l!(neq) | l!(lt) | l!(leq) | l!(gt) | l!(geq),
-        l!(add) | l!(sub) | l!(bit_or) | l!(bit_xor),
-        l!(mul) | l!(div) | l!(modulo) | l!(shl) | l!(shr) | l!(bit_and) | l!(bit_clear),
-    ]);
+    pub static ref PRECEDENCE: PrattParser<Rule> = PrattParser::new()
+        .op(l!(bool_or))
+        .op(l!(bool_and))
+        .op(l!(eq) | l!(neq) | l!(lt) | l!(leq) | l!(gt) | l!(geq))
+        .op(l!(add) | l!(sub) | l!(bit_or) | l!(bit_xor))
+        .op(l!(mul) | l!(div) | l!(modulo) | l!(shl) | l!(shr) | l!(bit_and) | l!(bit_clear))
+        .op(Op::prefix(Rule::receive)
+            | Op::prefix(Rule::negate)
+            | Op::prefix(Rule::not)
+            | Op::prefix(Rule::address)
+            | Op::prefix(Rule::deref))
+        .op(Op::postfix(Rule::type_assertion)
+            | Op::postfix(Rule::index)
+            | Op::postfix(Rule::slice)
+            | Op::postfix(Rule::arguments))
+        .op(l!(dot));
 }
@@ -1,5 +1,6 @@
 use thiserror::Error;
 
+use gor_eval::Failure;
 use gor_eval::RuntimeError;
 use gor_eval::Value;
 use gor_linker::LinkerError;
@@ -12,6 +13,9 @@ pub enum GoError {
     /// Something went wrong at runtime
     #[error("Runtime Error")]
     RuntimeError(#[from] RuntimeError),
+    /// A panic that no deferred call recovered from, along with the goroutine it ended
+    #[error("panic: {}", .0.panic.as_deref().unwrap_or_default())]
+    Panic(Failure),
     /// Something happened trying to load the module
     #[error("Error Loading Module")]
     LoaderError(#[from] LoaderError),
@@ -1,6 +1,5 @@
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     missing_debug_implementations,
@@ -10,7 +9,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unreachable_pub,
     unused,
@@ -23,8 +21,8 @@
 #![forbid(unsafe_code)]
 #![doc = include_str!("../README.md")]
 
-use crate::error::GoResult;
-use gor_eval::execute_in_default_context;
+use crate::error::{GoError, GoResult};
+use gor_eval::run_in_default_context;
 use gor_linker::Linker;
 use gor_loader::file_loader::FileLoader;
 use std::path::PathBuf;
@@ -41,7 +39,11 @@ pub mod error;
 pub async fn exec<T: Into<PathBuf>>(main: T) -> GoResult {
     let loader = FileLoader::new(main);
     let linker = Linker::bootstrap(loader).await?;
-    Ok(execute_in_default_context(linker, "main", "main").await?)
+    match run_in_default_context(linker, "main", "main").await {
+        Ok(value) => Ok(value),
+        Err(failure) if failure.panic.is_some() => Err(GoError::Panic(failure)),
+        Err(failure) => Err(failure.error.into()),
+    }
 }
 
 /// Utilities for integration testing
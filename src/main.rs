@@ -1,6 +1,5 @@
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     missing_debug_implementations,
@@ -9,7 +8,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unreachable_pub,
     unused,
@@ -21,11 +19,12 @@
 )]
 #![doc = include_str!("../README.md")]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 
+use gor::error::GoError;
 use gor::exec;
-use gor_eval::Value;
+use gor_eval::{Failure, RuntimeError, Value, MAX_DEPTH};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -37,12 +36,41 @@ struct Opt {
 async fn main() -> Result<(), anyhow::Error> {
     let opts = Opt::from_args();
 
-    let result = exec(&opts.input).await?;
-    if let Value::Void = result {
-        Ok(())
-    } else if let Value::Int(rv) = result {
-        exit(rv as i32);
-    } else {
-        Ok(())
+    let result = match exec(&opts.input).await {
+        Ok(result) => result,
+        Err(GoError::Panic(failure)) => {
+            report(&opts.input, &failure);
+            exit(2)
+        }
+        // Deadlocks and stack overflows are fatal errors in Go, which nothing can recover from
+        Err(GoError::RuntimeError(
+            error @ (RuntimeError::Deadlock | RuntimeError::StackOverflow),
+        )) => {
+            if let RuntimeError::StackOverflow = error {
+                eprintln!("runtime: goroutine stack exceeds {}-call limit", MAX_DEPTH);
+            }
+            eprintln!("fatal error: {}", error);
+            exit(2)
+        }
+        Err(error) => return Err(error.into()),
+    };
+    // Like Go, exit as soon as main returns rather than waiting for other goroutines
+    match result {
+        Value::Int(rv) => exit(rv as i32),
+        _ => exit(0),
+    }
+}
+
+/// Print an unrecovered panic the way Go does, along with the calls it unwound out of
+fn report(input: &Path, failure: &Failure) {
+    let input = input.canonicalize().unwrap_or_else(|_| input.to_path_buf());
+    eprintln!("panic: {}", failure.panic.as_deref().unwrap_or_default());
+    eprintln!();
+    eprintln!("goroutine {} [running]:", failure.goroutine);
+    for frame in &failure.trace {
+        eprintln!("{}", frame);
+        if let Some(line) = frame.line {
+            eprintln!("\t{}:{}", input.display(), line);
+        }
     }
 }
@@ -0,0 +1,38 @@
+package main
+
+type Matrix [2][2]int
+
+func multiply(a, b Matrix) Matrix {
+    var result Matrix
+    for i := 0; i < 2; i++ {
+        for j := 0; j < 2; j++ {
+            for k := 0; k < 2; k++ {
+                result[i][j] += a[i][k] * b[k][j]
+            }
+        }
+    }
+    return result
+}
+
+func checksum(data [8]int) int {
+    sum := 0
+    for i := 0; i < len(data); i++ {
+        sum = (sum*31 + data[i]) % 65521
+    }
+    return sum
+}
+
+func main() {
+    fib := Matrix{[2]int{1, 1}, [2]int{1, 0}}
+    power := fib
+    for i := 0; i < 9; i++ {
+        power = multiply(power, fib)
+    }
+    print(power[0][1], " ", fib)
+
+    data := [...]int{3, 1, 4, 1, 5, 9, 2, 6}
+    backup := data
+    window := data[2:5]
+    window[0] = 0
+    print(checksum(backup), " ", checksum(data), " ", backup == data)
+}
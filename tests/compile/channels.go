@@ -0,0 +1,46 @@
+package main
+
+func produce(out chan<- int, n int) {
+    for i := 1; i <= n; i++ {
+        out <- i
+    }
+    close(out)
+}
+
+func square(in <-chan int, out chan<- int) {
+    for v := range in {
+        out <- v * v
+    }
+    close(out)
+}
+
+func main() {
+    numbers := make(chan int)
+    squares := make(chan int)
+    go produce(numbers, 5)
+    go square(numbers, squares)
+    for v := range squares {
+        print(v)
+    }
+
+    buffered := make(chan string, 2)
+    buffered <- "first"
+    buffered <- "second"
+    print(len(buffered), " of ", cap(buffered))
+    close(buffered)
+    for {
+        s, ok := <-buffered
+        if !ok {
+            break
+        }
+        print(s)
+    }
+
+    done := make(chan bool)
+    go func() {
+        print("working")
+        done <- true
+    }()
+    <-done
+    print("done")
+}
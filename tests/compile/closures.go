@@ -0,0 +1,48 @@
+package main
+
+func counter() func() int {
+    n := 0
+    return func() int {
+        n++
+        return n
+    }
+}
+
+func apply(f func(int) int, x int) int {
+    return f(x)
+}
+
+func main() {
+    next := counter()
+    next()
+    next()
+    print(next())
+
+    total := 0
+    add := func(n int) {
+        total += n
+    }
+    for i := 1; i <= 4; i++ {
+        add(i)
+    }
+    print(total)
+
+    offset := 10
+    print(apply(func(x int) int { return x + offset }, 5))
+
+    var fib func(int) int
+    fib = func(n int) int {
+        if n < 2 {
+            return n
+        }
+        return fib(n-1) + fib(n-2)
+    }
+    print(fib(10))
+
+    var fs []func() int
+    for i := 0; i < 3; i++ {
+        fs = append(fs, func() int { return i * i })
+    }
+    print(fs[0](), " ", fs[1](), " ", fs[2]())
+    print(func(a, b int) int { return a * b }(6, 7))
+}
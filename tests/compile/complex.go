@@ -0,0 +1,21 @@
+package main
+
+const rotation = 1i
+
+func energy(s []complex128) float64 {
+    var energy float64
+    for _, sample := range s {
+        energy += real(sample)*real(sample) + imag(sample)*imag(sample)
+    }
+    return energy
+}
+
+func main() {
+    s := []complex128{1 + 2i, complex(0.5, -1), 1.5e3i}
+    for i := range s {
+        s[i] *= rotation
+    }
+    var small complex64 = 3 + 4i
+    print(s[0], " ", s[1] == 1i*complex(0.5, -1), " ", energy(s))
+    print(small/2, " ", real(small), " ", imag(small))
+}
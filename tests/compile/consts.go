@@ -0,0 +1,22 @@
+package main
+
+type Weekday int
+
+const (
+    Sunday Weekday = iota
+    Monday
+    Tuesday
+)
+
+const (
+    _  = iota
+    KB = 1 << (10 * iota)
+    MB
+)
+
+const huge = 1 << 100
+const greeting = "hello"
+
+func main() {
+    print(greeting, " ", Tuesday, " ", MB/KB, " ", huge>>98)
+}
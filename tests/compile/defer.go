@@ -0,0 +1,81 @@
+package main
+
+type Stack struct {
+    items []string
+}
+
+func (s *Stack) Push(item string) {
+    s.items = append(s.items, item)
+}
+
+func (s *Stack) Pop() string {
+    item := s.items[len(s.items)-1]
+    s.items = s.items[:len(s.items)-1]
+    return item
+}
+
+func trace(s *Stack, name string) func() {
+    s.Push(name)
+    print("enter", name)
+    return func() {
+        print("leave", s.Pop())
+    }
+}
+
+func nested(s *Stack) {
+    defer trace(s, "outer")()
+    defer trace(s, "inner")()
+    print("body")
+}
+
+func safeDivide(a, b int) (quotient int, err error) {
+    defer func() {
+        if r := recover(); r != nil {
+            err = &DivideError{a}
+        }
+    }()
+    quotient = a / b
+    return
+}
+
+type DivideError struct {
+    dividend int
+}
+
+func (e *DivideError) Error() string {
+    return "cannot divide by zero"
+}
+
+func mustPositive(n int) int {
+    if n < 0 {
+        panic("negative")
+    }
+    return n
+}
+
+func check(n int) (result string) {
+    defer func() {
+        if r := recover(); r != nil {
+            result = "recovered"
+        }
+    }()
+    mustPositive(n)
+    return "ok"
+}
+
+func main() {
+    s := &Stack{}
+    nested(s)
+
+    q, err := safeDivide(10, 2)
+    print(q, err == nil)
+    q, err = safeDivide(1, 0)
+    print(q, err.Error())
+
+    print(check(1))
+    print(check(-1))
+
+    for i := 0; i < 3; i++ {
+        defer print(i)
+    }
+}
@@ -0,0 +1,14 @@
+package main
+
+func add(a, b int) int {
+    return a + b
+}
+
+func describe(n int, label string) (s string) {
+    print(label, n)
+    return label
+}
+
+func main() {
+    describe(add(1, 2), "sum: ")
+}
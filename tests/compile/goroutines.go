@@ -0,0 +1,21 @@
+package main
+
+type Counter struct {
+    n int
+}
+
+func (c *Counter) spin() {
+    for {
+        c.n++
+    }
+}
+
+func main() {
+    c := &Counter{}
+    go c.spin()
+    go func(message string) {
+        for {
+        }
+    }("ignored")
+    print("main returns without waiting for goroutines")
+}
@@ -4,4 +4,4 @@ func main() {
     fmt.Println("hello world")
 }
 
-// err=RuntimeError(UnsupportedFeature(ExecutingFunctions))
+// err=RuntimeError(NameError("fmt"))
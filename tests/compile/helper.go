@@ -0,0 +1,8 @@
+package helper
+
+// Scale is exported to imports.go
+const Scale = 2
+
+func Double(n int) int {
+    return n * Scale
+}
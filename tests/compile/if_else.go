@@ -0,0 +1,19 @@
+package main
+
+func classify(n int) string {
+    if n < 0 {
+        return "negative"
+    } else if n == 0 {
+        return "zero"
+    }
+    return "positive"
+}
+
+func main() {
+    if c := classify(3); c != "positive" {
+        print("wrong: ", c)
+    } else {
+        print(c)
+    }
+    if !(1 > 2) && true { print("ok") }
+}
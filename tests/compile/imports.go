@@ -0,0 +1,7 @@
+package main
+
+import "helper"
+
+func main() {
+    print("doubled: ", helper.Double(21))
+}
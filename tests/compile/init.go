@@ -0,0 +1,28 @@
+package main
+
+var (
+    total = sum(prices)
+    prices = []int{3, 4, 5}
+)
+
+var greeting string
+
+func sum(xs []int) int {
+    s := 0
+    for _, x := range xs {
+        s += x
+    }
+    return s
+}
+
+func init() {
+    greeting = "total"
+}
+
+func init() {
+    greeting = greeting + ":"
+}
+
+func main() {
+    print(greeting, " ", total)
+}
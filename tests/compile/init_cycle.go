@@ -0,0 +1,15 @@
+package main
+
+var a = next()
+
+func next() int {
+    return b + 1
+}
+
+var b = a
+
+func main() {
+    print(a, b)
+}
+
+// err=LinkerError(Loader(AstError(InitializationCycle("a refers to b, b refers to a"))))
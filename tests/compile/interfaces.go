@@ -0,0 +1,54 @@
+package main
+
+type Shape interface {
+    Area() int
+}
+
+type Named interface {
+    Name() string
+}
+
+type NamedShape interface {
+    Shape
+    Named
+}
+
+type Rect struct {
+    w, h int
+}
+
+func (r Rect) Area() int {
+    return r.w * r.h
+}
+
+func (r Rect) Name() string {
+    return "rect"
+}
+
+type Square struct {
+    side int
+}
+
+func (s Square) Area() int {
+    return s.side * s.side
+}
+
+func describe(s Shape) string {
+    if n, ok := s.(Named); ok {
+        return n.Name()
+    }
+    return "shape"
+}
+
+func main() {
+    var shapes NamedShape = Rect{2, 3}
+    print(shapes.Name(), " ", shapes.Area())
+    var s Shape = Square{4}
+    print(describe(s), " ", s.Area())
+    sq := s.(Square)
+    print(sq.side)
+    var e error
+    if e == nil {
+        print("no error")
+    }
+}
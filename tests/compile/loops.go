@@ -0,0 +1,27 @@
+package main
+
+func main() {
+    total := 0
+    for i := 0; i < 5; i++ {
+        total += i
+    }
+    for total > 3 {
+        total -= 3
+    }
+    for {
+        break
+    }
+grid:
+    for row := 0; row < 3; row++ {
+        for col := 0; col < 3; col++ {
+            if col > row {
+                continue grid
+            }
+            if row == 2 {
+                break grid
+            }
+            print(row, col)
+        }
+    }
+    print(total)
+}
@@ -0,0 +1,50 @@
+package main
+
+type Point struct {
+    X, Y int
+}
+
+func wordCounts(words []string) map[string]int {
+    counts := make(map[string]int)
+    for i := range words {
+        counts[words[i]]++
+    }
+    return counts
+}
+
+func main() {
+    counts := wordCounts([]string{"the", "cat", "and", "the", "hat"})
+    print(counts, " ", len(counts))
+
+    if n, ok := counts["the"]; ok {
+        print("the: ", n)
+    }
+    n, ok := counts["dog"]
+    print("dog: ", n, " ", ok)
+
+    delete(counts, "the")
+    total := 0
+    for word, n := range counts {
+        total += n
+        if n == 1 {
+            delete(counts, word)
+        }
+    }
+    print(total, " ", counts)
+
+    grid := map[Point]string{Point{1, 2}: "a"}
+    grid[Point{3, 4}] = "b"
+    print(grid[Point{1, 2}], grid[Point{3, 4}], " ", len(grid))
+
+    var empty map[string]bool
+    print(empty == nil, " ", len(empty), " ", empty["x"])
+
+    sum := 0
+    for i := range 5 {
+        sum += i
+    }
+    for i, r := range "héllo" {
+        sum += i * r
+    }
+    print(sum)
+}
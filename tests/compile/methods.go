@@ -0,0 +1,35 @@
+package main
+
+type Account struct {
+    owner   string
+    balance int
+}
+
+func (a *Account) Deposit(amount int) {
+    a.balance += amount
+}
+
+func (a *Account) Withdraw(amount int) bool {
+    if amount > a.balance {
+        return false
+    }
+    a.balance -= amount
+    return true
+}
+
+func (a Account) Summary() string {
+    if a.balance > 0 {
+        return a.owner + " is in credit"
+    }
+    return a.owner + " is empty"
+}
+
+func main() {
+    acct := Account{owner: "sam"}
+    acct.Deposit(50)
+    if !acct.Withdraw(80) {
+        print("insufficient funds")
+    }
+    acct.Withdraw(50)
+    print(acct.Summary(), " ", acct.balance)
+}
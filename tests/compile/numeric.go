@@ -0,0 +1,27 @@
+package main
+
+type Celsius float64
+
+func (c Celsius) Fahrenheit() float64 {
+    return float64(c*9/5 + 32)
+}
+
+func checksum(data []byte) uint8 {
+    var sum uint8
+    for _, b := range data {
+        sum += b
+    }
+    return sum
+}
+
+func main() {
+    var counter int8 = 120
+    for i := 0; i < 10; i++ {
+        counter++
+    }
+    var f float32 = 1.0 / 3
+    data := []byte{200, 100, 0x10}
+    wide := 70000
+    print(counter, " ", f, " ", 1e100/3, " ", Celsius(37).Fahrenheit())
+    print(checksum(data), " ", uint16(wide), " ", int(-2.75*4))
+}
@@ -0,0 +1,48 @@
+package main
+
+type Node struct {
+    Value int
+    Next  *Node
+}
+
+func push(head *Node, value int) *Node {
+    return &Node{Value: value, Next: head}
+}
+
+func sum(list *Node) int {
+    total := 0
+    for n := list; n != nil; n = n.Next {
+        total += n.Value
+    }
+    return total
+}
+
+func swap(a, b *int) {
+    t := *a
+    *a = *b
+    *b = t
+}
+
+func main() {
+    var list *Node
+    for i := 1; i <= 4; i++ {
+        list = push(list, i)
+    }
+    print(sum(list), " ", list.Next.Value)
+
+    x, y := 1, 2
+    swap(&x, &y)
+    print(x, " ", y)
+
+    counter := new(int)
+    *counter++
+    *counter += 10
+    p := &counter
+    **p *= 2
+    print(*counter)
+
+    s := []int{1, 2, 3}
+    second := &s[1]
+    *second = 20
+    print(s)
+}
@@ -0,0 +1,50 @@
+package main
+
+type ParseError struct {
+    input string
+}
+
+func (e *ParseError) Error() string {
+    return "invalid digit in " + e.input
+}
+
+func digit(s string) (int, error) {
+    digits := map[string]int{"0": 0, "1": 1, "2": 2, "3": 3, "4": 4, "5": 5, "6": 6, "7": 7, "8": 8, "9": 9}
+    d, ok := digits[s]
+    if !ok {
+        return 0, &ParseError{s}
+    }
+    return d, nil
+}
+
+func divmod(a, b int) (quotient, remainder int) {
+    quotient = a / b
+    remainder = a % b
+    return
+}
+
+func sum(a, b int) int {
+    return a + b
+}
+
+func main() {
+    if d, err := digit("7"); err == nil {
+        print(d)
+    }
+    if _, err := digit("x"); err != nil {
+        print(err.Error())
+    }
+
+    q, r := divmod(17, 5)
+    print(q, " ", r)
+    q, r = r, q
+    print(q, " ", r)
+    print(sum(divmod(23, 4)))
+
+    total := 0
+    for _, n := range []int{1, 2, 3} {
+        total += n
+    }
+    _ = total
+    print(total)
+}
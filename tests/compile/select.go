@@ -0,0 +1,46 @@
+package main
+
+func worker(jobs <-chan int, results chan<- int, quit <-chan bool) {
+    for {
+        select {
+        case job := <-jobs:
+            results <- job * 10
+        case <-quit:
+            return
+        }
+    }
+}
+
+func main() {
+    jobs := make(chan int)
+    results := make(chan int)
+    quit := make(chan bool)
+    go worker(jobs, results, quit)
+    for i := 1; i <= 3; i++ {
+        jobs <- i
+        print(<-results)
+    }
+    close(quit)
+
+    // A nil channel is never ready, so only the default clause can run
+    var never chan int
+    select {
+    case v := <-never:
+        print("received ", v)
+    case never <- 1:
+        print("sent")
+    default:
+        print("nothing ready")
+    }
+
+    timeout := make(chan bool)
+    go func() {
+        close(timeout)
+    }()
+    select {
+    case v := <-results:
+        print("unexpected result ", v)
+    case <-timeout:
+        print("timed out")
+    }
+}
@@ -0,0 +1,32 @@
+package main
+
+func sum(values []int) int {
+    total := 0
+    for i := 0; i < len(values); i++ {
+        total += values[i]
+    }
+    return total
+}
+
+func main() {
+    primes := []int{2, 3, 5, 7, 11}
+    print(sum(primes[1:4]))
+
+    var squares []int
+    for i := 1; i <= 5; i++ {
+        squares = append(squares, i*i)
+    }
+    print(squares, " ", len(squares), " ", cap(squares))
+
+    window := squares[1:3:3]
+    window = append(window, 100)
+    window[0] = -1
+    print(squares[1], " ", window)
+
+    buffer := make([]int, 3, 10)
+    n := copy(buffer, primes)
+    print(n, " ", buffer)
+
+    greeting := "hello, world"
+    print(greeting[7:], " ", greeting[0])
+}
@@ -0,0 +1,31 @@
+package main
+
+type Point struct {
+    x, y int
+}
+
+type (
+    Label = string
+    Shape struct {
+        name   Label
+        origin Point
+        size   int
+    }
+)
+
+func area(s Shape) int {
+    return s.size * s.size
+}
+
+func main() {
+    var s Shape
+    s.name = "square"
+    s.origin.x = 3
+    s.size = 4
+    t := s
+    t.origin = Point{y: 1}
+    print(s.name, " ", s.origin, " ", t.origin, " ", area(s))
+    if s.origin == (Point{3, 0}) {
+        print("moved")
+    }
+}
@@ -0,0 +1,29 @@
+package main
+
+func classify(n int) string {
+    switch {
+    case n < 0:
+        return "negative"
+    case n == 0:
+        return "zero"
+    }
+    return "positive"
+}
+
+func main() {
+    for i := 0; i < 4; i++ {
+        switch i {
+        case 0:
+            print("zero")
+            fallthrough
+        case 1, 2:
+            print("small")
+        default:
+            print("other")
+        }
+    }
+    switch x := 3; x {
+    case 3:
+        print(classify(-x))
+    }
+}
@@ -0,0 +1,39 @@
+package main
+
+type Shape interface {
+    Area() int
+}
+
+type Square struct {
+    side int
+}
+
+func (s Square) Area() int {
+    return s.side * s.side
+}
+
+func describe(x any) string {
+    switch v := x.(type) {
+    case nil:
+        return "nothing"
+    case int, bool:
+        return "a number or a flag"
+    case string:
+        return "the string " + v
+    case Shape:
+        if v.Area() > 100 {
+            return "a big shape"
+        }
+        return "a shape"
+    default:
+        return "something else"
+    }
+}
+
+func main() {
+    print(describe(nil))
+    print(describe(3))
+    print(describe("go"))
+    print(describe(Square{2}))
+    print(describe(Square{20}))
+}
@@ -0,0 +1,16 @@
+package main
+
+func main() {
+    var greeting string = "hello"
+    var count = 3
+    var (
+        total int
+        done  bool
+    )
+    total += count
+    total <<= 1
+    total--
+    name, other := "world", "!"
+    name, other = other, name
+    print(greeting, " ", other, name, total, done)
+}
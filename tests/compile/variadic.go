@@ -0,0 +1,40 @@
+package main
+
+func sum(label string, xs ...int) string {
+    total := 0
+    for _, x := range xs {
+        total += x
+    }
+    if xs == nil {
+        return label + ": nothing"
+    }
+    return label + ": " + digits(total)
+}
+
+func digits(n int) string {
+    names := []string{"0", "1", "2", "3", "4", "5", "6", "7", "8", "9"}
+    if n < 10 {
+        return names[n]
+    }
+    return digits(n/10) + names[n%10]
+}
+
+func main() {
+    print(sum("none"))
+    print(sum("some", 1, 2, 3))
+    values := []int{10, 20, 30}
+    print(sum("spread", values...))
+
+    words := []string{"a", "b"}
+    words = append(words, []string{"c", "d"}...)
+    print(len(words), " ", words[3])
+
+    join := func(parts ...string) string {
+        out := ""
+        for _, p := range parts {
+            out += p
+        }
+        return out
+    }
+    print(join(words...), " ", join())
+}